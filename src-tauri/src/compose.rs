@@ -0,0 +1,1110 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+mod build;
+mod export;
+mod interpolation;
+mod merge;
+mod plan;
+mod ports;
+mod replicas;
+mod secrets;
+mod settings;
+mod volumes;
+
+pub use build::{tar_context, ComposeBuild};
+pub use export::{generate_compose, ContainerSnapshot, HealthcheckSnapshot, MountSnapshot, ProcessConfig};
+pub use interpolation::{interpolate_document, parse_env_file, InterpolationIssues};
+pub use merge::merge_compose_documents;
+pub use plan::{config_hash, diff_specs, ContainerSpec, PlanAction};
+pub use ports::{parse_port, ComposePort};
+pub use replicas::{check_scalable, replica_name, scale_numbers, ComposeDeploy};
+pub use secrets::{ownership_warnings, resolve_file_mounts, ComposeFileObject, FileObjectKind, ServiceFileReference};
+pub use settings::{format_duration, parse_duration, parse_duration_secs, split_shell_words, ComposeHealthcheck, ComposeLogging, ComposeUlimit, StringOrList};
+pub use volumes::{parse_volume, resolve_host_path, ComposeServiceVolume, MountKind};
+
+// Docker Compose structures
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeService {
+    pub image: Option<String>,
+    pub build: Option<ComposeBuild>,
+    pub container_name: Option<String>,
+    pub environment: Option<MappingOrList>,
+    pub ports: Option<Vec<ComposePort>>,
+    pub volumes: Option<Vec<ComposeServiceVolume>>,
+    pub networks: Option<ServiceNetworks>,
+    /// `host`, `none`, `bridge`, `container:<name>` or `service:<name>`, in place of `networks`
+    pub network_mode: Option<String>,
+    pub restart: Option<String>,
+    pub command: Option<serde_yaml::Value>,
+    pub mem_limit: Option<String>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub cpus: Option<f64>,
+    pub depends_on: Option<DependsOn>,
+    pub env_file: Option<EnvFiles>,
+    pub profiles: Option<Vec<String>>,
+    pub healthcheck: Option<ComposeHealthcheck>,
+    pub labels: Option<MappingOrList>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub user: Option<String>,
+    pub working_dir: Option<String>,
+    pub entrypoint: Option<serde_yaml::Value>,
+    pub hostname: Option<String>,
+    pub extra_hosts: Option<MappingOrList>,
+    pub dns: Option<StringOrList>,
+    pub cap_add: Option<Vec<String>>,
+    pub cap_drop: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub privileged: Option<bool>,
+    pub devices: Option<Vec<String>>,
+    pub tmpfs: Option<StringOrList>,
+    pub ulimits: Option<BTreeMap<String, ComposeUlimit>>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub shm_size: Option<String>,
+    pub stop_signal: Option<String>,
+    pub stop_grace_period: Option<String>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub init: Option<bool>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub read_only: Option<bool>,
+    pub logging: Option<ComposeLogging>,
+    pub deploy: Option<ComposeDeploy>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub scale: Option<u32>,
+    pub secrets: Option<Vec<ServiceFileReference>>,
+    pub configs: Option<Vec<ServiceFileReference>>,
+    /// Keys this model doesn't know, kept so they can be reported instead of silently dropped
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_yaml::Value>,
+}
+
+/// Service `networks` is a list of network keys or a map with per-network settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ServiceNetworks {
+    List(Vec<String>),
+    Map(BTreeMap<String, Option<ServiceNetworkConfig>>),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServiceNetworkConfig {
+    pub aliases: Option<Vec<String>>,
+    pub ipv4_address: Option<String>,
+    pub ipv6_address: Option<String>,
+}
+
+impl ServiceNetworks {
+    pub fn names(&self) -> Vec<String> {
+        match self {
+            ServiceNetworks::List(names) => names.clone(),
+            ServiceNetworks::Map(map) => map.keys().cloned().collect(),
+        }
+    }
+
+    pub fn config(&self, key: &str) -> Option<&ServiceNetworkConfig> {
+        match self {
+            ServiceNetworks::List(_) => None,
+            ServiceNetworks::Map(map) => map.get(key).and_then(|config| config.as_ref()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeFile {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub services: HashMap<String, ComposeService>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub networks: HashMap<String, Option<ComposeNetwork>>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub volumes: HashMap<String, Option<ComposeVolume>>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub secrets: HashMap<String, Option<ComposeFileObject>>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub configs: HashMap<String, Option<ComposeFileObject>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeNetwork {
+    pub name: Option<String>,
+    pub driver: Option<String>,
+    pub driver_opts: Option<HashMap<String, String>>,
+    pub external: Option<External>,
+    pub internal: Option<bool>,
+    pub attachable: Option<bool>,
+    pub ipam: Option<ComposeIpam>,
+    pub labels: Option<MappingOrList>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeIpam {
+    pub driver: Option<String>,
+    pub config: Option<Vec<ComposeIpamPool>>,
+    pub options: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeIpamPool {
+    pub subnet: Option<String>,
+    pub ip_range: Option<String>,
+    pub gateway: Option<String>,
+    pub aux_addresses: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeVolume {
+    pub name: Option<String>,
+    pub driver: Option<String>,
+    pub driver_opts: Option<HashMap<String, String>>,
+    pub external: Option<External>,
+    pub labels: Option<MappingOrList>,
+}
+
+/// `env_file` is a single path, a list of paths, or a list of `{path, required}` entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnvFiles {
+    Single(String),
+    List(Vec<EnvFileEntry>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnvFileEntry {
+    Path(String),
+    Detailed {
+        path: String,
+        #[serde(default = "default_true")]
+        required: bool,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+// Interpolated values are always strings, so numeric keys also accept their string form
+fn number_or_string<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr + Deserialize<'de>,
+    T::Err: std::fmt::Display,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw<T> {
+        Value(T),
+        Text(String),
+    }
+
+    match Option::<Raw<T>>::deserialize(deserializer)? {
+        Some(Raw::Value(value)) => Ok(Some(value)),
+        Some(Raw::Text(text)) => text.trim().parse().map(Some).map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+// Keys such as `user` may be written as a number (`user: 1000`)
+fn scalar_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_yaml::Value>::deserialize(deserializer)?;
+    match value {
+        None | Some(serde_yaml::Value::Null) => Ok(None),
+        Some(value) => yaml_scalar_to_string(&value)
+            .map(Some)
+            .ok_or_else(|| serde::de::Error::custom("expected a string or a number")),
+    }
+}
+
+/// `external` is either a boolean or, in the legacy syntax, a map carrying the real resource name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum External {
+    Flag(bool),
+    Named { name: String },
+}
+
+/// Keys such as `labels` may be written as a `KEY=value` list or as a map
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MappingOrList {
+    List(Vec<String>),
+    Map(BTreeMap<String, Option<serde_yaml::Value>>),
+}
+
+impl MappingOrList {
+    /// Entries in file order; keys listed without a value map to `None`
+    pub fn entries(&self) -> Vec<(String, Option<String>)> {
+        match self {
+            MappingOrList::List(items) => items
+                .iter()
+                .map(|item| match item.split_once('=') {
+                    Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                    None => (item.clone(), None),
+                })
+                .collect(),
+            MappingOrList::Map(map) => map
+                .iter()
+                .map(|(key, value)| (key.clone(), value.as_ref().and_then(yaml_scalar_to_string)))
+                .collect(),
+        }
+    }
+
+    /// Fills in keys listed without a value from `vars`, dropping those that are not set
+    pub fn resolve(&self, vars: &HashMap<String, String>) -> MappingOrList {
+        let resolved = self
+            .entries()
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let value = value.or_else(|| vars.get(&key).cloned())?;
+                Some((key, Some(serde_yaml::Value::String(value))))
+            })
+            .collect();
+        MappingOrList::Map(resolved)
+    }
+
+    pub fn to_map(&self) -> HashMap<String, String> {
+        match self {
+            MappingOrList::List(items) => items
+                .iter()
+                .map(|item| match item.split_once('=') {
+                    Some((key, value)) => (key.to_string(), value.to_string()),
+                    None => (item.clone(), String::new()),
+                })
+                .collect(),
+            MappingOrList::Map(map) => map
+                .iter()
+                .map(|(key, value)| {
+                    let value = value.as_ref().and_then(yaml_scalar_to_string).unwrap_or_default();
+                    (key.clone(), value)
+                })
+                .collect(),
+        }
+    }
+}
+
+// Render a scalar YAML value (string, number or bool) the way compose does
+pub fn yaml_scalar_to_string(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(s) => Some(s.clone()),
+        serde_yaml::Value::Number(n) => Some(n.to_string()),
+        serde_yaml::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+impl ComposeNetwork {
+    pub fn is_external(&self) -> bool {
+        matches!(self.external, Some(External::Flag(true)) | Some(External::Named { .. }))
+    }
+}
+
+impl ComposeVolume {
+    pub fn is_external(&self) -> bool {
+        matches!(self.external, Some(External::Flag(true)) | Some(External::Named { .. }))
+    }
+}
+
+/// The project name the compose CLI derives from a project directory: its base name in lowercase,
+/// without the characters a project name can't hold and without leading `-` and `_`
+pub fn project_name_from_dir(dir: &Path) -> Option<String> {
+    let base = dir.file_name()?.to_string_lossy().to_lowercase();
+    let name: String = base.chars().filter(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '-' || *c == '_').collect();
+    let name = name.trim_start_matches(['-', '_']);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+impl ComposeFile {
+    /// Loading always settles the name, from `name:`, the chosen project name or the project directory
+    pub fn project_name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+
+    /// Declares the implicit `default` network when some service doesn't list any network
+    pub fn add_default_network(&mut self) {
+        let needs_default = self.services.values()
+            .any(|service| service.network_mode.is_none() && service.networks.as_ref().is_none_or(|n| n.names().is_empty()));
+        if needs_default {
+            self.networks.entry(DEFAULT_NETWORK.to_string()).or_insert(None);
+        }
+    }
+
+    /// Resolves a network key used in the file to the name of the network on the daemon
+    pub fn network_name(&self, key: &str) -> String {
+        match self.networks.get(key) {
+            Some(Some(ComposeNetwork { external: Some(External::Named { name }), .. })) => name.clone(),
+            Some(Some(ComposeNetwork { name: Some(name), .. })) => name.clone(),
+            Some(Some(network)) if network.is_external() => key.to_string(),
+            // Undeclared networks such as `bridge` refer to existing daemon networks
+            None if key != DEFAULT_NETWORK => key.to_string(),
+            _ => format!("{}_{}", self.project_name(), key),
+        }
+    }
+
+    /// The networks a service joins, as daemon network names, with the aliases and static addresses
+    /// it has on each. The service name is an alias everywhere. The container is created on the
+    /// first network and connected to the others.
+    pub fn service_endpoints(&self, service_name: &str, service: &ComposeService) -> Vec<(String, ServiceNetworkConfig)> {
+        let keys = service.networks.as_ref()
+            .map(|networks| networks.names())
+            .filter(|names| !names.is_empty())
+            .unwrap_or_else(|| vec![DEFAULT_NETWORK.to_string()]);
+        keys.into_iter()
+            .map(|key| {
+                let config = service.networks.as_ref().and_then(|n| n.config(&key)).cloned().unwrap_or_default();
+                let mut aliases = vec![service_name.to_string()];
+                aliases.extend(config.aliases.unwrap_or_default());
+                (self.network_name(&key), ServiceNetworkConfig { aliases: Some(aliases), ..config })
+            })
+            .collect()
+    }
+
+    /// The `network_mode` of a service as the daemon takes it; `service:<name>` joins the
+    /// first container of that service
+    pub fn network_mode(&self, service: &ComposeService) -> Result<Option<String>, String> {
+        let Some(mode) = &service.network_mode else {
+            return Ok(None);
+        };
+        if service.networks.as_ref().is_some_and(|n| !n.names().is_empty()) {
+            return Err("network_mode and networks cannot be used together".to_string());
+        }
+        match mode.strip_prefix("service:") {
+            Some(name) => {
+                let target = self.services.get(name)
+                    .ok_or_else(|| format!("network_mode refers to unknown service '{}'", name))?;
+                let container = target.container_name.clone()
+                    .unwrap_or_else(|| replica_name(self.project_name(), name, 1));
+                Ok(Some(format!("container:{}", container)))
+            }
+            None => Ok(Some(mode.clone())),
+        }
+    }
+
+    /// Resolves a volume key used in the file to the name of the volume on the daemon
+    pub fn volume_name(&self, key: &str) -> String {
+        match self.volumes.get(key) {
+            Some(Some(ComposeVolume { external: Some(External::Named { name }), .. })) => name.clone(),
+            Some(Some(ComposeVolume { name: Some(name), .. })) => name.clone(),
+            Some(Some(volume)) if volume.is_external() => key.to_string(),
+            None => key.to_string(),
+            _ => format!("{}_{}", self.project_name(), key),
+        }
+    }
+
+    /// Drops the services whose profiles are all inactive. Services without profiles are always enabled
+    /// and `*` enables every profile.
+    pub fn apply_profiles(&mut self, active: &[String]) -> Result<(), String> {
+        let all = active.iter().any(|p| p == "*");
+        self.services.retain(|_, service| match &service.profiles {
+            Some(profiles) if !profiles.is_empty() => all || profiles.iter().any(|p| active.contains(p)),
+            _ => true,
+        });
+
+        let mut names: Vec<&String> = self.services.keys().collect();
+        names.sort();
+        for name in names {
+            for (dependency, _) in self.services[name].dependencies() {
+                if !self.services.contains_key(&dependency) {
+                    return Err(format!(
+                        "Service '{}' depends on '{}', which is not enabled by the active profiles",
+                        name, dependency
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the normalized model as YAML, like `docker compose config`
+    pub fn render(&self) -> Result<String, String> {
+        let value = serde_yaml::to_value(self).map_err(|e| format!("Failed to render compose file: {}", e))?;
+        serde_yaml::to_string(&normalize_rendered(value)).map_err(|e| format!("Failed to render compose file: {}", e))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DependsOn {
+    List(Vec<String>),
+    Map(BTreeMap<String, Option<ServiceDependency>>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceDependency {
+    #[serde(default)]
+    pub condition: DependencyCondition,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DependencyCondition {
+    #[default]
+    #[serde(rename = "service_started")]
+    Started,
+    #[serde(rename = "service_healthy")]
+    Healthy,
+    #[serde(rename = "service_completed_successfully")]
+    CompletedSuccessfully,
+}
+
+impl ComposeService {
+    /// Reads the variables of the service's `env_file` entries in order, resolving relative paths against `base_dir`
+    pub fn read_env_files(&self, base_dir: Option<&Path>) -> Result<Vec<(String, String)>, String> {
+        let entries: Vec<(String, bool)> = match &self.env_file {
+            Some(EnvFiles::Single(path)) => vec![(path.clone(), true)],
+            Some(EnvFiles::List(entries)) => entries
+                .iter()
+                .map(|entry| match entry {
+                    EnvFileEntry::Path(path) => (path.clone(), true),
+                    EnvFileEntry::Detailed { path, required } => (path.clone(), *required),
+                })
+                .collect(),
+            None => return Ok(Vec::new()),
+        };
+
+        let mut vars = Vec::new();
+        for (path, required) in entries {
+            let full_path = match base_dir {
+                Some(dir) => dir.join(&path),
+                None if Path::new(&path).is_absolute() => Path::new(&path).to_path_buf(),
+                None => return Err(format!("env_file '{}' is relative but no project directory was given", path)),
+            };
+            match std::fs::read_to_string(&full_path) {
+                Ok(content) => vars.extend(parse_env_file(&content)),
+                Err(_) if !required => {}
+                Err(e) => return Err(format!("Failed to read env_file '{}': {}", path, e)),
+            }
+        }
+
+        Ok(vars)
+    }
+
+    /// Image the service runs: `image`, or the name compose gives to images it builds
+    pub fn image_name(&self, project: &str, service_name: &str) -> Option<String> {
+        match (&self.image, &self.build) {
+            (Some(image), _) => Some(image.clone()),
+            (None, Some(_)) => Some(format!("{}-{}", project, service_name)),
+            (None, None) => None,
+        }
+    }
+
+    /// Number of containers to run: `deploy.replicas`, else `scale`, else one
+    pub fn replicas(&self) -> u32 {
+        self.deploy.as_ref()
+            .and_then(|d| d.replicas)
+            .or(self.scale)
+            .unwrap_or(1)
+    }
+
+    /// Top-level keys of the service that are not supported; `x-` extension keys are allowed anywhere
+    pub fn unknown_keys(&self) -> Vec<String> {
+        self.other.keys().filter(|key| !key.starts_with("x-")).cloned().collect()
+    }
+
+    /// Parts of the service that are accepted but can't be applied
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = self.unknown_keys()
+            .into_iter()
+            .map(|key| format!("Unsupported key '{}' was ignored", key))
+            .collect();
+        warnings.extend(ownership_warnings(FileObjectKind::Secret, self.secrets.as_deref().unwrap_or_default()));
+        warnings.extend(ownership_warnings(FileObjectKind::Config, self.configs.as_deref().unwrap_or_default()));
+        warnings
+    }
+
+    /// `command` as an argument list; the string form is split like a shell would
+    pub fn command_args(&self) -> Result<Option<Vec<String>>, String> {
+        command_value_args(self.command.as_ref())
+    }
+
+    /// `entrypoint` as an argument list; the string form is split like a shell would
+    pub fn entrypoint_args(&self) -> Result<Option<Vec<String>>, String> {
+        command_value_args(self.entrypoint.as_ref())
+    }
+
+    /// `extra_hosts` in the `host:ip` form the Docker API expects
+    pub fn extra_hosts_list(&self) -> Vec<String> {
+        match &self.extra_hosts {
+            Some(MappingOrList::List(items)) => items
+                .iter()
+                .map(|item| match item.split_once('=') {
+                    Some((host, ip)) => format!("{}:{}", host, ip),
+                    None => item.clone(),
+                })
+                .collect(),
+            Some(map) => map
+                .entries()
+                .into_iter()
+                .map(|(host, ip)| format!("{}:{}", host, ip.unwrap_or_default()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the `environment` entries, in file order
+    pub fn environment_vars(&self) -> Vec<(String, String)> {
+        self.environment
+            .as_ref()
+            .map(|env| {
+                env.entries()
+                    .into_iter()
+                    .map(|(key, value)| (key, value.unwrap_or_default()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the services this one depends on together with the condition to wait for
+    pub fn dependencies(&self) -> Vec<(String, DependencyCondition)> {
+        let mut dependencies = match &self.depends_on {
+            Some(DependsOn::List(names)) => names
+                .iter()
+                .map(|name| (name.clone(), DependencyCondition::Started))
+                .collect(),
+            Some(DependsOn::Map(map)) => map
+                .iter()
+                .map(|(name, dep)| (name.clone(), dep.as_ref().map(|d| d.condition).unwrap_or_default()))
+                .collect(),
+            None => Vec::new(),
+        };
+        // A service whose network it joins has to start first
+        if let Some(name) = self.network_mode.as_deref().and_then(|mode| mode.strip_prefix("service:")) {
+            if !dependencies.iter().any(|(dependency, _)| dependency == name) {
+                dependencies.push((name.to_string(), DependencyCondition::Started));
+            }
+        }
+        dependencies
+    }
+}
+
+/// Parses a compose file together with its override files, merged in order, and interpolates variables from `vars`.
+/// Returns the interpolation issues of each service alongside the parsed file.
+pub fn load(documents: &[&str], vars: &HashMap<String, String>) -> Result<(ComposeFile, BTreeMap<String, InterpolationIssues>), String> {
+    let mut document = serde_yaml::Value::Null;
+    for (index, yaml) in documents.iter().enumerate() {
+        let overlay: serde_yaml::Value = serde_yaml::from_str(yaml).map_err(|e| match index {
+            0 => format!("Failed to parse compose file: {}", e),
+            _ => format!("Failed to parse override file {}: {}", index, e),
+        })?;
+        document = merge_compose_documents(document, overlay);
+    }
+    let interpolated = interpolate_document(document, vars)?;
+    let mut file: ComposeFile = serde_yaml::from_value(interpolated.document)
+        .map_err(|e| format!("Failed to parse compose file: {}", e))?;
+
+    // `environment` keys and build args without a value take it from the variables, and are dropped when it is unset
+    for service in file.services.values_mut() {
+        if let Some(environment) = &service.environment {
+            service.environment = Some(environment.resolve(vars));
+        }
+        if let Some(ComposeBuild::Detailed(build)) = &mut service.build {
+            if let Some(args) = &build.args {
+                build.args = Some(args.resolve(vars));
+            }
+        }
+    }
+
+    Ok((file, interpolated.services))
+}
+
+// Drop unset keys and sort mappings so the rendered file is stable
+fn normalize_rendered(value: serde_yaml::Value) -> serde_yaml::Value {
+    match value {
+        serde_yaml::Value::Mapping(map) => {
+            let mut entries: Vec<(serde_yaml::Value, serde_yaml::Value)> = map
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, normalize_rendered(value)))
+                .collect();
+            entries.sort_by(|(a, _), (b, _)| a.as_str().cmp(&b.as_str()));
+            serde_yaml::Value::Mapping(entries.into_iter().collect())
+        }
+        serde_yaml::Value::Sequence(items) => serde_yaml::Value::Sequence(items.into_iter().map(normalize_rendered).collect()),
+        other => other,
+    }
+}
+
+fn command_value_args(value: Option<&serde_yaml::Value>) -> Result<Option<Vec<String>>, String> {
+    match value {
+        None | Some(serde_yaml::Value::Null) => Ok(None),
+        Some(serde_yaml::Value::String(command)) => split_shell_words(command).map(Some),
+        Some(serde_yaml::Value::Sequence(items)) => items
+            .iter()
+            .map(|item| yaml_scalar_to_string(item).ok_or_else(|| "Command arguments must be strings".to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some),
+        Some(_) => Err("Command must be a string or a list".to_string()),
+    }
+}
+
+/// Orders services so that every service comes after the services it depends on.
+/// Services without a dependency relation are ordered by name to keep deployments reproducible.
+pub fn deployment_order(services: &HashMap<String, ComposeService>) -> Result<Vec<String>, String> {
+    let mut graph = BTreeMap::new();
+    for (name, service) in services {
+        let mut dependencies = Vec::new();
+        for (dependency, _) in service.dependencies() {
+            if !services.contains_key(&dependency) {
+                return Err(format!("Service '{}' depends on undefined service '{}'", name, dependency));
+            }
+            dependencies.push(dependency);
+        }
+        graph.insert(name.clone(), dependencies);
+    }
+
+    topological_order(&graph)
+}
+
+/// Topologically sorts a dependency graph (node -> nodes it depends on).
+/// Dependencies that are not nodes of the graph are ignored.
+pub fn topological_order(graph: &BTreeMap<String, Vec<String>>) -> Result<Vec<String>, String> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Visiting,
+        Done,
+    }
+
+    fn visit(
+        name: &str,
+        graph: &BTreeMap<String, Vec<String>>,
+        marks: &mut HashMap<String, Mark>,
+        path: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), String> {
+        match marks.get(name) {
+            Some(Mark::Done) => return Ok(()),
+            Some(Mark::Visiting) => {
+                let start = path.iter().position(|p| p == name).unwrap_or(0);
+                let mut cycle: Vec<&str> = path[start..].iter().map(|s| s.as_str()).collect();
+                cycle.push(name);
+                return Err(format!("Circular dependency between services: {}", cycle.join(" -> ")));
+            }
+            None => {}
+        }
+
+        marks.insert(name.to_string(), Mark::Visiting);
+        path.push(name.to_string());
+
+        let mut dependencies: Vec<&String> = graph[name].iter().filter(|d| graph.contains_key(*d)).collect();
+        dependencies.sort();
+        for dependency in dependencies {
+            visit(dependency, graph, marks, path, order)?;
+        }
+
+        path.pop();
+        marks.insert(name.to_string(), Mark::Done);
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    let mut marks = HashMap::new();
+    let mut order = Vec::with_capacity(graph.len());
+    for name in graph.keys() {
+        visit(name, graph, &mut marks, &mut Vec::new(), &mut order)?;
+    }
+
+    Ok(order)
+}
+
+// Labels shared with the docker compose CLI so projects are interchangeable between the two
+pub const PROJECT_LABEL: &str = "com.docker.compose.project";
+pub const SERVICE_LABEL: &str = "com.docker.compose.service";
+pub const CONTAINER_NUMBER_LABEL: &str = "com.docker.compose.container-number";
+pub const ONEOFF_LABEL: &str = "com.docker.compose.oneoff";
+pub const DEPENDS_ON_LABEL: &str = "com.docker.compose.depends_on";
+pub const CONFIG_HASH_LABEL: &str = "com.docker.compose.config-hash";
+pub const NETWORK_LABEL: &str = "com.docker.compose.network";
+pub const VOLUME_LABEL: &str = "com.docker.compose.volume";
+pub const CONFIG_FILES_LABEL: &str = "com.docker.compose.project.config_files";
+
+/// Network that services without an explicit `networks` entry are attached to
+pub const DEFAULT_NETWORK: &str = "default";
+
+impl DependencyCondition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DependencyCondition::Started => "service_started",
+            DependencyCondition::Healthy => "service_healthy",
+            DependencyCondition::CompletedSuccessfully => "service_completed_successfully",
+        }
+    }
+}
+
+/// Encodes `depends_on` the way the compose CLI stores it on containers (`db:service_healthy:false,...`)
+pub fn depends_on_label(service: &ComposeService) -> String {
+    service
+        .dependencies()
+        .iter()
+        .map(|(name, condition)| format!("{}:{}:false", name, condition.as_str()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Extracts the service names from a `com.docker.compose.depends_on` label
+pub fn parse_depends_on_label(label: &str) -> Vec<String> {
+    label
+        .split(',')
+        .filter_map(|entry| entry.split(':').next())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+// Parse memory limit string (e.g., "512m", "1g", "64mb") to bytes
+pub fn parse_memory_limit(limit_str: &str) -> Option<i64> {
+    let limit_str = limit_str.trim().to_lowercase();
+    let limit_str = match limit_str.strip_suffix('b') {
+        Some(stripped) if stripped.ends_with(['k', 'm', 'g']) => stripped.to_string(),
+        _ => limit_str,
+    };
+
+    if let Some(stripped) = limit_str.strip_suffix('g') {
+        stripped.parse::<i64>().ok().map(|v| v * 1024 * 1024 * 1024)
+    } else if let Some(stripped) = limit_str.strip_suffix('m') {
+        stripped.parse::<i64>().ok().map(|v| v * 1024 * 1024)
+    } else if let Some(stripped) = limit_str.strip_suffix('k') {
+        stripped.parse::<i64>().ok().map(|v| v * 1024)
+    } else {
+        limit_str.parse::<i64>().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> ComposeFile {
+        serde_yaml::from_str(yaml).expect("valid compose file")
+    }
+
+    #[test]
+    fn test_depends_on_list_and_map() {
+        let compose = parse(
+            r#"
+services:
+  web:
+    image: nginx
+    depends_on: [api]
+  api:
+    image: api
+    depends_on:
+      db:
+        condition: service_healthy
+      migrate:
+        condition: service_completed_successfully
+  db:
+    image: postgres
+  migrate:
+    image: api
+    depends_on:
+      db: {}
+"#,
+        );
+
+        assert_eq!(compose.services["web"].dependencies(), vec![("api".to_string(), DependencyCondition::Started)]);
+        assert_eq!(
+            compose.services["api"].dependencies(),
+            vec![
+                ("db".to_string(), DependencyCondition::Healthy),
+                ("migrate".to_string(), DependencyCondition::CompletedSuccessfully),
+            ]
+        );
+        assert_eq!(compose.services["migrate"].dependencies(), vec![("db".to_string(), DependencyCondition::Started)]);
+    }
+
+    #[test]
+    fn test_deployment_order() {
+        let compose = parse(
+            r#"
+services:
+  web: { image: nginx, depends_on: [api] }
+  api: { image: api, depends_on: [db, cache] }
+  cache: { image: redis }
+  db: { image: postgres }
+"#,
+        );
+
+        assert_eq!(deployment_order(&compose.services).unwrap(), vec!["cache", "db", "api", "web"]);
+    }
+
+    #[test]
+    fn test_deployment_order_cycle() {
+        let compose = parse(
+            r#"
+services:
+  a: { image: x, depends_on: [b] }
+  b: { image: x, depends_on: [c] }
+  c: { image: x, depends_on: [a] }
+"#,
+        );
+
+        let err = deployment_order(&compose.services).unwrap_err();
+        assert_eq!(err, "Circular dependency between services: a -> b -> c -> a");
+    }
+
+    #[test]
+    fn test_top_level_networks_and_volumes() {
+        let compose = parse(
+            r#"
+name: shop
+services:
+  app: { image: app, networks: [backend], volumes: ["data:/data"] }
+networks:
+  backend:
+    driver: bridge
+    ipam:
+      config:
+        - subnet: 172.28.0.0/16
+    labels: [tier=backend]
+  shared:
+    external: true
+    name: infra_shared
+  legacy:
+    external:
+      name: old_net
+  plain:
+volumes:
+  data:
+    name: app-data
+    labels:
+      backup: true
+  cache:
+"#,
+        );
+
+        assert_eq!(compose.network_name("backend"), "shop_backend");
+        assert_eq!(compose.network_name("shared"), "infra_shared");
+        assert_eq!(compose.network_name("legacy"), "old_net");
+        assert_eq!(compose.network_name("default"), "shop_default");
+        assert_eq!(compose.network_name("bridge"), "bridge");
+        assert!(compose.networks["shared"].as_ref().unwrap().is_external());
+        assert!(compose.networks["legacy"].as_ref().unwrap().is_external());
+        assert!(compose.networks["plain"].is_none());
+
+        let backend = compose.networks["backend"].as_ref().unwrap();
+        assert_eq!(backend.labels.as_ref().unwrap().to_map()["tier"], "backend");
+        assert_eq!(backend.ipam.as_ref().unwrap().config.as_ref().unwrap()[0].subnet.as_deref(), Some("172.28.0.0/16"));
+
+        assert_eq!(compose.volume_name("data"), "app-data");
+        assert_eq!(compose.volume_name("cache"), "shop_cache");
+        let data = compose.volumes["data"].as_ref().unwrap();
+        assert_eq!(data.labels.as_ref().unwrap().to_map()["backup"], "true");
+    }
+
+    #[test]
+    fn test_service_endpoints() {
+        let compose = parse(
+            r#"
+name: shop
+services:
+  api:
+    image: api
+    networks:
+      backend:
+        aliases: [internal-api]
+        ipv4_address: 172.20.0.10
+      frontend:
+  worker:
+    image: worker
+networks:
+  backend:
+  frontend:
+    external: true
+    name: edge
+"#,
+        );
+        let endpoints = compose.service_endpoints("api", &compose.services["api"]);
+        assert_eq!(endpoints.len(), 2);
+        assert_eq!(endpoints[0].0, "shop_backend");
+        assert_eq!(endpoints[0].1.aliases, Some(vec!["api".to_string(), "internal-api".to_string()]));
+        assert_eq!(endpoints[0].1.ipv4_address.as_deref(), Some("172.20.0.10"));
+        assert_eq!(endpoints[1].0, "edge");
+        assert_eq!(endpoints[1].1.aliases, Some(vec!["api".to_string()]));
+
+        let endpoints = compose.service_endpoints("worker", &compose.services["worker"]);
+        assert_eq!(endpoints.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec!["shop_default"]);
+    }
+
+    #[test]
+    fn test_network_mode() {
+        let mut compose = parse(
+            r#"
+name: shop
+services:
+  web:
+    image: nginx
+    network_mode: host
+  sidecar:
+    image: envoy
+    network_mode: "service:api"
+    depends_on: [web]
+  api:
+    image: api
+"#,
+        );
+        assert_eq!(compose.network_mode(&compose.services["web"]).unwrap().as_deref(), Some("host"));
+        assert_eq!(compose.network_mode(&compose.services["sidecar"]).unwrap().as_deref(), Some("container:shop-api-1"));
+        assert_eq!(compose.network_mode(&compose.services["api"]).unwrap(), None);
+        let dependencies: Vec<String> = compose.services["sidecar"].dependencies().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(dependencies, vec!["web", "api"]);
+
+        compose.services.get_mut("web").unwrap().networks = Some(ServiceNetworks::List(vec!["front".to_string()]));
+        assert!(compose.network_mode(&compose.services["web"]).is_err());
+        compose.services.get_mut("sidecar").unwrap().network_mode = Some("service:db".to_string());
+        assert!(compose.network_mode(&compose.services["sidecar"]).unwrap_err().contains("unknown service"));
+    }
+
+    #[test]
+    fn test_depends_on_label_round_trip() {
+        let compose = parse("services:\n  web: { image: nginx, depends_on: { db: { condition: service_healthy }, cache: {} } }\n");
+        let label = depends_on_label(&compose.services["web"]);
+        assert_eq!(label, "cache:service_started:false,db:service_healthy:false");
+        assert_eq!(parse_depends_on_label(&label), vec!["cache", "db"]);
+        assert!(parse_depends_on_label("").is_empty());
+    }
+
+    #[test]
+    fn test_topological_order_ignores_unknown_nodes() {
+        let mut graph = BTreeMap::new();
+        graph.insert("web".to_string(), vec!["db".to_string(), "gone".to_string()]);
+        graph.insert("db".to_string(), Vec::new());
+        assert_eq!(topological_order(&graph).unwrap(), vec!["db", "web"]);
+    }
+
+    #[test]
+    fn test_load_interpolates_values() {
+        let mut vars = HashMap::new();
+        vars.insert("CPUS".to_string(), "1.5".to_string());
+        let (compose, issues) = load(
+            &["services:\n  db:\n    image: postgres:${PG_VERSION:-16}\n    cpus: ${CPUS}\n    environment: [\"POSTGRES_PASSWORD=${POSTGRES_PASSWORD:-secret}\"]\n"],
+            &vars,
+        )
+        .unwrap();
+
+        let db = &compose.services["db"];
+        assert_eq!(db.image.as_deref(), Some("postgres:16"));
+        assert_eq!(db.cpus, Some(1.5));
+        assert_eq!(db.environment_vars(), vec![("POSTGRES_PASSWORD".to_string(), "secret".to_string())]);
+        assert_eq!(issues["db"], InterpolationIssues::default());
+    }
+
+    #[test]
+    fn test_load_environment_forms() {
+        let mut vars = HashMap::new();
+        vars.insert("HOST_TOKEN".to_string(), "abc".to_string());
+        let (compose, _) = load(
+            &["services:\n  list:\n    image: x\n    environment: [A=1, HOST_TOKEN, NOT_SET]\n  map:\n    image: x\n    environment:\n      DEBUG: true\n      PORT: 8080\n      HOST_TOKEN:\n      EMPTY: \"\"\n"],
+            &vars,
+        )
+        .unwrap();
+
+        let list: HashMap<_, _> = compose.services["list"].environment_vars().into_iter().collect();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list["A"], "1");
+        assert_eq!(list["HOST_TOKEN"], "abc");
+
+        let map: HashMap<_, _> = compose.services["map"].environment_vars().into_iter().collect();
+        assert_eq!(map["DEBUG"], "true");
+        assert_eq!(map["PORT"], "8080");
+        assert_eq!(map["HOST_TOKEN"], "abc");
+        assert_eq!(map["EMPTY"], "");
+    }
+
+    #[test]
+    fn test_read_env_files() {
+        let dir = std::env::temp_dir().join(format!("dock-env-file-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("app.env"), "A=1\nB=2\n").unwrap();
+
+        let compose = parse(
+            "services:\n  app:\n    image: app\n    env_file:\n      - app.env\n      - path: missing.env\n        required: false\n",
+        );
+        let vars = compose.services["app"].read_env_files(Some(&dir)).unwrap();
+        assert_eq!(vars, vec![("A".to_string(), "1".to_string()), ("B".to_string(), "2".to_string())]);
+
+        let compose = parse("services:\n  app: { image: app, env_file: missing.env }\n");
+        assert!(compose.services["app"].read_env_files(Some(&dir)).is_err());
+        assert!(compose.services["app"].read_env_files(None).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_overrides_and_profiles() {
+        let base = "services:\n  web:\n    image: app\n    networks: [front]\n    environment: [MODE=prod]\n  debug:\n    image: busybox\n    profiles: [debug]\n";
+        let overlay = "services:\n  web:\n    image: app:dev\n    networks:\n      front:\n        aliases: [site]\n    environment:\n      MODE: dev\n";
+        let (mut compose, _) = load(&[base, overlay], &HashMap::new()).unwrap();
+        let web = &compose.services["web"];
+        assert_eq!(web.image.as_deref(), Some("app:dev"));
+        assert_eq!(web.environment_vars(), vec![("MODE".to_string(), "dev".to_string())]);
+        let networks = web.networks.as_ref().unwrap();
+        assert_eq!(networks.names(), vec!["front"]);
+        assert_eq!(networks.config("front").unwrap().aliases.as_deref(), Some(&["site".to_string()][..]));
+
+        compose.apply_profiles(&[]).unwrap();
+        assert!(!compose.services.contains_key("debug"));
+
+        let (mut compose, _) = load(&[base], &HashMap::new()).unwrap();
+        compose.apply_profiles(&["debug".to_string()]).unwrap();
+        assert!(compose.services.contains_key("debug"));
+
+        let (mut compose, _) = load(&["services:\n  web: { image: app, depends_on: [debug] }\n  debug: { image: busybox, profiles: [debug] }\n"], &HashMap::new()).unwrap();
+        assert!(compose.apply_profiles(&[]).unwrap_err().contains("not enabled by the active profiles"));
+    }
+
+    #[test]
+    fn test_render_is_normalized() {
+        let (compose, _) = load(&["name: shop\nservices:\n  web: { image: nginx, ports: [\"80:80\"] }\n"], &HashMap::new()).unwrap();
+        let rendered = compose.render().unwrap();
+        assert!(!rendered.contains("null"));
+        assert!(rendered.find("name: shop").unwrap() < rendered.find("services:").unwrap());
+        assert!(rendered.contains("image: nginx"));
+    }
+
+    #[test]
+    fn test_service_keys() {
+        let compose = parse(
+            r#"
+services:
+  app:
+    image: app
+    command: node server.js --port "3000"
+    entrypoint: ["/sbin/tini", "--"]
+    user: 1000
+    extra_hosts: ["db.local=10.0.0.5", "cache.local:10.0.0.6"]
+    ulimits:
+      nproc: 65535
+      nofile: { soft: 1024, hard: 2048 }
+    shm_size: 64mb
+    deploy: { replicas: "2" }
+    develop: { watch: [] }
+    x-notes: kept quietly
+"#,
+        );
+        let app = &compose.services["app"];
+        assert_eq!(app.command_args().unwrap().unwrap(), vec!["node", "server.js", "--port", "3000"]);
+        assert_eq!(app.entrypoint_args().unwrap().unwrap(), vec!["/sbin/tini", "--"]);
+        assert_eq!(app.user.as_deref(), Some("1000"));
+        assert_eq!(app.extra_hosts_list(), vec!["db.local:10.0.0.5", "cache.local:10.0.0.6"]);
+        assert_eq!(app.ulimits.as_ref().unwrap()["nofile"].limits(), (1024, 2048));
+        assert_eq!(parse_memory_limit(app.shm_size.as_deref().unwrap()), Some(64 * 1024 * 1024));
+        assert_eq!(app.replicas(), 2);
+        assert_eq!(app.unknown_keys(), vec!["develop"]);
+    }
+
+    #[test]
+    fn test_deployment_order_unknown_dependency() {
+        let compose = parse("services:\n  web: { image: nginx, depends_on: [db] }\n");
+        assert!(deployment_order(&compose.services).unwrap_err().contains("undefined service 'db'"));
+    }
+
+    #[test]
+    fn test_project_name_from_dir() {
+        assert_eq!(project_name_from_dir(Path::new("/home/me/My Shop")).as_deref(), Some("myshop"));
+        assert_eq!(project_name_from_dir(Path::new("/srv/_web.app-2")).as_deref(), Some("webapp-2"));
+        assert_eq!(project_name_from_dir(Path::new("/srv/...")), None);
+        assert_eq!(project_name_from_dir(Path::new("/")), None);
+    }
+}
@@ -0,0 +1,318 @@
+use super::MappingOrList;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// `build` is either the context path or a detailed build definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ComposeBuild {
+    Context(String),
+    Detailed(ComposeBuildConfig),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeBuildConfig {
+    pub context: Option<String>,
+    pub dockerfile: Option<String>,
+    pub args: Option<MappingOrList>,
+    pub target: Option<String>,
+    pub labels: Option<MappingOrList>,
+    pub cache_from: Option<Vec<String>>,
+    pub network: Option<String>,
+    pub pull: Option<bool>,
+    pub no_cache: Option<bool>,
+}
+
+impl ComposeBuild {
+    pub fn config(&self) -> ComposeBuildConfig {
+        match self {
+            ComposeBuild::Context(context) => ComposeBuildConfig {
+                context: Some(context.clone()),
+                ..Default::default()
+            },
+            ComposeBuild::Detailed(config) => config.clone(),
+        }
+    }
+}
+
+impl ComposeBuildConfig {
+    pub fn context(&self) -> &str {
+        self.context.as_deref().unwrap_or(".")
+    }
+
+    pub fn dockerfile(&self) -> &str {
+        self.dockerfile.as_deref().unwrap_or("Dockerfile")
+    }
+
+    /// Git and HTTP contexts are fetched by the daemon instead of being uploaded
+    pub fn is_remote(&self) -> bool {
+        let context = self.context();
+        context.starts_with("http://")
+            || context.starts_with("https://")
+            || context.starts_with("git://")
+            || context.starts_with("git@")
+    }
+}
+
+/// Exclusion rules of a `.dockerignore` file
+pub struct DockerIgnore {
+    patterns: Vec<(Regex, bool)>,
+}
+
+impl DockerIgnore {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut patterns = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, pattern) = match line.strip_prefix('!') {
+                Some(rest) => (true, rest.trim()),
+                None => (false, line),
+            };
+            let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+            let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+            if pattern.is_empty() {
+                continue;
+            }
+            let regex = Regex::new(&glob_to_regex(pattern))
+                .map_err(|e| format!("Invalid .dockerignore pattern '{}': {}", line, e))?;
+            patterns.push((regex, negated));
+        }
+        Ok(DockerIgnore { patterns })
+    }
+
+    fn has_exceptions(&self) -> bool {
+        self.patterns.iter().any(|(_, negated)| *negated)
+    }
+
+    /// Whether a `/`-separated path relative to the context is excluded.
+    /// A pattern matching a parent directory excludes everything below it; later patterns win.
+    pub fn is_excluded(&self, path: &str) -> bool {
+        let mut prefixes = Vec::new();
+        for (i, c) in path.char_indices() {
+            if c == '/' {
+                prefixes.push(&path[..i]);
+            }
+        }
+        prefixes.push(path);
+
+        let mut excluded = false;
+        for (regex, negated) in &self.patterns {
+            if prefixes.iter().any(|prefix| regex.is_match(prefix)) {
+                excluded = !negated;
+            }
+        }
+        excluded
+    }
+}
+
+fn glob_to_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                // `**/` also matches zero directories
+                if chars.get(i + 2) == Some(&'/') {
+                    regex.push_str("(.*/)?");
+                    i += 3;
+                } else {
+                    regex.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    regex.push('$');
+    regex
+}
+
+/// Drops the stages that follow `target` so the target becomes the final stage.
+/// The daemon supports a `target` build parameter, but bollard 0.17's `BuildImageOptions` doesn't
+/// expose it; this can go once a bollard upgrade does.
+pub fn truncate_to_target(dockerfile: &str, target: &str) -> Result<String, String> {
+    let mut output = String::new();
+    let mut in_target = false;
+
+    for line in dockerfile.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.first().is_some_and(|w| w.eq_ignore_ascii_case("FROM")) {
+            if in_target {
+                return Ok(output);
+            }
+            let stage = words.windows(2).find(|w| w[0].eq_ignore_ascii_case("AS")).map(|w| w[1]);
+            in_target = stage.is_some_and(|stage| stage.eq_ignore_ascii_case(target));
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    if in_target {
+        Ok(output)
+    } else {
+        Err(format!("Build target '{}' not found in Dockerfile", target))
+    }
+}
+
+/// Packs a build context directory into a tar archive, honouring its `.dockerignore`.
+/// Symlinks are archived as links and file modes are preserved.
+/// With a `target`, the archived Dockerfile ends with that stage.
+pub fn tar_context(context: &Path, dockerfile: &str, target: Option<&str>) -> Result<Vec<u8>, String> {
+    if !context.is_dir() {
+        return Err(format!("Build context '{}' is not a directory", context.display()));
+    }
+    let dockerfile = dockerfile.trim_start_matches("./");
+
+    let ignore = match std::fs::read_to_string(context.join(".dockerignore")) {
+        Ok(content) => DockerIgnore::parse(&content)?,
+        Err(_) => DockerIgnore { patterns: Vec::new() },
+    };
+    // The daemon needs these two even when .dockerignore lists them
+    let always_included = [dockerfile, ".dockerignore"];
+
+    let mut builder = tar::Builder::new(Vec::new());
+    builder.follow_symlinks(false);
+    builder.mode(tar::HeaderMode::Complete);
+
+    match target {
+        Some(target) => {
+            let path = context.join(dockerfile);
+            let read_error = |e: std::io::Error| format!("Failed to read Dockerfile '{}': {}", dockerfile, e);
+            let content = std::fs::read_to_string(&path).map_err(read_error)?;
+            let truncated = truncate_to_target(&content, target)?;
+            let mut header = tar::Header::new_gnu();
+            header.set_metadata(&std::fs::metadata(&path).map_err(read_error)?);
+            header.set_size(truncated.len() as u64);
+            builder
+                .append_data(&mut header, dockerfile, truncated.as_bytes())
+                .map_err(|e| format!("Failed to add '{}' to build context: {}", dockerfile, e))?;
+            append_dir(&mut builder, context, "", &ignore, &always_included, Some(dockerfile))?;
+        }
+        None => append_dir(&mut builder, context, "", &ignore, &always_included, None)?,
+    }
+
+    builder
+        .into_inner()
+        .map_err(|e| format!("Failed to create build context archive: {}", e))
+}
+
+fn append_dir(
+    builder: &mut tar::Builder<Vec<u8>>,
+    dir: &Path,
+    relative: &str,
+    ignore: &DockerIgnore,
+    always_included: &[&str],
+    replaced: Option<&str>,
+) -> Result<(), String> {
+    let mut entries: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read '{}': {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok())
+        .collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = if relative.is_empty() { name } else { format!("{}/{}", relative, name) };
+        let file_type = entry.file_type().map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+        if replaced == Some(path.as_str()) {
+            continue;
+        }
+        let excluded = ignore.is_excluded(&path) && !always_included.contains(&path.as_str());
+
+        if file_type.is_dir() {
+            // Exception patterns may re-include files below an excluded directory
+            if excluded && !ignore.has_exceptions() {
+                continue;
+            }
+            if !excluded {
+                builder
+                    .append_path_with_name(entry.path(), &path)
+                    .map_err(|e| format!("Failed to add '{}' to build context: {}", path, e))?;
+            }
+            append_dir(builder, &entry.path(), &path, ignore, always_included, replaced)?;
+        } else if !excluded {
+            builder
+                .append_path_with_name(entry.path(), &path)
+                .map_err(|e| format!("Failed to add '{}' to build context: {}", path, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_syntax() {
+        let build: ComposeBuild = serde_yaml::from_str("./app").unwrap();
+        assert_eq!(build.config().context(), "./app");
+        assert_eq!(build.config().dockerfile(), "Dockerfile");
+
+        let build: ComposeBuild =
+            serde_yaml::from_str("{ context: ., dockerfile: docker/Dockerfile.dev, target: dev, args: [VERSION=1] }").unwrap();
+        let config = build.config();
+        assert_eq!(config.dockerfile(), "docker/Dockerfile.dev");
+        assert_eq!(config.target.as_deref(), Some("dev"));
+        assert_eq!(config.args.unwrap().to_map()["VERSION"], "1");
+
+        let build: ComposeBuild = serde_yaml::from_str("https://github.com/example/app.git#main").unwrap();
+        assert!(build.config().is_remote());
+    }
+
+    #[test]
+    fn test_dockerignore() {
+        let ignore = DockerIgnore::parse("# comment\nnode_modules\n*.log\n**/tmp\n/dist/\n!dist/keep.txt\n").unwrap();
+        assert!(ignore.is_excluded("node_modules"));
+        assert!(ignore.is_excluded("node_modules/pkg/index.js"));
+        assert!(ignore.is_excluded("debug.log"));
+        assert!(!ignore.is_excluded("logs/debug.log"));
+        assert!(ignore.is_excluded("tmp"));
+        assert!(ignore.is_excluded("src/cache/tmp/file"));
+        assert!(ignore.is_excluded("dist/app.js"));
+        assert!(!ignore.is_excluded("dist/keep.txt"));
+        assert!(!ignore.is_excluded("src/main.rs"));
+    }
+
+    #[test]
+    fn test_truncate_to_target() {
+        let dockerfile = "FROM rust AS build\nRUN cargo build\n\nfrom build as test\nRUN cargo test\n\nFROM debian\nCOPY --from=build /app /app\n";
+        assert_eq!(
+            truncate_to_target(dockerfile, "test").unwrap(),
+            "FROM rust AS build\nRUN cargo build\n\nfrom build as test\nRUN cargo test\n\n"
+        );
+        assert!(truncate_to_target(dockerfile, "missing").is_err());
+    }
+
+    #[test]
+    fn test_tar_context() {
+        let dir = std::env::temp_dir().join(format!("dock-build-context-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::create_dir_all(dir.join("target")).unwrap();
+        std::fs::write(dir.join("Dockerfile"), "FROM scratch\n").unwrap();
+        std::fs::write(dir.join(".dockerignore"), "target\nDockerfile\n").unwrap();
+        std::fs::write(dir.join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(dir.join("target/app"), "binary").unwrap();
+
+        let archive = tar_context(&dir, "Dockerfile", None).unwrap();
+        let mut names: Vec<String> = tar::Archive::new(archive.as_slice())
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().trim_end_matches('/').to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![".dockerignore", "Dockerfile", "src", "src/main.rs"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
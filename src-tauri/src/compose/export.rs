@@ -0,0 +1,409 @@
+use super::{
+    format_duration, ComposeFile, ComposeHealthcheck, ComposeNetwork, ComposePort, ComposeService, ComposeServiceVolume,
+    ComposeVolume, MappingOrList, ServiceNetworkConfig, ServiceNetworks, StringOrList, PROJECT_LABEL, SERVICE_LABEL,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Settings a container can inherit from its image
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessConfig {
+    pub env: Vec<String>,
+    pub command: Option<Vec<String>>,
+    pub entrypoint: Option<Vec<String>>,
+    pub user: Option<String>,
+    pub working_dir: Option<String>,
+    pub labels: HashMap<String, String>,
+    pub healthcheck: Option<HealthcheckSnapshot>,
+}
+
+/// A healthcheck as the daemon reports it, with durations in nanoseconds
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthcheckSnapshot {
+    pub test: Vec<String>,
+    pub interval: Option<i64>,
+    pub timeout: Option<i64>,
+    pub retries: Option<i64>,
+    pub start_period: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MountSnapshot {
+    /// "bind", "volume" or "tmpfs"
+    pub kind: String,
+    /// Host path for binds, volume name for volumes
+    pub source: Option<String>,
+    pub target: String,
+    pub read_only: bool,
+}
+
+/// What `generate_compose` needs to know about an existing container and its image
+#[derive(Debug, Clone, Default)]
+pub struct ContainerSnapshot {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub config: ProcessConfig,
+    pub image_config: ProcessConfig,
+    /// (container port such as "80/tcp", host IP, host port)
+    pub ports: Vec<(String, String, String)>,
+    pub mounts: Vec<MountSnapshot>,
+    pub network_mode: Option<String>,
+    /// Attached networks with the aliases of the container on each
+    pub networks: BTreeMap<String, Vec<String>>,
+    pub restart_policy: Option<String>,
+    pub restart_retries: Option<i64>,
+    pub memory: Option<i64>,
+    pub cpus: Option<f64>,
+}
+
+const BUILTIN_NETWORKS: [&str; 4] = ["default", "bridge", "host", "none"];
+
+/// Builds a compose file that recreates the given containers, leaving out everything that
+/// matches the image or the daemon's defaults
+pub fn generate_compose(containers: &[ContainerSnapshot]) -> Result<String, String> {
+    let mut compose = ComposeFile {
+        name: None,
+        version: None,
+        services: HashMap::new(),
+        networks: HashMap::new(),
+        volumes: HashMap::new(),
+        secrets: HashMap::new(),
+        configs: HashMap::new(),
+    };
+
+    for container in containers {
+        let service_name = unique_name(&service_name(container), &compose.services);
+        let service = export_service(container, &mut compose);
+        compose.services.insert(service_name, service);
+    }
+
+    compose.render()
+}
+
+// Compose-deployed containers keep their service name; others are named after the container
+fn service_name(container: &ContainerSnapshot) -> String {
+    if let Some(service) = container.config.labels.get(SERVICE_LABEL) {
+        return service.clone();
+    }
+    let name: String = container
+        .name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    let name = name.trim_matches('-').to_string();
+    if name.is_empty() { "service".to_string() } else { name }
+}
+
+fn unique_name(name: &str, services: &HashMap<String, ComposeService>) -> String {
+    if !services.contains_key(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", name, n))
+        .find(|candidate| !services.contains_key(candidate))
+        .unwrap_or_default()
+}
+
+fn export_service(container: &ContainerSnapshot, compose: &mut ComposeFile) -> ComposeService {
+    let config = &container.config;
+    let image_config = &container.image_config;
+    let mut service = ComposeService {
+        image: Some(container.image.clone()),
+        ..Default::default()
+    };
+
+    // Compose names its own containers, so only hand-made ones keep a fixed name
+    if !config.labels.contains_key(PROJECT_LABEL) {
+        service.container_name = Some(container.name.clone());
+    }
+
+    let env: Vec<String> = config.env.iter().filter(|entry| !image_config.env.contains(entry)).cloned().collect();
+    if !env.is_empty() {
+        service.environment = Some(MappingOrList::List(env));
+    }
+
+    // Overriding the entrypoint discards the image's command, so the container's command has to be kept
+    let entrypoint_changed = config.entrypoint != image_config.entrypoint;
+    if entrypoint_changed {
+        service.entrypoint = config.entrypoint.as_ref().map(|values| string_list(values));
+    }
+    if entrypoint_changed || config.command != image_config.command {
+        service.command = config.command.as_ref().map(|values| string_list(values));
+    }
+    service.user = config.user.clone().filter(|user| !user.is_empty() && config.user != image_config.user);
+    service.working_dir = config
+        .working_dir
+        .clone()
+        .filter(|dir| !dir.is_empty() && config.working_dir != image_config.working_dir);
+
+    let mut ports: Vec<String> = Vec::new();
+    for (container_port, host_ip, host_port) in &container.ports {
+        if host_port.is_empty() {
+            continue;
+        }
+        let container_port = container_port.strip_suffix("/tcp").unwrap_or(container_port);
+        // The daemon lists a binding on all interfaces once for IPv4 and once for IPv6
+        let port = match host_ip.as_str() {
+            "" | "0.0.0.0" | "::" => format!("{}:{}", host_port, container_port),
+            ip if ip.contains(':') => format!("[{}]:{}:{}", ip, host_port, container_port),
+            ip => format!("{}:{}:{}", ip, host_port, container_port),
+        };
+        if !ports.contains(&port) {
+            ports.push(port);
+        }
+    }
+    if !ports.is_empty() {
+        service.ports = Some(ports.into_iter().map(ComposePort::Short).collect());
+    }
+
+    let mut volumes = Vec::new();
+    let mut tmpfs = Vec::new();
+    for mount in &container.mounts {
+        let mode = if mount.read_only { ":ro" } else { "" };
+        match (mount.kind.as_str(), &mount.source) {
+            ("tmpfs", _) => tmpfs.push(mount.target.clone()),
+            ("volume", Some(name)) if !is_anonymous_volume(name) => {
+                volumes.push(format!("{}:{}{}", name, mount.target, mode));
+                compose.volumes.insert(
+                    name.clone(),
+                    Some(ComposeVolume {
+                        name: Some(name.clone()),
+                        ..Default::default()
+                    }),
+                );
+            }
+            ("volume", _) => volumes.push(mount.target.clone()),
+            (_, Some(source)) => volumes.push(format!("{}:{}{}", source, mount.target, mode)),
+            (_, None) => {}
+        }
+    }
+    if !volumes.is_empty() {
+        service.volumes = Some(volumes.into_iter().map(ComposeServiceVolume::Short).collect());
+    }
+    if !tmpfs.is_empty() {
+        service.tmpfs = Some(StringOrList::List(tmpfs));
+    }
+
+    export_networks(container, &mut service, compose);
+
+    service.restart = match container.restart_policy.as_deref() {
+        None | Some("") | Some("no") => None,
+        Some("on-failure") if container.restart_retries.unwrap_or(0) > 0 => {
+            Some(format!("on-failure:{}", container.restart_retries.unwrap_or(0)))
+        }
+        Some(policy) => Some(policy.to_string()),
+    };
+    service.mem_limit = container.memory.filter(|m| *m > 0).map(format_memory);
+    service.cpus = container.cpus.filter(|c| *c > 0.0);
+
+    let labels: BTreeMap<String, Option<serde_yaml::Value>> = config
+        .labels
+        .iter()
+        .filter(|(key, value)| !key.starts_with("com.docker.compose.") && image_config.labels.get(*key) != Some(value))
+        .map(|(key, value)| (key.clone(), Some(serde_yaml::Value::from(value.as_str()))))
+        .collect();
+    if !labels.is_empty() {
+        service.labels = Some(MappingOrList::Map(labels));
+    }
+
+    if config.healthcheck != image_config.healthcheck {
+        service.healthcheck = config.healthcheck.as_ref().map(export_healthcheck);
+    }
+
+    service
+}
+
+fn export_networks(container: &ContainerSnapshot, service: &mut ComposeService, compose: &mut ComposeFile) {
+    let mode = container.network_mode.as_deref().unwrap_or_default();
+    if mode == "host" || mode == "none" || mode.starts_with("container:") {
+        service.network_mode = Some(mode.to_string());
+        return;
+    }
+
+    let mut networks = BTreeMap::new();
+    for (network, aliases) in &container.networks {
+        if BUILTIN_NETWORKS.contains(&network.as_str()) {
+            continue;
+        }
+        // The daemon adds the container's short ID and name as aliases by itself
+        let aliases: Vec<String> = aliases
+            .iter()
+            .filter(|alias| !container.id.starts_with(alias.as_str()) && **alias != container.name)
+            .cloned()
+            .collect();
+        let config = (!aliases.is_empty()).then(|| ServiceNetworkConfig {
+            aliases: Some(aliases),
+            ..Default::default()
+        });
+        networks.insert(network.clone(), config);
+        compose.networks.insert(
+            network.clone(),
+            Some(ComposeNetwork {
+                name: Some(network.clone()),
+                ..Default::default()
+            }),
+        );
+    }
+
+    if networks.is_empty() {
+        return;
+    }
+    service.networks = Some(if networks.values().all(Option::is_none) {
+        ServiceNetworks::List(networks.into_keys().collect())
+    } else {
+        ServiceNetworks::Map(networks)
+    });
+}
+
+fn export_healthcheck(healthcheck: &HealthcheckSnapshot) -> ComposeHealthcheck {
+    if healthcheck.test.first().map(String::as_str) == Some("NONE") {
+        return ComposeHealthcheck {
+            disable: Some(true),
+            ..Default::default()
+        };
+    }
+    let duration = |value: Option<i64>| value.filter(|v| *v > 0).map(format_duration);
+    ComposeHealthcheck {
+        test: Some(StringOrList::List(healthcheck.test.clone())),
+        interval: duration(healthcheck.interval),
+        timeout: duration(healthcheck.timeout),
+        retries: healthcheck.retries.filter(|r| *r > 0),
+        start_period: duration(healthcheck.start_period),
+        ..Default::default()
+    }
+}
+
+fn string_list(values: &[String]) -> serde_yaml::Value {
+    serde_yaml::Value::Sequence(values.iter().map(|v| serde_yaml::Value::from(v.as_str())).collect())
+}
+
+// Anonymous volumes are named with 64 hex characters by the daemon
+fn is_anonymous_volume(name: &str) -> bool {
+    name.len() == 64 && name.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn format_memory(bytes: i64) -> String {
+    const UNITS: [(i64, &str); 3] = [(1024 * 1024 * 1024, "g"), (1024 * 1024, "m"), (1024, "k")];
+    UNITS
+        .iter()
+        .find(|(size, _)| bytes % size == 0)
+        .map(|(size, unit)| format!("{}{}", bytes / size, unit))
+        .unwrap_or_else(|| bytes.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> ContainerSnapshot {
+        let image_config = ProcessConfig {
+            env: vec!["PATH=/usr/local/bin:/usr/bin".to_string(), "NGINX_VERSION=1.27".to_string()],
+            command: Some(vec!["nginx".to_string(), "-g".to_string(), "daemon off;".to_string()]),
+            entrypoint: Some(vec!["/docker-entrypoint.sh".to_string()]),
+            labels: HashMap::from([("maintainer".to_string(), "NGINX".to_string())]),
+            ..Default::default()
+        };
+        let mut config = image_config.clone();
+        config.env.push("MODE=prod".to_string());
+        config.labels.insert("team".to_string(), "web".to_string());
+        config.healthcheck = Some(HealthcheckSnapshot {
+            test: vec!["CMD-SHELL".to_string(), "curl -f http://localhost".to_string()],
+            interval: Some(30_000_000_000),
+            retries: Some(3),
+            ..Default::default()
+        });
+
+        ContainerSnapshot {
+            id: "0123456789abcdef".to_string(),
+            name: "My.Web".to_string(),
+            image: "nginx:1.27".to_string(),
+            config,
+            image_config,
+            ports: vec![
+                ("80/tcp".to_string(), "0.0.0.0".to_string(), "8080".to_string()),
+                ("80/tcp".to_string(), "::".to_string(), "8080".to_string()),
+                ("53/udp".to_string(), "127.0.0.1".to_string(), "5353".to_string()),
+            ],
+            mounts: vec![
+                MountSnapshot { kind: "volume".to_string(), source: Some("web_data".to_string()), target: "/data".to_string(), read_only: false },
+                MountSnapshot { kind: "bind".to_string(), source: Some("/srv/conf".to_string()), target: "/etc/nginx".to_string(), read_only: true },
+                MountSnapshot { kind: "volume".to_string(), source: Some("a".repeat(64)), target: "/cache".to_string(), read_only: false },
+            ],
+            network_mode: Some("frontend".to_string()),
+            networks: BTreeMap::from([("frontend".to_string(), vec!["0123456789ab".to_string(), "web".to_string()])]),
+            restart_policy: Some("unless-stopped".to_string()),
+            memory: Some(512 * 1024 * 1024),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_generate_compose() {
+        let yaml = generate_compose(&[snapshot()]).unwrap();
+        let (compose, _) = super::super::load(&[&yaml], &HashMap::new()).unwrap();
+
+        let service = &compose.services["my-web"];
+        assert_eq!(service.container_name.as_deref(), Some("My.Web"));
+        assert_eq!(service.environment_vars(), vec![("MODE".to_string(), "prod".to_string())]);
+        assert!(service.command.is_none() && service.entrypoint.is_none());
+        let ports: Vec<String> = service.ports.iter().flatten().map(|p| format!("{:?}", p)).collect();
+        assert_eq!(ports, vec!["Short(\"8080:80\")", "Short(\"127.0.0.1:5353:53/udp\")"]);
+        assert_eq!(service.volumes.as_ref().unwrap().len(), 3);
+        assert_eq!(service.restart.as_deref(), Some("unless-stopped"));
+        assert_eq!(service.mem_limit.as_deref(), Some("512m"));
+        assert_eq!(service.labels.as_ref().unwrap().to_map(), HashMap::from([("team".to_string(), "web".to_string())]));
+        assert_eq!(service.healthcheck.as_ref().unwrap().interval.as_deref(), Some("30s"));
+        assert_eq!(service.networks.as_ref().unwrap().config("frontend").unwrap().aliases, Some(vec!["web".to_string()]));
+
+        assert_eq!(compose.volumes["web_data"].as_ref().unwrap().name.as_deref(), Some("web_data"));
+        assert_eq!(compose.volumes.len(), 1);
+        assert_eq!(compose.networks["frontend"].as_ref().unwrap().name.as_deref(), Some("frontend"));
+    }
+
+    #[test]
+    fn test_generate_compose_overrides_and_names() {
+        let mut overridden = snapshot();
+        overridden.config.entrypoint = Some(vec!["/bin/sh".to_string(), "-c".to_string()]);
+        overridden.network_mode = Some("host".to_string());
+        overridden.networks.clear();
+        let mut second = snapshot();
+        second.config.labels.insert(SERVICE_LABEL.to_string(), "my-web".to_string());
+        second.config.labels.insert(PROJECT_LABEL.to_string(), "shop".to_string());
+
+        let yaml = generate_compose(&[overridden, second]).unwrap();
+        let (compose, _) = super::super::load(&[&yaml], &HashMap::new()).unwrap();
+
+        let first = &compose.services["my-web"];
+        assert_eq!(first.entrypoint_args().unwrap().unwrap(), vec!["/bin/sh", "-c"]);
+        assert_eq!(first.command_args().unwrap().unwrap(), vec!["nginx", "-g", "daemon off;"]);
+        assert_eq!(first.network_mode.as_deref(), Some("host"));
+        assert!(first.networks.is_none());
+        assert!(first.unknown_keys().is_empty());
+        assert_eq!(compose.network_mode(first).unwrap().as_deref(), Some("host"));
+
+        let second = &compose.services["my-web-2"];
+        assert!(second.container_name.is_none());
+        assert!(second.labels.as_ref().unwrap().to_map().keys().all(|key| !key.starts_with("com.docker.compose.")));
+    }
+
+    #[test]
+    fn test_restart_retries_round_trip() {
+        let mut container = snapshot();
+        container.restart_policy = Some("on-failure".to_string());
+        container.restart_retries = Some(3);
+
+        let yaml = generate_compose(&[container]).unwrap();
+        let (compose, _) = super::super::load(&[&yaml], &HashMap::new()).unwrap();
+        let restart = compose.services["my-web"].restart.as_deref().unwrap();
+        assert_eq!(restart, "on-failure:3");
+        assert_eq!(crate::validation::validate_restart_policy(restart).unwrap(), ("on-failure".to_string(), Some(3)));
+    }
+
+    #[test]
+    fn test_format_memory() {
+        assert_eq!(format_memory(2 * 1024 * 1024 * 1024), "2g");
+        assert_eq!(format_memory(1536 * 1024), "1536k");
+        assert_eq!(format_memory(1000), "1000");
+    }
+}
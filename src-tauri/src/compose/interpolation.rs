@@ -0,0 +1,310 @@
+use serde_yaml::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Variables that could not be resolved while interpolating part of a compose file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InterpolationIssues {
+    /// Variables referenced without a default that are not set (they resolve to an empty string)
+    pub unset: BTreeSet<String>,
+    /// Error messages of `${VAR:?err}` / `${VAR?err}` references whose variable is missing
+    pub required: Vec<String>,
+}
+
+/// Result of interpolating a whole compose document
+#[derive(Debug, Default)]
+pub struct InterpolatedDocument {
+    pub document: Value,
+    pub services: BTreeMap<String, InterpolationIssues>,
+}
+
+/// Interpolates every string value of a parsed compose document.
+/// Issues inside `services` are reported per service so a single bad service doesn't block the rest;
+/// a required variable missing anywhere else fails the whole document.
+pub fn interpolate_document(mut document: Value, vars: &HashMap<String, String>) -> Result<InterpolatedDocument, String> {
+    let mut services = BTreeMap::new();
+    let mut global = InterpolationIssues::default();
+
+    if let Value::Mapping(root) = &mut document {
+        for (key, value) in root.iter_mut() {
+            if key.as_str() == Some("services") {
+                if let Value::Mapping(service_map) = value {
+                    for (name, service) in service_map.iter_mut() {
+                        let mut issues = InterpolationIssues::default();
+                        interpolate_value(service, vars, &mut issues)?;
+                        services.insert(name.as_str().unwrap_or_default().to_string(), issues);
+                    }
+                    continue;
+                }
+            }
+            interpolate_value(value, vars, &mut global)?;
+        }
+    }
+
+    if !global.required.is_empty() {
+        return Err(global.required.join("; "));
+    }
+
+    Ok(InterpolatedDocument { document, services })
+}
+
+fn interpolate_value(value: &mut Value, vars: &HashMap<String, String>, issues: &mut InterpolationIssues) -> Result<(), String> {
+    match value {
+        Value::String(s) => *s = interpolate(s, vars, issues)?,
+        Value::Sequence(seq) => {
+            for item in seq {
+                interpolate_value(item, vars, issues)?;
+            }
+        }
+        Value::Mapping(map) => {
+            for (_, item) in map.iter_mut() {
+                interpolate_value(item, vars, issues)?;
+            }
+        }
+        Value::Tagged(tagged) => interpolate_value(&mut tagged.value, vars, issues)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Substitutes `$VAR`, `${VAR}`, `${VAR:-default}`, `${VAR-default}`, `${VAR:?err}`, `${VAR?err}`,
+/// `${VAR:+alt}` and `${VAR+alt}` following the compose specification. `$$` yields a literal `$`.
+pub fn interpolate(input: &str, vars: &HashMap<String, String>, issues: &mut InterpolationIssues) -> Result<String, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] != '$' {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        match chars.get(i + 1) {
+            Some('$') => {
+                out.push('$');
+                i += 2;
+            }
+            Some('{') => {
+                let start = i + 2;
+                let mut depth = 1;
+                let mut j = start;
+                while j < chars.len() {
+                    if chars[j] == '$' && chars.get(j + 1) == Some(&'{') {
+                        depth += 1;
+                        j += 2;
+                        continue;
+                    }
+                    if chars[j] == '}' {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    j += 1;
+                }
+                if j >= chars.len() {
+                    return Err(format!("Invalid interpolation format for \"{}\": missing closing brace", input));
+                }
+
+                let expression: String = chars[start..j].iter().collect();
+                out.push_str(&evaluate_braced(&expression, input, vars, issues)?);
+                i = j + 1;
+            }
+            Some(&c) if is_name_start(c) => {
+                let start = i + 1;
+                let mut j = start;
+                while j < chars.len() && is_name_char(chars[j]) {
+                    j += 1;
+                }
+                let name: String = chars[start..j].iter().collect();
+                match vars.get(&name) {
+                    Some(value) => out.push_str(value),
+                    None => {
+                        issues.unset.insert(name);
+                    }
+                }
+                i = j;
+            }
+            _ => {
+                out.push('$');
+                i += 1;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn evaluate_braced(
+    expression: &str,
+    input: &str,
+    vars: &HashMap<String, String>,
+    issues: &mut InterpolationIssues,
+) -> Result<String, String> {
+    let name_len = expression.find(|c: char| !is_name_char(c)).unwrap_or(expression.len());
+    let name = &expression[..name_len];
+    if !name.starts_with(is_name_start) {
+        return Err(format!("Invalid interpolation format for \"{}\": invalid variable name", input));
+    }
+
+    let value = vars.get(name);
+    let is_set_non_empty = value.is_some_and(|v| !v.is_empty());
+    let rest = &expression[name_len..];
+
+    let (operator, word) = [":-", ":?", ":+", "-", "?", "+"]
+        .iter()
+        .find_map(|op| rest.strip_prefix(op).map(|word| (*op, word)))
+        .unwrap_or(("", rest));
+
+    match operator {
+        "" if word.is_empty() => {
+            if value.is_none() {
+                issues.unset.insert(name.to_string());
+            }
+            Ok(value.cloned().unwrap_or_default())
+        }
+        ":-" if is_set_non_empty => Ok(value.cloned().unwrap_or_default()),
+        "-" if value.is_some() => Ok(value.cloned().unwrap_or_default()),
+        ":-" | "-" => interpolate(word, vars, issues),
+        ":?" if is_set_non_empty => Ok(value.cloned().unwrap_or_default()),
+        "?" if value.is_some() => Ok(value.cloned().unwrap_or_default()),
+        ":?" | "?" => {
+            let message = interpolate(word, vars, issues)?;
+            issues.required.push(if message.is_empty() {
+                format!("required variable {} is missing a value", name)
+            } else {
+                format!("required variable {} is missing a value: {}", name, message)
+            });
+            Ok(String::new())
+        }
+        ":+" if is_set_non_empty => interpolate(word, vars, issues),
+        "+" if value.is_some() => interpolate(word, vars, issues),
+        ":+" | "+" => Ok(String::new()),
+        _ => Err(format!("Invalid interpolation format for \"{}\": unsupported expression \"${{{}}}\"", input, expression)),
+    }
+}
+
+/// Parses dotenv syntax as used by `.env` and `env_file:` files.
+/// Supports comments, `export` prefixes and single/double quoted values.
+pub fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").map(str::trim_start).unwrap_or(line);
+
+        let (key, raw_value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (line, ""),
+        };
+        if key.is_empty() {
+            continue;
+        }
+
+        let value = if let Some(quoted) = raw_value.strip_prefix('"') {
+            let inner = quoted.rfind('"').map(|end| &quoted[..end]).unwrap_or(quoted);
+            inner.replace("\\n", "\n").replace("\\t", "\t").replace("\\\"", "\"").replace("\\\\", "\\")
+        } else if let Some(quoted) = raw_value.strip_prefix('\'') {
+            quoted.rfind('\'').map(|end| &quoted[..end]).unwrap_or(quoted).to_string()
+        } else {
+            // Unquoted values end at an inline comment
+            match raw_value.find(" #") {
+                Some(pos) => raw_value[..pos].trim_end().to_string(),
+                None => raw_value.to_string(),
+            }
+        };
+
+        entries.push((key.to_string(), value));
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<String, String> {
+        let mut vars = HashMap::new();
+        vars.insert("USER".to_string(), "admin".to_string());
+        vars.insert("EMPTY".to_string(), String::new());
+        vars
+    }
+
+    fn run(input: &str) -> (String, InterpolationIssues) {
+        let mut issues = InterpolationIssues::default();
+        let out = interpolate(input, &vars(), &mut issues).unwrap();
+        (out, issues)
+    }
+
+    #[test]
+    fn test_interpolate_forms() {
+        assert_eq!(run("$USER-${USER}").0, "admin-admin");
+        assert_eq!(run("${MISSING:-secret}").0, "secret");
+        assert_eq!(run("${EMPTY:-secret}").0, "secret");
+        assert_eq!(run("${EMPTY-secret}").0, "");
+        assert_eq!(run("${USER:+set}${MISSING:+set}").0, "set");
+        assert_eq!(run("${MISSING:-${USER}}").0, "admin");
+        assert_eq!(run("cost: $$5 and $$USER").0, "cost: $5 and $USER");
+        assert_eq!(run("trailing $").0, "trailing $");
+    }
+
+    #[test]
+    fn test_interpolate_reports_unset_and_required() {
+        let (out, issues) = run("${MISSING}/${REQUIRED:?set REQUIRED first}/${EMPTY?}");
+        assert_eq!(out, "//");
+        assert!(issues.unset.contains("MISSING"));
+        assert_eq!(issues.required, vec!["required variable REQUIRED is missing a value: set REQUIRED first"]);
+    }
+
+    #[test]
+    fn test_interpolate_invalid() {
+        let mut issues = InterpolationIssues::default();
+        assert!(interpolate("${USER", &vars(), &mut issues).is_err());
+        assert!(interpolate("${1ABC}", &vars(), &mut issues).is_err());
+    }
+
+    #[test]
+    fn test_interpolate_document_per_service() {
+        let document: Value = serde_yaml::from_str(
+            "services:\n  db:\n    environment: [\"PASSWORD=${DB_PASSWORD:?}\"]\n  web:\n    image: \"nginx:${TAG:-latest}\"\n",
+        )
+        .unwrap();
+        let result = interpolate_document(document, &vars()).unwrap();
+        assert_eq!(result.document["services"]["web"]["image"], Value::from("nginx:latest"));
+        assert!(result.services["web"].required.is_empty());
+        assert_eq!(result.services["db"].required.len(), 1);
+
+        let document: Value = serde_yaml::from_str("networks:\n  front:\n    name: ${NET:?}\n").unwrap();
+        assert!(interpolate_document(document, &vars()).is_err());
+    }
+
+    #[test]
+    fn test_parse_env_file() {
+        let entries = parse_env_file(
+            "# comment\nexport A=1\nB = two words # note\nC=\"line\\nbreak\"\nD='$literal'\n\nE\n",
+        );
+        assert_eq!(
+            entries,
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "two words".to_string()),
+                ("C".to_string(), "line\nbreak".to_string()),
+                ("D".to_string(), "$literal".to_string()),
+                ("E".to_string(), String::new()),
+            ]
+        );
+    }
+}
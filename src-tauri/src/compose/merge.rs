@@ -0,0 +1,261 @@
+use serde_yaml::{Mapping, Value};
+
+/// Service keys whose list entries from later files are appended, skipping duplicates
+const APPENDED_KEYS: [&str; 13] = [
+    "cap_add", "cap_drop", "configs", "devices", "dns", "dns_search", "env_file", "expose",
+    "external_links", "group_add", "links", "ports", "secrets",
+];
+
+/// Keys that may be written as a list or a map and are merged entry by entry
+const MAPPING_KEYS: [&str; 6] = ["annotations", "args", "environment", "extra_hosts", "labels", "sysctls"];
+
+/// Keys whose value from a later file replaces the earlier one entirely
+const REPLACED_KEYS: [&str; 3] = ["command", "entrypoint", "test"];
+
+fn merge_value(base: Value, overlay: Value, key: &str) -> Value {
+    match (base, overlay) {
+        (_, Value::Tagged(tagged)) if tagged.tag == "override" => tagged.value,
+        (_, overlay) if REPLACED_KEYS.contains(&key) => overlay,
+        (base, overlay) if MAPPING_KEYS.contains(&key) => {
+            merge_mapping(to_mapping(base, key), to_mapping(overlay, key))
+        }
+        // `depends_on` and service `networks` accept both a list of names and a map
+        (base, overlay) if key == "depends_on" || key == "service_networks" => {
+            merge_mapping(names_to_mapping(base), names_to_mapping(overlay))
+        }
+        (Value::Sequence(mut base), Value::Sequence(overlay)) if APPENDED_KEYS.contains(&key) => {
+            for item in overlay {
+                if !base.contains(&item) {
+                    base.push(item);
+                }
+            }
+            Value::Sequence(base)
+        }
+        (Value::Sequence(base), Value::Sequence(overlay)) if key == "volumes" => {
+            Value::Sequence(merge_by_target(base, overlay))
+        }
+        (Value::String(base), Value::Sequence(overlay)) if key == "env_file" => {
+            merge_value(Value::Sequence(vec![Value::String(base)]), Value::Sequence(overlay), key)
+        }
+        (Value::Sequence(base), Value::String(overlay)) if key == "env_file" => {
+            merge_value(Value::Sequence(base), Value::Sequence(vec![Value::String(overlay)]), key)
+        }
+        (Value::Mapping(base), Value::Mapping(overlay)) => merge_mapping(Value::Mapping(base), Value::Mapping(overlay)),
+        (_, overlay) => overlay,
+    }
+}
+
+fn merge_mapping(base: Value, overlay: Value) -> Value {
+    let (mut base, overlay) = match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => (base, overlay),
+        (_, overlay) => return overlay,
+    };
+
+    for (key, value) in overlay {
+        if matches!(&value, Value::Tagged(tagged) if tagged.tag == "reset") {
+            base.remove(&key);
+            continue;
+        }
+        let name = key.as_str().unwrap_or_default().to_string();
+        let merged = match base.remove(&key) {
+            Some(existing) => merge_value(existing, value, &name),
+            None => strip_tags(value),
+        };
+        base.insert(key, merged);
+    }
+
+    Value::Mapping(base)
+}
+
+// `!override` on a key that only exists in the override file is just the value
+fn strip_tags(value: Value) -> Value {
+    match value {
+        Value::Tagged(tagged) if tagged.tag == "override" => tagged.value,
+        other => other,
+    }
+}
+
+// Converts `KEY=value` (or `host:ip` for extra_hosts) lists into a mapping
+fn to_mapping(value: Value, key: &str) -> Value {
+    let Value::Sequence(items) = value else {
+        return value;
+    };
+
+    let separator = if key == "extra_hosts" { ':' } else { '=' };
+    let mut mapping = Mapping::new();
+    for item in items {
+        let Some(item) = item.as_str() else { continue };
+        // extra_hosts also accepts `host=ip`
+        let split = item.split_once('=').filter(|_| key == "extra_hosts").or_else(|| item.split_once(separator));
+        match split {
+            Some((name, value)) => mapping.insert(Value::from(name), Value::from(value)),
+            None => mapping.insert(Value::from(item), Value::Null),
+        };
+    }
+    Value::Mapping(mapping)
+}
+
+fn names_to_mapping(value: Value) -> Value {
+    match value {
+        Value::Sequence(names) => Value::Mapping(names.into_iter().map(|name| (name, Value::Null)).collect()),
+        other => other,
+    }
+}
+
+// Service volumes are unique by mount target; a later definition for the same target wins
+fn merge_by_target(base: Vec<Value>, overlay: Vec<Value>) -> Vec<Value> {
+    let overlay_targets: Vec<Option<String>> = overlay.iter().map(volume_target).collect();
+    let mut merged: Vec<Value> = base
+        .into_iter()
+        .filter(|item| {
+            let target = volume_target(item);
+            target.is_none() || !overlay_targets.contains(&target)
+        })
+        .collect();
+    merged.extend(overlay);
+    merged
+}
+
+fn volume_target(volume: &Value) -> Option<String> {
+    match volume {
+        Value::String(spec) => {
+            let parts: Vec<&str> = spec.split(':').collect();
+            let target = if parts.len() == 1 { parts[0] } else { parts[1] };
+            Some(target.trim_end_matches('/').to_string())
+        }
+        Value::Mapping(map) => map.get("target").and_then(Value::as_str).map(|t| t.trim_end_matches('/').to_string()),
+        _ => None,
+    }
+}
+
+/// Merges an override file into a base compose document following the compose specification:
+/// mappings merge recursively, most lists are appended, and scalars, `command` and `entrypoint` are replaced.
+/// `!reset` removes a value and `!override` replaces it without merging.
+pub fn merge_compose_documents(base: Value, overlay: Value) -> Value {
+    let (mut base, mut overlay) = match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => (base, overlay),
+        (base, Value::Null) => return base,
+        (_, overlay) => return overlay,
+    };
+
+    let base_services = base.remove("services");
+    let overlay_services = overlay.remove("services");
+    let mut merged = match merge_mapping(Value::Mapping(base), Value::Mapping(overlay)) {
+        Value::Mapping(merged) => merged,
+        _ => Mapping::new(),
+    };
+
+    let services = match (base_services, overlay_services) {
+        (Some(Value::Mapping(mut base)), Some(Value::Mapping(overlay))) => {
+            for (name, service) in overlay {
+                let merged_service = match base.remove(&name) {
+                    Some(existing) => merge_service(existing, service),
+                    None => service,
+                };
+                base.insert(name, merged_service);
+            }
+            Some(Value::Mapping(base))
+        }
+        (base, overlay) => overlay.or(base),
+    };
+    if let Some(services) = services {
+        merged.insert(Value::from("services"), services);
+    }
+
+    Value::Mapping(merged)
+}
+
+// Service-level `networks` gets its own rule since the top-level `networks` is a plain map
+fn merge_service(base: Value, overlay: Value) -> Value {
+    let (mut base, overlay) = match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => (base, overlay),
+        (base, Value::Null) => return base,
+        (_, overlay) => return overlay,
+    };
+
+    for (key, value) in overlay {
+        if matches!(&value, Value::Tagged(tagged) if tagged.tag == "reset") {
+            base.remove(&key);
+            continue;
+        }
+        let name = match key.as_str() {
+            Some("networks") => "service_networks".to_string(),
+            other => other.unwrap_or_default().to_string(),
+        };
+        let merged = match base.remove(&key) {
+            Some(existing) => merge_value(existing, value, &name),
+            None => strip_tags(value),
+        };
+        base.insert(key, merged);
+    }
+
+    Value::Mapping(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(base: &str, overlay: &str) -> Value {
+        merge_compose_documents(serde_yaml::from_str(base).unwrap(), serde_yaml::from_str(overlay).unwrap())
+    }
+
+    #[test]
+    fn test_merge_service_keys() {
+        let merged = merge(
+            r#"
+services:
+  web:
+    image: app:1
+    command: ["serve", "--port", "80"]
+    environment: [MODE=prod, LOG=info]
+    ports: ["80:80"]
+    volumes: ["data:/data", "./conf:/etc/app"]
+    depends_on: [db]
+    networks: [front]
+"#,
+            r#"
+services:
+  web:
+    image: app:dev
+    command: ["serve", "--reload"]
+    environment:
+      MODE: dev
+    ports: ["9229:9229"]
+    volumes: ["./src:/data"]
+    depends_on:
+      cache:
+        condition: service_started
+    networks: [back]
+  cache:
+    image: redis
+"#,
+        );
+
+        let web = &merged["services"]["web"];
+        assert_eq!(web["image"], Value::from("app:dev"));
+        assert_eq!(web["command"], serde_yaml::from_str::<Value>("[serve, --reload]").unwrap());
+        assert_eq!(web["environment"]["MODE"], Value::from("dev"));
+        assert_eq!(web["environment"]["LOG"], Value::from("info"));
+        assert_eq!(web["ports"], serde_yaml::from_str::<Value>("[\"80:80\", \"9229:9229\"]").unwrap());
+        assert_eq!(web["volumes"], serde_yaml::from_str::<Value>("[\"./conf:/etc/app\", \"./src:/data\"]").unwrap());
+        assert!(web["depends_on"].get("db").is_some());
+        assert_eq!(web["depends_on"]["cache"]["condition"], Value::from("service_started"));
+        assert!(web["networks"].get("front").is_some() && web["networks"].get("back").is_some());
+        assert_eq!(merged["services"]["cache"]["image"], Value::from("redis"));
+    }
+
+    #[test]
+    fn test_merge_reset_and_override() {
+        let merged = merge(
+            "services:\n  web:\n    image: app\n    ports: [\"80:80\"]\n    labels: { a: \"1\" }\nvolumes:\n  data: { driver: local }\n",
+            "services:\n  web:\n    ports: !reset []\n    labels: !override { b: \"2\" }\nvolumes:\n  data: { name: shared }\n",
+        );
+        let web = &merged["services"]["web"];
+        assert!(web.get("ports").is_none());
+        assert!(web["labels"].get("a").is_none());
+        assert_eq!(web["labels"]["b"], Value::from("2"));
+        assert_eq!(merged["volumes"]["data"]["driver"], Value::from("local"));
+        assert_eq!(merged["volumes"]["data"]["name"], Value::from("shared"));
+    }
+}
@@ -0,0 +1,288 @@
+use super::CONTAINER_NUMBER_LABEL;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// What deploying a compose file will do to a service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanAction {
+    Create,
+    Recreate,
+    Unchanged,
+    /// A container of the project whose service is no longer in the file
+    Orphaned,
+    /// A replica beyond the number the service asks for
+    Remove,
+}
+
+impl PlanAction {
+    /// What to do with an existing container given the settings that changed
+    pub fn for_changes(changes: &[String]) -> Self {
+        if changes.is_empty() { PlanAction::Unchanged } else { PlanAction::Recreate }
+    }
+}
+
+/// The parts of a container's configuration that decide whether it must be recreated
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContainerSpec {
+    pub image: String,
+    pub image_id: Option<String>,
+    pub env: Vec<String>,
+    /// (container port, host IP, host port)
+    pub ports: BTreeSet<(String, String, String)>,
+    /// (type, source, target, read-only)
+    pub mounts: BTreeSet<(String, String, String, bool)>,
+    pub command: Option<Vec<String>>,
+    pub network: Option<String>,
+    pub memory: Option<i64>,
+    pub cpu_quota: Option<i64>,
+    /// `CONFIG_HASH_LABEL`, which covers the settings not compared one by one
+    pub config_hash: Option<String>,
+}
+
+impl ContainerSpec {
+    pub fn add_port(&mut self, container_port: &str, host_ip: Option<&str>, host_port: Option<&str>) {
+        // The daemon reports an unset host IP as either "" or "0.0.0.0"
+        let host_ip = host_ip.filter(|ip| !ip.is_empty() && *ip != "0.0.0.0").unwrap_or_default();
+        self.ports.insert((container_port.to_string(), host_ip.to_string(), host_port.unwrap_or_default().to_string()));
+    }
+
+    pub fn add_mount(&mut self, kind: &str, source: Option<&str>, target: &str, read_only: bool) {
+        let target = target.trim_end_matches('/');
+        self.mounts.insert((kind.to_lowercase(), source.unwrap_or_default().to_string(), target.to_string(), read_only));
+    }
+
+    /// Adds a legacy `source:target[:options]` bind
+    pub fn add_bind(&mut self, bind: &str) {
+        let parts: Vec<&str> = bind.split(':').collect();
+        if parts.len() < 2 {
+            return;
+        }
+        let read_only = parts.get(2).is_some_and(|options| options.split(',').any(|o| o == "ro"));
+        let kind = if parts[0].starts_with('/') { "bind" } else { "volume" };
+        self.add_mount(kind, Some(parts[0]), parts[1], read_only);
+    }
+}
+
+/// Lists the settings in which the running container differs from the desired one.
+/// `image_env` is the environment baked into the image, which the daemon merges into the container's.
+pub fn diff_specs(desired: &ContainerSpec, actual: &ContainerSpec, image_env: Option<&[String]>) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    let image_changed = match (&desired.image_id, &actual.image_id) {
+        (Some(desired_id), Some(actual_id)) => desired_id != actual_id,
+        _ => desired.image != actual.image,
+    };
+    if image_changed {
+        changes.push("image".to_string());
+    }
+
+    if env_changed(&desired.env, &actual.env, image_env) {
+        changes.push("environment".to_string());
+    }
+    if desired.ports != actual.ports {
+        changes.push("ports".to_string());
+    }
+    if desired.mounts != actual.mounts {
+        changes.push("volumes".to_string());
+    }
+    // Without a command the image default applies, which the container reports as its own
+    if desired.command.is_some() && desired.command != actual.command {
+        changes.push("command".to_string());
+    }
+    // The daemon names a joined container by its ID; joining another one changes the config hash
+    let joins_container = |spec: &ContainerSpec| spec.network.as_deref().is_some_and(|n| n.starts_with("container:"));
+    if desired.network.is_some() && desired.network != actual.network && !(joins_container(desired) && joins_container(actual)) {
+        changes.push("networks".to_string());
+    }
+    if desired.memory.unwrap_or(0) != actual.memory.unwrap_or(0) {
+        changes.push("mem_limit".to_string());
+    }
+    if desired.cpu_quota.unwrap_or(0) != actual.cpu_quota.unwrap_or(0) {
+        changes.push("cpus".to_string());
+    }
+    // Containers created before the label existed have no hash and are recreated once
+    if changes.is_empty() && desired.config_hash.is_some() && desired.config_hash != actual.config_hash {
+        changes.push("config".to_string());
+    }
+
+    changes
+}
+
+/// Hashes a container's full create request. Object keys are visited in sorted order,
+/// so the result doesn't depend on how the request was built. What tells the replicas of a
+/// service apart is left out, the `name` and the `CONTAINER_NUMBER_LABEL` among the `labels`,
+/// so that every replica, including those `compose_scale` copies, hashes the same.
+pub fn config_hash(config: &serde_json::Value) -> String {
+    // 64-bit FNV-1a, which is stable across builds unlike the standard library's hasher
+    fn feed(hash: &mut u64, bytes: &[u8]) {
+        for byte in bytes {
+            *hash ^= u64::from(*byte);
+            *hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    fn visit(hash: &mut u64, value: &serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                feed(hash, b"{");
+                for key in keys {
+                    feed(hash, serde_json::Value::from(key.as_str()).to_string().as_bytes());
+                    feed(hash, b":");
+                    visit(hash, &map[key]);
+                    feed(hash, b",");
+                }
+                feed(hash, b"}");
+            }
+            serde_json::Value::Array(items) => {
+                feed(hash, b"[");
+                for item in items {
+                    visit(hash, item);
+                    feed(hash, b",");
+                }
+                feed(hash, b"]");
+            }
+            scalar => feed(hash, scalar.to_string().as_bytes()),
+        }
+    }
+
+    let mut config = config.clone();
+    if let Some(request) = config.as_object_mut() {
+        request.remove("name");
+        if let Some(labels) = request.get_mut("labels").and_then(|labels| labels.as_object_mut()) {
+            labels.remove(CONTAINER_NUMBER_LABEL);
+        }
+    }
+
+    let mut hash = 0xcbf2_9ce4_8422_2325;
+    visit(&mut hash, &config);
+    format!("{:016x}", hash)
+}
+
+fn env_changed(desired: &[String], actual: &[String], image_env: Option<&[String]>) -> bool {
+    let key = |entry: &str| entry.split('=').next().unwrap_or_default().to_string();
+    let desired_keys: HashMap<String, &String> = desired.iter().map(|e| (key(e), e)).collect();
+
+    if desired.iter().any(|entry| !actual.contains(entry)) {
+        return true;
+    }
+
+    // Anything else the container has must come from the image
+    match image_env {
+        Some(image_env) => actual
+            .iter()
+            .filter(|entry| !desired_keys.contains_key(&key(entry)))
+            .any(|entry| !image_env.contains(entry)),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> ContainerSpec {
+        let mut spec = ContainerSpec {
+            image: "nginx:latest".to_string(),
+            image_id: Some("sha256:1".to_string()),
+            env: vec!["MODE=prod".to_string()],
+            command: None,
+            network: Some("shop_default".to_string()),
+            memory: Some(512 * 1024 * 1024),
+            ..Default::default()
+        };
+        spec.add_port("80/tcp", None, Some("8080"));
+        spec.add_mount("volume", Some("shop_data"), "/data", false);
+        spec
+    }
+
+    #[test]
+    fn test_unchanged_container() {
+        let desired = spec();
+        let mut actual = spec();
+        actual.env.push("PATH=/usr/bin".to_string());
+        actual.command = Some(vec!["nginx".to_string(), "-g".to_string()]);
+        actual.ports.clear();
+        actual.add_port("80/tcp", Some("0.0.0.0"), Some("8080"));
+
+        let image_env = vec!["PATH=/usr/bin".to_string()];
+        assert!(diff_specs(&desired, &actual, Some(&image_env)).is_empty());
+    }
+
+    #[test]
+    fn test_changed_container() {
+        let mut desired = spec();
+        desired.image_id = Some("sha256:2".to_string());
+        desired.env = vec!["MODE=dev".to_string()];
+        desired.add_port("443/tcp", None, Some("8443"));
+        desired.memory = None;
+
+        let actual = spec();
+        assert_eq!(diff_specs(&desired, &actual, Some(&[])), vec!["image", "environment", "ports", "mem_limit"]);
+    }
+
+    #[test]
+    fn test_removed_environment_variable() {
+        let desired = spec();
+        let mut actual = spec();
+        actual.env.push("OLD=1".to_string());
+        assert_eq!(diff_specs(&desired, &actual, Some(&[])), vec!["environment"]);
+        // Unknown image environment can't tell leftovers apart from image defaults
+        assert!(diff_specs(&desired, &actual, None).is_empty());
+    }
+
+    #[test]
+    fn test_config_hash() {
+        let config = serde_json::json!({
+            "image": "nginx:latest",
+            "labels": { "tier": "web", "team": "shop" },
+            "healthcheck": { "test": ["CMD", "true"], "interval": 30_000_000_000_i64 },
+            "restart_policy": "always",
+        });
+        let reordered = serde_json::json!({
+            "restart_policy": "always",
+            "healthcheck": { "interval": 30_000_000_000_i64, "test": ["CMD", "true"] },
+            "labels": { "team": "shop", "tier": "web" },
+            "image": "nginx:latest",
+        });
+        assert_eq!(config_hash(&config), config_hash(&reordered));
+
+        let with_hash = |config: &serde_json::Value| ContainerSpec { config_hash: Some(config_hash(config)), ..spec() };
+        let actual = with_hash(&config);
+        assert_eq!(PlanAction::for_changes(&diff_specs(&with_hash(&config), &actual, Some(&[]))), PlanAction::Unchanged);
+
+        let mut changed = config.clone();
+        changed["labels"]["tier"] = "api".into();
+        changed["healthcheck"]["test"] = serde_json::json!(["CMD-SHELL", "curl -f localhost"]);
+        changed["restart_policy"] = "on-failure:3".into();
+        let changes = diff_specs(&with_hash(&changed), &actual, Some(&[]));
+        assert_eq!(changes, vec!["config"]);
+        assert_eq!(PlanAction::for_changes(&changes), PlanAction::Recreate);
+
+        // A replica `compose_scale` added keeps the labels of the one it copies, its number aside
+        let replica = |number: &str| {
+            let mut config = config.clone();
+            config["name"] = format!("shop-web-{}", number).into();
+            config["labels"][CONTAINER_NUMBER_LABEL] = number.into();
+            config
+        };
+        let template = with_hash(&replica("1"));
+        let scaled = ContainerSpec { config_hash: template.config_hash.clone(), ..spec() };
+        let changes = diff_specs(&with_hash(&replica("2")), &scaled, Some(&[]));
+        assert_eq!(PlanAction::for_changes(&changes), PlanAction::Unchanged);
+
+        // A container from before the label existed
+        let unlabelled = ContainerSpec { config_hash: None, ..actual.clone() };
+        assert_eq!(diff_specs(&with_hash(&config), &unlabelled, Some(&[])), vec!["config"]);
+    }
+
+    #[test]
+    fn test_binds() {
+        let mut spec = ContainerSpec::default();
+        spec.add_bind("/srv/config:/etc/app:ro,z");
+        spec.add_bind("data:/data");
+        assert!(spec.mounts.contains(&("bind".to_string(), "/srv/config".to_string(), "/etc/app".to_string(), true)));
+        assert!(spec.mounts.contains(&("volume".to_string(), "data".to_string(), "/data".to_string(), false)));
+    }
+}
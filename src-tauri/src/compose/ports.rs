@@ -0,0 +1,196 @@
+use super::{number_or_string, yaml_scalar_to_string};
+use serde::{Deserialize, Serialize};
+
+/// A `ports` entry: a bare container port, the short `[ip:][host:]container[/proto]` string, or the long syntax
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ComposePort {
+    Number(u32),
+    Short(String),
+    Long(ComposePortLong),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposePortLong {
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub target: Option<u16>,
+    pub published: Option<serde_yaml::Value>,
+    pub host_ip: Option<String>,
+    pub protocol: Option<String>,
+    pub mode: Option<String>,
+    pub name: Option<String>,
+    pub app_protocol: Option<String>,
+}
+
+/// One container port together with how it is published on the host
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortPublish {
+    pub container_port: u16,
+    pub protocol: String,
+    pub host_ip: Option<String>,
+    /// Host port or port range; `None` lets the daemon pick a free port
+    pub host_port: Option<String>,
+}
+
+impl PortPublish {
+    /// Key used by the Docker API for exposed ports and port bindings, e.g. `80/tcp`
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.container_port, self.protocol)
+    }
+}
+
+fn parse_port_number(value: &str, spec: &str) -> Result<u16, String> {
+    match value.trim().parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!("Invalid port '{}' in \"{}\"", value, spec)),
+    }
+}
+
+// Parses "8080" or "8080-8090" into an inclusive range
+fn parse_port_range(value: &str, spec: &str) -> Result<(u16, u16), String> {
+    match value.split_once('-') {
+        Some((start, end)) => {
+            let (start, end) = (parse_port_number(start, spec)?, parse_port_number(end, spec)?);
+            if start > end {
+                return Err(format!("Invalid port range '{}' in \"{}\"", value, spec));
+            }
+            Ok((start, end))
+        }
+        None => {
+            let port = parse_port_number(value, spec)?;
+            Ok((port, port))
+        }
+    }
+}
+
+fn expand(
+    spec: &str,
+    container: &str,
+    host: Option<&str>,
+    host_ip: Option<String>,
+    protocol: &str,
+) -> Result<Vec<PortPublish>, String> {
+    let (container_start, container_end) = parse_port_range(container, spec)?;
+    let count = container_end - container_start;
+    let host = host.map(str::trim).filter(|h| !h.is_empty());
+
+    let host_ports: Vec<Option<String>> = match host {
+        None => vec![None; count as usize + 1],
+        Some(host) => {
+            let (host_start, host_end) = parse_port_range(host, spec)?;
+            if count == 0 {
+                // A single container port may be published on any port of a host range
+                vec![Some(host.to_string())]
+            } else if host_end - host_start == count {
+                (host_start..=host_end).map(|p| Some(p.to_string())).collect()
+            } else {
+                return Err(format!("Host and container port ranges differ in size in \"{}\"", spec));
+            }
+        }
+    };
+
+    Ok((container_start..=container_end)
+        .zip(host_ports)
+        .map(|(container_port, host_port)| PortPublish {
+            container_port,
+            protocol: protocol.to_string(),
+            host_ip: host_ip.clone(),
+            host_port,
+        })
+        .collect())
+}
+
+fn parse_short(spec: &str) -> Result<Vec<PortPublish>, String> {
+    let (address, protocol) = match spec.rsplit_once('/') {
+        Some((address, protocol)) => (address, protocol.to_lowercase()),
+        None => (spec, "tcp".to_string()),
+    };
+
+    // IPv6 host addresses are written in brackets, e.g. "[::1]:8080:80"
+    let (host_ip, rest) = match address.strip_prefix('[') {
+        Some(bracketed) => {
+            let (ip, rest) = bracketed
+                .split_once("]:")
+                .ok_or_else(|| format!("Invalid port specification \"{}\"", spec))?;
+            (Some(ip.to_string()), rest)
+        }
+        None => (None, address),
+    };
+
+    let parts: Vec<&str> = rest.split(':').collect();
+    match (host_ip, parts.as_slice()) {
+        (None, [container]) => expand(spec, container, None, None, &protocol),
+        (None, [host, container]) => expand(spec, container, Some(host), None, &protocol),
+        (None, [ip, host, container]) => expand(spec, container, Some(host), Some(ip.to_string()), &protocol),
+        (Some(ip), [host, container]) => expand(spec, container, Some(host), Some(ip), &protocol),
+        _ => Err(format!("Invalid port specification \"{}\"", spec)),
+    }
+}
+
+/// Expands a `ports` entry into the individual container ports it publishes
+pub fn parse_port(port: &ComposePort) -> Result<Vec<PortPublish>, String> {
+    match port {
+        ComposePort::Number(number) => parse_short(&number.to_string()),
+        ComposePort::Short(spec) => parse_short(spec.trim()),
+        ComposePort::Long(long) => {
+            let target = long.target.ok_or("Port definition is missing 'target'")?;
+            let published = long.published.as_ref().and_then(yaml_scalar_to_string);
+            let protocol = long.protocol.clone().unwrap_or_else(|| "tcp".to_string()).to_lowercase();
+            let spec = format!("{}:{}/{}", published.as_deref().unwrap_or(""), target, protocol);
+            expand(&spec, &target.to_string(), published.as_deref(), long.host_ip.clone(), &protocol)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn short(spec: &str) -> Vec<PortPublish> {
+        parse_port(&ComposePort::Short(spec.to_string())).unwrap()
+    }
+
+    fn publish(container_port: u16, protocol: &str, host_ip: Option<&str>, host_port: Option<&str>) -> PortPublish {
+        PortPublish {
+            container_port,
+            protocol: protocol.to_string(),
+            host_ip: host_ip.map(str::to_string),
+            host_port: host_port.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_short_syntax() {
+        assert_eq!(short("80"), vec![publish(80, "tcp", None, None)]);
+        assert_eq!(short("8080:80"), vec![publish(80, "tcp", None, Some("8080"))]);
+        assert_eq!(short("53:53/udp"), vec![publish(53, "udp", None, Some("53"))]);
+        assert_eq!(short("127.0.0.1:8080:80"), vec![publish(80, "tcp", Some("127.0.0.1"), Some("8080"))]);
+        assert_eq!(short("127.0.0.1::80"), vec![publish(80, "tcp", Some("127.0.0.1"), None)]);
+        assert_eq!(short("[::1]:8080:80"), vec![publish(80, "tcp", Some("::1"), Some("8080"))]);
+        assert_eq!(short("9000-9010:80"), vec![publish(80, "tcp", None, Some("9000-9010"))]);
+        assert_eq!(
+            short("8080-8081:80-81"),
+            vec![publish(80, "tcp", None, Some("8080")), publish(81, "tcp", None, Some("8081"))]
+        );
+        assert_eq!(parse_port(&ComposePort::Number(3000)).unwrap(), vec![publish(3000, "tcp", None, None)]);
+    }
+
+    #[test]
+    fn test_short_syntax_errors() {
+        assert!(parse_port(&ComposePort::Short("8080-8090:80-81".to_string())).is_err());
+        assert!(parse_port(&ComposePort::Short("http:80".to_string())).is_err());
+        assert!(parse_port(&ComposePort::Short("0:80".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_long_syntax() {
+        let port: ComposePort = serde_yaml::from_str("{ target: 80, published: \"8080\", host_ip: 127.0.0.1, protocol: UDP }").unwrap();
+        assert_eq!(parse_port(&port).unwrap(), vec![publish(80, "udp", Some("127.0.0.1"), Some("8080"))]);
+
+        let port: ComposePort = serde_yaml::from_str("{ target: 443, published: 8443 }").unwrap();
+        assert_eq!(parse_port(&port).unwrap(), vec![publish(443, "tcp", None, Some("8443"))]);
+
+        let port: ComposePort = serde_yaml::from_str("{ target: 80 }").unwrap();
+        assert_eq!(parse_port(&port).unwrap(), vec![publish(80, "tcp", None, None)]);
+    }
+}
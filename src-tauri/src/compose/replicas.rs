@@ -0,0 +1,88 @@
+use super::number_or_string;
+use serde::{Deserialize, Serialize};
+
+/// The `deploy` section of a service; only the replica count is used outside of Swarm
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeDeploy {
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub replicas: Option<u32>,
+}
+
+/// Name compose gives to the `number`th container of a service
+pub fn replica_name(project: &str, service_name: &str, number: u32) -> String {
+    format!("{}-{}-{}", project, service_name, number)
+}
+
+/// A single published host port can only be bound by one container; ranges and ports Docker picks can be shared
+pub fn is_fixed_host_port(host_port: &str) -> bool {
+    !host_port.is_empty() && !host_port.contains('-')
+}
+
+/// Checks that a service can run `count` containers side by side
+pub fn check_scalable(
+    service_name: &str,
+    container_name: Option<&str>,
+    host_ports: &[String],
+    addresses: &[String],
+    count: u32,
+) -> Result<(), String> {
+    if count <= 1 {
+        return Ok(());
+    }
+    if let Some(name) = container_name {
+        return Err(format!(
+            "Service '{}' can't be scaled to {} containers because it sets container_name '{}'",
+            service_name, count, name
+        ));
+    }
+    if let Some(port) = host_ports.iter().find(|port| is_fixed_host_port(port)) {
+        return Err(format!(
+            "Service '{}' can't be scaled to {} containers because host port {} can only be published once",
+            service_name, count, port
+        ));
+    }
+    if let Some(address) = addresses.first() {
+        return Err(format!(
+            "Service '{}' can't be scaled to {} containers because IP address {} can only be assigned once",
+            service_name, count, address
+        ));
+    }
+    Ok(())
+}
+
+/// Container numbers to create and to remove so that exactly `1..=count` remain.
+/// Gaps are filled before new numbers are used, and the highest numbers are removed first.
+pub fn scale_numbers(existing: &[u32], count: u32) -> (Vec<u32>, Vec<u32>) {
+    let to_add = (1..=count).filter(|n| !existing.contains(n)).collect();
+    let mut to_remove: Vec<u32> = existing.iter().copied().filter(|n| *n > count || *n == 0).collect();
+    to_remove.sort_unstable_by(|a, b| b.cmp(a));
+    to_remove.dedup();
+    (to_add, to_remove)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_scalable() {
+        assert!(check_scalable("web", Some("web"), &["8080".to_string()], &["172.20.0.10".to_string()], 1).is_ok());
+        assert!(check_scalable("web", None, &["8000-8010".to_string(), String::new()], &[], 3).is_ok());
+
+        let error = check_scalable("web", Some("my-web"), &[], &[], 2).unwrap_err();
+        assert!(error.contains("container_name 'my-web'"));
+        let error = check_scalable("web", None, &["8080".to_string()], &[], 2).unwrap_err();
+        assert!(error.contains("host port 8080"));
+        let error = check_scalable("web", None, &[], &["172.20.0.10".to_string()], 2).unwrap_err();
+        assert!(error.contains("IP address 172.20.0.10"));
+    }
+
+    #[test]
+    fn test_scale_numbers() {
+        assert_eq!(scale_numbers(&[1], 3), (vec![2, 3], vec![]));
+        assert_eq!(scale_numbers(&[1, 3], 3), (vec![2], vec![]));
+        assert_eq!(scale_numbers(&[1, 2, 3, 4], 2), (vec![], vec![4, 3]));
+        assert_eq!(scale_numbers(&[2, 5], 2), (vec![1], vec![5]));
+        assert_eq!(scale_numbers(&[1, 2], 0), (vec![], vec![2, 1]));
+    }
+}
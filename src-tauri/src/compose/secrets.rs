@@ -0,0 +1,183 @@
+use super::{resolve_host_path, scalar_string, External};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A top-level `secrets` or `configs` entry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeFileObject {
+    pub name: Option<String>,
+    pub file: Option<String>,
+    pub environment: Option<String>,
+    pub content: Option<String>,
+    pub external: Option<External>,
+}
+
+/// A service's `secrets` or `configs` entry: the name of a top-level entry, or a detailed reference
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ServiceFileReference {
+    Short(String),
+    Long(ServiceFileReferenceLong),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceFileReferenceLong {
+    pub source: String,
+    pub target: Option<String>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub uid: Option<String>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub gid: Option<String>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub mode: Option<String>,
+}
+
+impl ServiceFileReference {
+    pub fn source(&self) -> &str {
+        match self {
+            ServiceFileReference::Short(source) => source,
+            ServiceFileReference::Long(long) => &long.source,
+        }
+    }
+
+    fn target(&self) -> Option<&str> {
+        match self {
+            ServiceFileReference::Short(_) => None,
+            ServiceFileReference::Long(long) => long.target.as_deref(),
+        }
+    }
+
+    fn sets_ownership(&self) -> bool {
+        match self {
+            ServiceFileReference::Short(_) => false,
+            ServiceFileReference::Long(long) => long.uid.is_some() || long.gid.is_some() || long.mode.is_some(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileObjectKind {
+    Secret,
+    Config,
+}
+
+impl FileObjectKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileObjectKind::Secret => "secret",
+            FileObjectKind::Config => "config",
+        }
+    }
+
+    // Secrets land in /run/secrets unless the target is absolute; configs at the root of the filesystem
+    fn target_path(&self, reference: &ServiceFileReference) -> String {
+        let target = reference.target().unwrap_or(reference.source());
+        match self {
+            _ if target.starts_with('/') => target.to_string(),
+            FileObjectKind::Secret => format!("/run/secrets/{}", target),
+            FileObjectKind::Config => format!("/{}", target),
+        }
+    }
+}
+
+/// A secret or config file to bind-mount read-only into a container
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileMount {
+    pub source: String,
+    pub target: String,
+}
+
+/// Resolves a service's secret or config references to the host files backing them.
+/// Only `file` sources can be mounted without Swarm; a missing file is an error.
+pub fn resolve_file_mounts(
+    kind: FileObjectKind,
+    references: &[ServiceFileReference],
+    definitions: &HashMap<String, Option<ComposeFileObject>>,
+    base_dir: Option<&Path>,
+) -> Result<Vec<FileMount>, String> {
+    let mut mounts = Vec::new();
+    for reference in references {
+        let name = reference.source();
+        let definition = definitions
+            .get(name)
+            .ok_or_else(|| format!("Undefined {} '{}'", kind.as_str(), name))?
+            .clone()
+            .unwrap_or_default();
+
+        let file = match (&definition.file, &definition.external) {
+            (Some(file), _) => file,
+            (None, Some(_)) => return Err(format!("External {} '{}' requires Swarm mode", kind.as_str(), name)),
+            (None, None) => {
+                return Err(format!("Only file-based {}s are supported, but '{}' has no 'file'", kind.as_str(), name))
+            }
+        };
+        let source = resolve_host_path(file, base_dir)?;
+        if !Path::new(&source).exists() {
+            return Err(format!("File '{}' of {} '{}' does not exist", source, kind.as_str(), name));
+        }
+
+        mounts.push(FileMount {
+            source,
+            target: kind.target_path(reference),
+        });
+    }
+    Ok(mounts)
+}
+
+/// Warnings for references whose uid, gid or mode can't be honoured because the file is bind-mounted
+pub fn ownership_warnings(kind: FileObjectKind, references: &[ServiceFileReference]) -> Vec<String> {
+    references
+        .iter()
+        .filter(|reference| reference.sets_ownership())
+        .map(|reference| {
+            format!(
+                "uid, gid and mode of {} '{}' were ignored; the file keeps its ownership and permissions on the host",
+                kind.as_str(),
+                reference.source()
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definitions(yaml: &str) -> HashMap<String, Option<ComposeFileObject>> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_file_mounts() {
+        let dir = std::env::temp_dir().join(format!("dock-secrets-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("db_password.txt"), "hunter2").unwrap();
+        std::fs::write(dir.join("nginx.conf"), "events {}").unwrap();
+
+        let secrets = definitions("db_password: { file: ./db_password.txt }\nmissing: { file: ./missing.txt }\napi_key: { environment: API_KEY }\n");
+        let references: Vec<ServiceFileReference> =
+            serde_yaml::from_str("[db_password, { source: db_password, target: password, mode: 0440 }]").unwrap();
+        let mounts = resolve_file_mounts(FileObjectKind::Secret, &references, &secrets, Some(&dir)).unwrap();
+        assert_eq!(mounts[0].target, "/run/secrets/db_password");
+        assert_eq!(mounts[1].target, "/run/secrets/password");
+        assert_eq!(mounts[0].source, dir.join("db_password.txt").to_string_lossy());
+        assert_eq!(ownership_warnings(FileObjectKind::Secret, &references).len(), 1);
+
+        let error = resolve_file_mounts(FileObjectKind::Secret, &[ServiceFileReference::Short("missing".to_string())], &secrets, Some(&dir));
+        assert!(error.unwrap_err().contains("does not exist"));
+        let error = resolve_file_mounts(FileObjectKind::Secret, &[ServiceFileReference::Short("api_key".to_string())], &secrets, Some(&dir));
+        assert!(error.is_err());
+        let error = resolve_file_mounts(FileObjectKind::Secret, &[ServiceFileReference::Short("nope".to_string())], &secrets, Some(&dir));
+        assert_eq!(error.unwrap_err(), "Undefined secret 'nope'");
+
+        let configs = definitions("nginx: { file: ./nginx.conf }\n");
+        let references: Vec<ServiceFileReference> =
+            serde_yaml::from_str("[nginx, { source: nginx, target: /etc/nginx/nginx.conf }]").unwrap();
+        let mounts = resolve_file_mounts(FileObjectKind::Config, &references, &configs, Some(&dir)).unwrap();
+        assert_eq!(mounts[0].target, "/nginx");
+        assert_eq!(mounts[1].target, "/etc/nginx/nginx.conf");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
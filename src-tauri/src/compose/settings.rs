@@ -0,0 +1,249 @@
+use super::{number_or_string, MappingOrList};
+use serde::{Deserialize, Serialize};
+
+/// Keys such as `dns` and `tmpfs` take a single string or a list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StringOrList {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl StringOrList {
+    pub fn to_vec(&self) -> Vec<String> {
+        match self {
+            StringOrList::Single(value) => vec![value.clone()],
+            StringOrList::List(values) => values.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeHealthcheck {
+    pub test: Option<StringOrList>,
+    pub interval: Option<String>,
+    pub timeout: Option<String>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub retries: Option<i64>,
+    pub start_period: Option<String>,
+    pub start_interval: Option<String>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub disable: Option<bool>,
+}
+
+impl ComposeHealthcheck {
+    /// The test in Docker API form (`["CMD", ...]`, `["CMD-SHELL", "..."]` or `["NONE"]`)
+    pub fn test_command(&self) -> Option<Vec<String>> {
+        if self.disable == Some(true) {
+            return Some(vec!["NONE".to_string()]);
+        }
+        match &self.test {
+            Some(StringOrList::Single(command)) => Some(vec!["CMD-SHELL".to_string(), command.clone()]),
+            Some(StringOrList::List(test)) => Some(test.clone()),
+            None => None,
+        }
+    }
+}
+
+/// A ulimit is a single value used for both limits, or separate soft and hard limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ComposeUlimit {
+    Single(i64),
+    Range { soft: i64, hard: i64 },
+}
+
+impl ComposeUlimit {
+    pub fn limits(&self) -> (i64, i64) {
+        match self {
+            ComposeUlimit::Single(value) => (*value, *value),
+            ComposeUlimit::Range { soft, hard } => (*soft, *hard),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComposeLogging {
+    pub driver: Option<String>,
+    pub options: Option<MappingOrList>,
+}
+
+/// Parses a compose duration such as `1m30s`, `500ms` or `2h` into nanoseconds
+pub fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    // A bare number is taken as seconds
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some((seconds * 1e9) as i64);
+    }
+
+    let mut total = 0f64;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_len = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest.find(|c: char| c.is_ascii_digit() || c == '.').unwrap_or(rest.len());
+        let nanos = match &rest[..unit_len] {
+            "ns" => 1.0,
+            "us" | "µs" => 1e3,
+            "ms" => 1e6,
+            "s" => 1e9,
+            "m" => 60e9,
+            "h" => 3600e9,
+            _ => return None,
+        };
+        total += number * nanos;
+        rest = &rest[unit_len..];
+    }
+    Some(total as i64)
+}
+
+/// Parses a compose duration into whole seconds for settings the daemon only takes in seconds.
+/// Rounds up, so that a sub-second grace period still gets a second rather than none.
+pub fn parse_duration_secs(value: &str) -> Option<i64> {
+    let nanos = u64::try_from(parse_duration(value)?).ok()?;
+    Some(nanos.div_ceil(1_000_000_000) as i64)
+}
+
+/// Formats nanoseconds as a compose duration such as `1m30s`
+pub fn format_duration(nanos: i64) -> String {
+    const UNITS: [(i64, &str); 6] = [
+        (3_600_000_000_000, "h"),
+        (60_000_000_000, "m"),
+        (1_000_000_000, "s"),
+        (1_000_000, "ms"),
+        (1_000, "us"),
+        (1, "ns"),
+    ];
+    if nanos <= 0 {
+        return "0s".to_string();
+    }
+    let mut rest = nanos;
+    let mut output = String::new();
+    for (size, unit) in UNITS {
+        if rest >= size {
+            output.push_str(&format!("{}{}", rest / size, unit));
+            rest %= size;
+        }
+    }
+    output
+}
+
+/// Splits a command string into arguments the way a POSIX shell would, honouring quotes and backslashes
+pub fn split_shell_words(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(format!("Unterminated quote in \"{}\"", command)),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err(format!("Unterminated quote in \"{}\"", command)),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(format!("Unterminated quote in \"{}\"", command)),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(c) = chars.next() {
+                    current.push(c);
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Some(30_000_000_000));
+        assert_eq!(parse_duration("1m30s"), Some(90_000_000_000));
+        assert_eq!(parse_duration("500ms"), Some(500_000_000));
+        assert_eq!(parse_duration("1.5h"), Some(5_400_000_000_000));
+        assert_eq!(parse_duration("10"), Some(10_000_000_000));
+        assert_eq!(parse_duration("10 parsecs"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+
+    #[test]
+    fn test_parse_duration_secs() {
+        assert_eq!(parse_duration_secs("500ms"), Some(1));
+        assert_eq!(parse_duration_secs("1500ms"), Some(2));
+        assert_eq!(parse_duration_secs("10s"), Some(10));
+        assert_eq!(parse_duration_secs("0s"), Some(0));
+        assert_eq!(parse_duration_secs("-1"), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(30_000_000_000), "30s");
+        assert_eq!(format_duration(90_500_000_000), "1m30s500ms");
+        assert_eq!(format_duration(7_200_000_000_000), "2h");
+        assert_eq!(parse_duration(&format_duration(5_400_000_000_123)), Some(5_400_000_000_123));
+    }
+
+    #[test]
+    fn test_split_shell_words() {
+        assert_eq!(split_shell_words("npm run start").unwrap(), vec!["npm", "run", "start"]);
+        assert_eq!(
+            split_shell_words(r#"sh -c "echo \"hi\" && sleep 1" 'a b' c\ d"#).unwrap(),
+            vec!["sh", "-c", "echo \"hi\" && sleep 1", "a b", "c d"]
+        );
+        assert_eq!(split_shell_words("''").unwrap(), vec![""]);
+        assert!(split_shell_words("echo 'oops").is_err());
+    }
+
+    #[test]
+    fn test_healthcheck_test_command() {
+        let healthcheck: ComposeHealthcheck = serde_yaml::from_str("{ test: curl -f http://localhost, retries: \"3\" }").unwrap();
+        assert_eq!(healthcheck.test_command().unwrap(), vec!["CMD-SHELL", "curl -f http://localhost"]);
+        assert_eq!(healthcheck.retries, Some(3));
+
+        let healthcheck: ComposeHealthcheck = serde_yaml::from_str("{ test: [CMD, pg_isready], disable: true }").unwrap();
+        assert_eq!(healthcheck.test_command().unwrap(), vec!["NONE"]);
+    }
+}
@@ -0,0 +1,269 @@
+use super::{parse_memory_limit, yaml_scalar_to_string};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A service `volumes` entry in short (`source:target:mode`) or long syntax
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ComposeServiceVolume {
+    Short(String),
+    Long(Box<ComposeServiceVolumeLong>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeServiceVolumeLong {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub source: Option<String>,
+    pub target: String,
+    pub read_only: Option<bool>,
+    pub consistency: Option<String>,
+    pub bind: Option<ComposeBindOptions>,
+    pub volume: Option<ComposeVolumeOptions>,
+    pub tmpfs: Option<ComposeTmpfsOptions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeBindOptions {
+    pub propagation: Option<String>,
+    pub create_host_path: Option<bool>,
+    pub selinux: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeVolumeOptions {
+    pub nocopy: Option<bool>,
+    pub subpath: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeTmpfsOptions {
+    pub size: Option<serde_yaml::Value>,
+    pub mode: Option<serde_yaml::Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MountKind {
+    Bind,
+    Volume,
+    Tmpfs,
+}
+
+impl MountKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MountKind::Bind => "bind",
+            MountKind::Volume => "volume",
+            MountKind::Tmpfs => "tmpfs",
+        }
+    }
+}
+
+/// A normalized service mount
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeMount {
+    pub kind: MountKind,
+    /// Host path for binds, volume name for volumes (`None` for anonymous volumes and tmpfs)
+    pub source: Option<String>,
+    pub target: String,
+    pub read_only: bool,
+    pub propagation: Option<String>,
+    pub selinux: Option<String>,
+    pub create_host_path: bool,
+    pub nocopy: bool,
+    pub subpath: Option<String>,
+    pub tmpfs_size: Option<i64>,
+    pub tmpfs_mode: Option<i64>,
+}
+
+impl VolumeMount {
+    fn new(kind: MountKind, source: Option<String>, target: String) -> Self {
+        VolumeMount {
+            kind,
+            source,
+            target,
+            read_only: false,
+            propagation: None,
+            selinux: None,
+            create_host_path: false,
+            nocopy: false,
+            subpath: None,
+            tmpfs_size: None,
+            tmpfs_mode: None,
+        }
+    }
+}
+
+const PROPAGATION_MODES: [&str; 6] = ["private", "rprivate", "shared", "rshared", "slave", "rslave"];
+
+fn is_host_path(source: &str) -> bool {
+    source.starts_with('/') || source.starts_with('.') || source.starts_with('~')
+}
+
+fn parse_short(spec: &str) -> Result<VolumeMount, String> {
+    let parts: Vec<&str> = spec.split(':').collect();
+    let (source, target, mode) = match parts.as_slice() {
+        [target] => (None, *target, None),
+        [source, target] => (Some(*source), *target, None),
+        [source, target, mode] => (Some(*source), *target, Some(*mode)),
+        _ => return Err(format!("Invalid volume specification \"{}\"", spec)),
+    };
+
+    let mut mount = match source {
+        Some(source) if is_host_path(source) => {
+            let mut mount = VolumeMount::new(MountKind::Bind, Some(source.to_string()), target.to_string());
+            // The short syntax creates missing host directories, like `docker run -v` does
+            mount.create_host_path = true;
+            mount
+        }
+        Some(source) => VolumeMount::new(MountKind::Volume, Some(source.to_string()), target.to_string()),
+        None => VolumeMount::new(MountKind::Volume, None, target.to_string()),
+    };
+
+    for option in mode.into_iter().flat_map(|m| m.split(',')) {
+        match option {
+            "ro" => mount.read_only = true,
+            "rw" => mount.read_only = false,
+            "z" | "Z" => mount.selinux = Some(option.to_string()),
+            "nocopy" => mount.nocopy = true,
+            "cached" | "delegated" | "consistent" => {}
+            _ if PROPAGATION_MODES.contains(&option) => mount.propagation = Some(option.to_string()),
+            _ => return Err(format!("Invalid volume mode '{}' in \"{}\"", option, spec)),
+        }
+    }
+
+    Ok(mount)
+}
+
+fn parse_long(long: &ComposeServiceVolumeLong) -> Result<VolumeMount, String> {
+    let kind = match long.kind.as_str() {
+        "bind" => MountKind::Bind,
+        "volume" => MountKind::Volume,
+        "tmpfs" => MountKind::Tmpfs,
+        other => return Err(format!("Unsupported volume type '{}' for '{}'", other, long.target)),
+    };
+    if kind == MountKind::Bind && long.source.is_none() {
+        return Err(format!("Bind mount for '{}' is missing 'source'", long.target));
+    }
+
+    let mut mount = VolumeMount::new(kind, long.source.clone(), long.target.clone());
+    mount.read_only = long.read_only.unwrap_or(false);
+
+    if let Some(bind) = &long.bind {
+        if let Some(propagation) = &bind.propagation {
+            if !PROPAGATION_MODES.contains(&propagation.as_str()) {
+                return Err(format!("Invalid bind propagation '{}' for '{}'", propagation, long.target));
+            }
+        }
+        mount.propagation = bind.propagation.clone();
+        mount.selinux = bind.selinux.clone();
+        mount.create_host_path = bind.create_host_path.unwrap_or(false);
+    }
+    if let Some(volume) = &long.volume {
+        mount.nocopy = volume.nocopy.unwrap_or(false);
+        mount.subpath = volume.subpath.clone();
+    }
+    if let Some(tmpfs) = &long.tmpfs {
+        mount.tmpfs_size = match tmpfs.size.as_ref().and_then(yaml_scalar_to_string) {
+            Some(size) => Some(parse_memory_limit(&size).ok_or_else(|| format!("Invalid tmpfs size '{}'", size))?),
+            None => None,
+        };
+        mount.tmpfs_mode = match tmpfs.mode.as_ref().and_then(yaml_scalar_to_string) {
+            // Modes are octal, whether written as 1777 or "01777"
+            Some(mode) => Some(i64::from_str_radix(&mode, 8).map_err(|_| format!("Invalid tmpfs mode '{}'", mode))?),
+            None => None,
+        };
+    }
+
+    Ok(mount)
+}
+
+/// Normalizes a service `volumes` entry
+pub fn parse_volume(volume: &ComposeServiceVolume) -> Result<VolumeMount, String> {
+    match volume {
+        ComposeServiceVolume::Short(spec) => parse_short(spec.trim()),
+        ComposeServiceVolume::Long(long) => parse_long(long),
+    }
+}
+
+/// Resolves `~` and paths relative to the project directory to an absolute host path
+pub fn resolve_host_path(source: &str, base_dir: Option<&Path>) -> Result<String, String> {
+    if let Some(rest) = source.strip_prefix('~') {
+        let home = std::env::var("HOME").map_err(|_| format!("Cannot resolve '{}': HOME is not set", source))?;
+        return Ok(format!("{}{}", home, rest));
+    }
+    if Path::new(source).is_absolute() {
+        return Ok(source.to_string());
+    }
+    match base_dir {
+        Some(dir) => {
+            let relative = source.strip_prefix("./").unwrap_or(source);
+            Ok(dir.join(relative).to_string_lossy().to_string())
+        }
+        None => Err(format!("Bind mount source '{}' is relative but no project directory was given", source)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn short(spec: &str) -> VolumeMount {
+        parse_volume(&ComposeServiceVolume::Short(spec.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_short_syntax() {
+        let anonymous = short("/var/lib/data");
+        assert_eq!(anonymous.kind, MountKind::Volume);
+        assert_eq!(anonymous.source, None);
+
+        let named = short("data:/var/lib/data:ro");
+        assert_eq!(named.kind, MountKind::Volume);
+        assert_eq!(named.source.as_deref(), Some("data"));
+        assert!(named.read_only);
+
+        let bind = short("./config:/etc/app:ro,z,rshared");
+        assert_eq!(bind.kind, MountKind::Bind);
+        assert!(bind.read_only && bind.create_host_path);
+        assert_eq!(bind.selinux.as_deref(), Some("z"));
+        assert_eq!(bind.propagation.as_deref(), Some("rshared"));
+
+        assert!(parse_volume(&ComposeServiceVolume::Short("a:/b:bogus".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_long_syntax() {
+        let volume: ComposeServiceVolume = serde_yaml::from_str(
+            "{ type: bind, source: ./src, target: /app, read_only: true, bind: { propagation: rslave, create_host_path: true } }",
+        )
+        .unwrap();
+        let mount = parse_volume(&volume).unwrap();
+        assert_eq!(mount.kind, MountKind::Bind);
+        assert!(mount.read_only && mount.create_host_path);
+        assert_eq!(mount.propagation.as_deref(), Some("rslave"));
+
+        let volume: ComposeServiceVolume =
+            serde_yaml::from_str("{ type: volume, source: data, target: /data, volume: { nocopy: true, subpath: sub } }").unwrap();
+        let mount = parse_volume(&volume).unwrap();
+        assert!(mount.nocopy);
+        assert_eq!(mount.subpath.as_deref(), Some("sub"));
+
+        let volume: ComposeServiceVolume = serde_yaml::from_str("{ type: tmpfs, target: /tmp, tmpfs: { size: 64m, mode: 1777 } }").unwrap();
+        let mount = parse_volume(&volume).unwrap();
+        assert_eq!(mount.kind, MountKind::Tmpfs);
+        assert_eq!(mount.tmpfs_size, Some(64 * 1024 * 1024));
+        assert_eq!(mount.tmpfs_mode, Some(0o1777));
+
+        let volume: ComposeServiceVolume = serde_yaml::from_str("{ type: npipe, source: x, target: /x }").unwrap();
+        assert!(parse_volume(&volume).is_err());
+    }
+
+    #[test]
+    fn test_resolve_host_path() {
+        let base = Path::new("/srv/app");
+        assert_eq!(resolve_host_path("./data", Some(base)).unwrap(), "/srv/app/data");
+        assert_eq!(resolve_host_path("/abs", None).unwrap(), "/abs");
+        assert!(resolve_host_path("./data", None).is_err());
+    }
+}
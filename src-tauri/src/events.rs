@@ -0,0 +1,288 @@
+use crate::logs::parse_log_time;
+use bollard::models::{EventMessage, EventMessageTypeEnum};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+/// Object types whose events are forwarded to the frontend
+pub const EVENT_TYPES: [&str; 4] = ["container", "image", "volume", "network"];
+
+/// Container actions that don't change what the container lists show. Polling processes, browsing
+/// files and attaching terminals cause them every few seconds.
+const NOISE_ACTIONS: [&str; 5] = ["top", "archive-path", "extract-to-dir", "attach", "resize"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Container,
+    Image,
+    Volume,
+    Network,
+}
+
+/// A daemon event, as sent to the frontend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DockerEvent {
+    pub kind: EventKind,
+    /// `create`, `start`, `die`, `oom`, `health_status`, `destroy`, `pull`, ...
+    pub action: String,
+    /// What follows the action after a colon, like `healthy` in `health_status: healthy`
+    pub detail: Option<String>,
+    /// Container, volume or network id, or the image reference
+    pub id: String,
+    /// Container, volume, network or image name
+    pub name: Option<String>,
+    /// Labels and details such as `exitCode` for `die`
+    pub attributes: BTreeMap<String, String>,
+    /// Unix time in nanoseconds
+    pub time_nano: i64,
+}
+
+impl DockerEvent {
+    /// Events of other object types, or without an action or actor, are dropped
+    pub fn from_message(message: EventMessage) -> Option<Self> {
+        let kind = match message.typ? {
+            EventMessageTypeEnum::CONTAINER => EventKind::Container,
+            EventMessageTypeEnum::IMAGE => EventKind::Image,
+            EventMessageTypeEnum::VOLUME => EventKind::Volume,
+            EventMessageTypeEnum::NETWORK => EventKind::Network,
+            _ => return None,
+        };
+        let action = message.action?;
+        let (action, detail) = match action.split_once(':') {
+            Some((name, detail)) => (name.to_string(), Some(detail.trim().to_string())),
+            None => (action.clone(), None),
+        };
+        let actor = message.actor?;
+        let attributes: BTreeMap<String, String> = actor.attributes.unwrap_or_default().into_iter().collect();
+        let time_nano = message
+            .time_nano
+            .or_else(|| message.time.map(|seconds| seconds * 1_000_000_000))
+            .unwrap_or(0);
+
+        Some(DockerEvent {
+            kind,
+            action,
+            detail,
+            id: actor.id.unwrap_or_default(),
+            name: attributes.get("name").cloned(),
+            attributes,
+            time_nano,
+        })
+    }
+
+    /// Whether the event is one of the `NOISE_ACTIONS` or an `exec_*` action
+    pub fn is_noise(&self) -> bool {
+        self.kind == EventKind::Container
+            && (self.action.starts_with("exec_") || NOISE_ACTIONS.contains(&self.action.as_str()))
+    }
+}
+
+/// Narrows down the events `query_events` returns
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EventFilter {
+    /// Container id, id prefix or name; only that container's events
+    pub container: Option<String>,
+    pub kind: Option<EventKind>,
+    pub action: Option<String>,
+    /// RFC 3339 time, Unix timestamp, or a duration back from now such as `24h`
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Most recent events to return, 1000 when unset
+    pub limit: Option<usize>,
+}
+
+impl EventFilter {
+    fn matches(&self, event: &DockerEvent, since: Option<i64>, until: Option<i64>) -> bool {
+        let seconds = event.time_nano / 1_000_000_000;
+        let container_matches = self.container.as_deref().is_none_or(|container| {
+            event.kind == EventKind::Container
+                && (event.id.starts_with(container) || event.name.as_deref() == Some(container))
+        });
+        container_matches
+            && self.kind.is_none_or(|kind| event.kind == kind)
+            && self.action.as_deref().is_none_or(|action| event.action == action)
+            && since.is_none_or(|since| seconds >= since)
+            && until.is_none_or(|until| seconds <= until)
+    }
+}
+
+/// Event history kept as JSON Lines in a directory. The current file is `events.jsonl`; once it
+/// reaches `max_bytes` it becomes `events.1.jsonl` and older files shift up, keeping `keep` of them.
+pub struct EventStore {
+    dir: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl EventStore {
+    pub fn new(dir: PathBuf, max_bytes: u64, keep: usize) -> Self {
+        EventStore { dir, max_bytes, keep }
+    }
+
+    fn file(&self, generation: usize) -> PathBuf {
+        match generation {
+            0 => self.dir.join("events.jsonl"),
+            n => self.dir.join(format!("events.{}.jsonl", n)),
+        }
+    }
+
+    pub fn append(&self, event: &DockerEvent) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create event store: {}", e))?;
+        let current = self.file(0);
+        if std::fs::metadata(&current).is_ok_and(|m| m.len() >= self.max_bytes) {
+            self.rotate().map_err(|e| format!("Failed to rotate event store: {}", e))?;
+        }
+
+        let mut line = serde_json::to_string(event).map_err(|e| format!("Failed to serialize event: {}", e))?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&current)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| format!("Failed to write event: {}", e))
+    }
+
+    fn rotate(&self) -> std::io::Result<()> {
+        let oldest = self.file(self.keep);
+        if oldest.exists() {
+            std::fs::remove_file(oldest)?;
+        }
+        for generation in (0..self.keep).rev() {
+            let file = self.file(generation);
+            if file.exists() {
+                std::fs::rename(file, self.file(generation + 1))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Matching events, oldest first, limited to the most recent ones
+    pub fn query(&self, filter: &EventFilter, now: i64) -> Result<Vec<DockerEvent>, String> {
+        let since = filter.since.as_deref().map(|s| parse_log_time(s, now)).transpose()?;
+        let until = filter.until.as_deref().map(|u| parse_log_time(u, now)).transpose()?;
+        let limit = filter.limit.unwrap_or(1000);
+
+        let mut events = Vec::new();
+        for generation in (0..=self.keep).rev() {
+            let Ok(file) = std::fs::File::open(self.file(generation)) else { continue };
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(|e| format!("Failed to read event store: {}", e))?;
+                // A line cut short by a crash is skipped rather than failing the whole query
+                let Ok(event) = serde_json::from_str::<DockerEvent>(&line) else { continue };
+                // History written before noise was left out may still have some
+                if !event.is_noise() && filter.matches(&event, since, until) {
+                    events.push(event);
+                }
+            }
+        }
+
+        let skip = events.len().saturating_sub(limit);
+        Ok(events.split_off(skip))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::models::EventActor;
+    use std::collections::HashMap;
+
+    fn message(typ: EventMessageTypeEnum, action: &str, attributes: &[(&str, &str)]) -> EventMessage {
+        EventMessage {
+            typ: Some(typ),
+            action: Some(action.to_string()),
+            actor: Some(EventActor {
+                id: Some("abc123".to_string()),
+                attributes: Some(attributes.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>()),
+            }),
+            time: Some(1_714_564_800),
+            time_nano: Some(1_714_564_800_123_456_789),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_from_message() {
+        let event = DockerEvent::from_message(message(EventMessageTypeEnum::CONTAINER, "die", &[("name", "web"), ("exitCode", "137")])).unwrap();
+        assert_eq!(event.kind, EventKind::Container);
+        assert_eq!(event.action, "die");
+        assert_eq!(event.detail, None);
+        assert_eq!(event.id, "abc123");
+        assert_eq!(event.name.as_deref(), Some("web"));
+        assert_eq!(event.attributes["exitCode"], "137");
+        assert_eq!(event.time_nano, 1_714_564_800_123_456_789);
+
+        let event = DockerEvent::from_message(message(EventMessageTypeEnum::CONTAINER, "health_status: unhealthy", &[])).unwrap();
+        assert_eq!(event.action, "health_status");
+        assert_eq!(event.detail.as_deref(), Some("unhealthy"));
+        assert_eq!(event.name, None);
+
+        let mut pull = message(EventMessageTypeEnum::IMAGE, "pull", &[("name", "nginx")]);
+        pull.time_nano = None;
+        let event = DockerEvent::from_message(pull).unwrap();
+        assert_eq!(event.kind, EventKind::Image);
+        assert_eq!(event.time_nano, 1_714_564_800_000_000_000);
+
+        assert!(DockerEvent::from_message(message(EventMessageTypeEnum::DAEMON, "reload", &[])).is_none());
+        assert!(DockerEvent::from_message(EventMessage { typ: Some(EventMessageTypeEnum::NETWORK), ..Default::default() }).is_none());
+    }
+
+    #[test]
+    fn test_is_noise() {
+        let event = |typ, action| DockerEvent::from_message(message(typ, action, &[])).unwrap();
+        assert!(event(EventMessageTypeEnum::CONTAINER, "exec_start: sh -c ps").is_noise());
+        assert!(event(EventMessageTypeEnum::CONTAINER, "exec_die").is_noise());
+        assert!(event(EventMessageTypeEnum::CONTAINER, "top").is_noise());
+        assert!(event(EventMessageTypeEnum::CONTAINER, "archive-path").is_noise());
+        assert!(!event(EventMessageTypeEnum::CONTAINER, "health_status: unhealthy").is_noise());
+        assert!(!event(EventMessageTypeEnum::CONTAINER, "die").is_noise());
+        assert!(!event(EventMessageTypeEnum::IMAGE, "pull").is_noise());
+    }
+
+    #[test]
+    fn test_event_store() {
+        let dir = std::env::temp_dir().join(format!("dock-events-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let event = |action: &str, id: &str, name: &str, seconds: i64| DockerEvent {
+            kind: EventKind::Container,
+            action: action.to_string(),
+            detail: None,
+            id: id.to_string(),
+            name: Some(name.to_string()),
+            attributes: BTreeMap::from([("exitCode".to_string(), "1".to_string())]),
+            time_nano: seconds * 1_000_000_000,
+        };
+
+        // Small enough that every append after the first rotates
+        let store = EventStore::new(dir.clone(), 10, 2);
+        store.append(&event("start", "abc123", "web", 100)).unwrap();
+        store.append(&event("die", "abc123", "web", 200)).unwrap();
+        store.append(&event("start", "def456", "db", 300)).unwrap();
+        store.append(&event("die", "def456", "db", 400)).unwrap();
+        assert!(dir.join("events.2.jsonl").exists());
+        assert!(!dir.join("events.3.jsonl").exists());
+        // Noise recorded before it was left out, then a line cut short
+        let mut current = std::fs::OpenOptions::new().append(true).open(dir.join("events.jsonl")).unwrap();
+        writeln!(current, "{}", serde_json::to_string(&event("exec_die", "def456", "db", 410)).unwrap()).unwrap();
+        current.write_all(b"{\"kind\":").unwrap();
+
+        let all = store.query(&EventFilter::default(), 1000).unwrap();
+        // The oldest event was rotated out
+        assert_eq!(all.iter().map(|e| e.time_nano / 1_000_000_000).collect::<Vec<_>>(), vec![200, 300, 400]);
+
+        let filter = EventFilter { container: Some("def".to_string()), action: Some("die".to_string()), ..Default::default() };
+        assert_eq!(store.query(&filter, 1000).unwrap(), vec![event("die", "def456", "db", 400)]);
+        let filter = EventFilter { container: Some("web".to_string()), ..Default::default() };
+        assert_eq!(store.query(&filter, 1000).unwrap().len(), 1);
+        let filter = EventFilter { since: Some("250".to_string()), limit: Some(1), ..Default::default() };
+        assert_eq!(store.query(&filter, 1000).unwrap(), vec![event("die", "def456", "db", 400)]);
+        let filter = EventFilter { kind: Some(EventKind::Image), ..Default::default() };
+        assert!(store.query(&filter, 1000).unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,449 @@
+use bollard::models::{ChangeType, FilesystemChange};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Component, Path};
+
+/// Lists a directory with `stat` when the container can run a shell. Prints, for each entry,
+/// the raw mode in hex, size and mtime, then the name and link target, each ended by a NUL.
+/// Exits non-zero when anything is missing so the caller can fall back to the archive.
+pub const LIST_DIR_SCRIPT: &str = r#"cd -- "$1" || exit 2
+for f in * .[!.]* ..?*; do
+  [ -e "$f" ] || [ -L "$f" ] || continue
+  info=$(stat -c '%f %s %Y' -- "$f") || exit 3
+  link=""
+  if [ -L "$f" ]; then link=$(readlink -- "$f") || exit 3; fi
+  printf '%s\0%s\0%s\0' "$info" "$f" "$link"
+done"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    File,
+    Directory,
+    Symlink,
+    /// Devices, sockets and pipes
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerDirEntry {
+    pub name: String,
+    pub kind: FileKind,
+    pub size: u64,
+    /// Permission bits, like 0o755
+    pub mode: u32,
+    /// Unix time in seconds
+    pub mtime: u64,
+    pub link_target: Option<String>,
+}
+
+/// The start of a file in a container, for previews
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerFile {
+    pub path: String,
+    /// Size of the whole file
+    pub size: u64,
+    /// Empty for binary files
+    pub content: String,
+    /// Only the first `max_bytes` were read
+    pub truncated: bool,
+    pub binary: bool,
+}
+
+fn kind_from_mode(mode: u32) -> FileKind {
+    match mode & 0o170000 {
+        0o040000 => FileKind::Directory,
+        0o100000 => FileKind::File,
+        0o120000 => FileKind::Symlink,
+        _ => FileKind::Other,
+    }
+}
+
+fn sort_entries(entries: &mut [ContainerDirEntry]) {
+    entries.sort_by(|a, b| (a.kind != FileKind::Directory, &a.name).cmp(&(b.kind != FileKind::Directory, &b.name)));
+}
+
+/// Parses what `LIST_DIR_SCRIPT` prints; directories come first, then by name
+pub fn parse_stat_output(output: &str) -> Result<Vec<ContainerDirEntry>, String> {
+    let fields: Vec<&str> = output.split('\0').collect();
+    let mut entries = Vec::new();
+    // The output ends with a NUL, leaving an empty field after the last record
+    for record in fields.chunks_exact(3) {
+        let [info, name, link] = record else { continue };
+        let parsed = match info.split(' ').collect::<Vec<_>>()[..] {
+            [mode, size, mtime] => u32::from_str_radix(mode, 16)
+                .ok()
+                .zip(size.parse::<u64>().ok())
+                .zip(mtime.parse::<u64>().ok()),
+            _ => None,
+        };
+        let ((mode, size), mtime) = parsed.ok_or_else(|| format!("Failed to parse stat output: '{}'", info))?;
+        entries.push(ContainerDirEntry {
+            name: name.to_string(),
+            kind: kind_from_mode(mode),
+            size,
+            mode: mode & 0o7777,
+            mtime,
+            link_target: (!link.is_empty()).then(|| link.to_string()),
+        });
+    }
+    sort_entries(&mut entries);
+    Ok(entries)
+}
+
+fn header_kind(entry_type: tar::EntryType) -> FileKind {
+    match entry_type {
+        tar::EntryType::Directory => FileKind::Directory,
+        tar::EntryType::Symlink => FileKind::Symlink,
+        // Hard links are files that appeared earlier in the archive
+        tar::EntryType::Regular | tar::EntryType::Continuous | tar::EntryType::Link => FileKind::File,
+        _ => FileKind::Other,
+    }
+}
+
+fn normal_components(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Lists the directory an archive from the archive endpoint holds: the entries one level below its
+/// first entry. Works on stopped containers and images without a shell, but reads the whole tree.
+pub fn list_archive_dir<R: Read>(reader: R, path: &str) -> Result<Vec<ContainerDirEntry>, String> {
+    let mut archive = tar::Archive::new(reader);
+    let entries = archive.entries().map_err(|e| format!("Failed to read archive: {}", e))?;
+
+    let mut root: Option<Vec<String>> = None;
+    let mut listing = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read archive: {}", e))?;
+        let components = normal_components(&entry.path().map_err(|e| format!("Failed to read archive: {}", e))?);
+        let header = entry.header();
+
+        let Some(root) = &root else {
+            if !header.entry_type().is_dir() {
+                return Err(format!("'{}' is not a directory", path));
+            }
+            root = Some(components);
+            continue;
+        };
+        if components.len() != root.len() + 1 || !components.starts_with(root) {
+            continue;
+        }
+        listing.push(ContainerDirEntry {
+            name: components[root.len()].clone(),
+            kind: header_kind(header.entry_type()),
+            size: header.size().unwrap_or(0),
+            mode: header.mode().unwrap_or(0) & 0o7777,
+            mtime: header.mtime().unwrap_or(0),
+            link_target: entry
+                .link_name()
+                .ok()
+                .flatten()
+                .filter(|_| header.entry_type().is_symlink())
+                .map(|link| link.to_string_lossy().to_string()),
+        });
+    }
+
+    if root.is_none() {
+        return Err(format!("'{}' does not exist", path));
+    }
+    sort_entries(&mut listing);
+    Ok(listing)
+}
+
+/// Reads up to `max_bytes` of the file an archive from the archive endpoint holds.
+/// Stops reading there, so the rest of a large file isn't downloaded.
+pub fn read_archive_file<R: Read>(reader: R, path: &str, max_bytes: u64) -> Result<ContainerFile, String> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = archive.entries().map_err(|e| format!("Failed to read archive: {}", e))?;
+    let entry = entries
+        .next()
+        .ok_or_else(|| format!("'{}' does not exist", path))?
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+
+    let entry_type = entry.header().entry_type();
+    if entry_type.is_dir() {
+        return Err(format!("'{}' is a directory", path));
+    }
+    if entry_type.is_symlink() {
+        let target = entry.link_name().ok().flatten().map(|link| link.to_string_lossy().to_string()).unwrap_or_default();
+        return Err(format!("'{}' is a symlink to '{}'", path, target));
+    }
+    if header_kind(entry_type) != FileKind::File {
+        return Err(format!("'{}' is not a regular file", path));
+    }
+
+    let size = entry.header().size().unwrap_or(0);
+    let mut bytes = Vec::new();
+    entry.take(max_bytes).read_to_end(&mut bytes).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+    let truncated = size > bytes.len() as u64;
+
+    // A cut can land inside a multi-byte character, which doesn't make the file binary
+    let text = match std::str::from_utf8(&bytes) {
+        Ok(text) => Some(text),
+        Err(e) if truncated && e.error_len().is_none() => std::str::from_utf8(&bytes[..e.valid_up_to()]).ok(),
+        Err(_) => None,
+    };
+    let binary = text.is_none_or(|text| text.contains('\0'));
+    Ok(ContainerFile {
+        path: path.to_string(),
+        size,
+        content: if binary { String::new() } else { text.unwrap_or_default().to_string() },
+        truncated,
+        binary,
+    })
+}
+
+/// Prints the size of each regular file among its arguments as size and path, each ended by a NUL.
+/// Paths that are gone or aren't regular files are left out.
+pub const FILE_SIZES_SCRIPT: &str = r#"for p; do
+  [ -f "$p" ] && [ ! -L "$p" ] || continue
+  size=$(stat -c '%s' -- "$p" 2>/dev/null) && printf '%s\0%s\0' "$size" "$p"
+done
+exit 0"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl From<ChangeType> for ChangeKind {
+    fn from(kind: ChangeType) -> Self {
+        match kind {
+            ChangeType::_0 => ChangeKind::Modified,
+            ChangeType::_1 => ChangeKind::Added,
+            ChangeType::_2 => ChangeKind::Deleted,
+        }
+    }
+}
+
+/// A changed path in a container's writable layer, with the changes below it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeNode {
+    pub name: String,
+    pub path: String,
+    pub kind: ChangeKind,
+    /// A file's size, or the total of the resolved sizes below a directory
+    pub size: Option<u64>,
+    pub children: Vec<ChangeNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerChanges {
+    pub added: usize,
+    pub modified: usize,
+    pub deleted: usize,
+    /// Size of the writable layer
+    pub size_rw: Option<i64>,
+    /// Size of the image and the writable layer together
+    pub size_root_fs: Option<i64>,
+    /// The entries below `/`
+    pub tree: Vec<ChangeNode>,
+}
+
+/// Parses what `FILE_SIZES_SCRIPT` prints into sizes by path
+pub fn parse_file_sizes(output: &str) -> HashMap<String, u64> {
+    let fields: Vec<&str> = output.split('\0').collect();
+    fields
+        .chunks_exact(2)
+        .filter_map(|record| Some((record[1].to_string(), record[0].parse().ok()?)))
+        .collect()
+}
+
+#[derive(Default)]
+struct PendingNode {
+    kind: Option<ChangeKind>,
+    children: BTreeMap<String, PendingNode>,
+}
+
+fn finish_node(name: String, path: String, pending: PendingNode, sizes: &HashMap<String, u64>) -> ChangeNode {
+    let children: Vec<ChangeNode> = pending
+        .children
+        .into_iter()
+        .map(|(child, node)| {
+            let child_path = format!("{}/{}", path, child);
+            finish_node(child, child_path, node, sizes)
+        })
+        .collect();
+    let size = if children.is_empty() {
+        sizes.get(&path).copied()
+    } else {
+        children.iter().filter_map(|child| child.size).reduce(|a, b| a + b)
+    };
+    ChangeNode {
+        name,
+        path,
+        // Docker lists the parents of changed paths as modified; this covers any it leaves out
+        kind: pending.kind.unwrap_or(ChangeKind::Modified),
+        size,
+        children,
+    }
+}
+
+/// Arranges the changes the daemon reports into a tree sorted by name, with the sizes that
+/// could be resolved. Deleted paths never have a size.
+pub fn build_changes(changes: &[FilesystemChange], sizes: &HashMap<String, u64>, size_rw: Option<i64>, size_root_fs: Option<i64>) -> ContainerChanges {
+    let mut root = PendingNode::default();
+    let (mut added, mut modified, mut deleted) = (0, 0, 0);
+    for change in changes {
+        let kind = ChangeKind::from(change.kind);
+        match kind {
+            ChangeKind::Added => added += 1,
+            ChangeKind::Modified => modified += 1,
+            ChangeKind::Deleted => deleted += 1,
+        }
+        let node = normal_components(Path::new(&change.path))
+            .into_iter()
+            .fold(&mut root, |node, name| node.children.entry(name).or_default());
+        node.kind = Some(kind);
+    }
+
+    ContainerChanges {
+        added,
+        modified,
+        deleted,
+        size_rw,
+        size_root_fs,
+        tree: finish_node(String::new(), String::new(), root, sizes).children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append(builder: &mut tar::Builder<Vec<u8>>, path: &str, entry_type: tar::EntryType, data: &[u8], link: Option<&str>) {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_size(data.len() as u64);
+        header.set_mode(if entry_type.is_dir() { 0o755 } else { 0o644 });
+        header.set_mtime(1_714_564_800);
+        match link {
+            Some(link) => builder.append_link(&mut header, path, link).unwrap(),
+            None => builder.append_data(&mut header, path, data).unwrap(),
+        }
+    }
+
+    fn etc_archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        append(&mut builder, "etc/", tar::EntryType::Directory, b"", None);
+        append(&mut builder, "etc/passwd", tar::EntryType::Regular, b"root:x:0:0", None);
+        append(&mut builder, "etc/ssl/", tar::EntryType::Directory, b"", None);
+        append(&mut builder, "etc/ssl/cert.pem", tar::EntryType::Regular, b"-----", None);
+        append(&mut builder, "etc/localtime", tar::EntryType::Symlink, b"", Some("/usr/share/zoneinfo/UTC"));
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_parse_stat_output() {
+        let output = [
+            "81a4 10 1714564800", "passwd", "",
+            "a1ff 23 1714564801", "local time", "/usr/share/zoneinfo/UTC",
+            "41ed 4096 1714564802", "ssl", "",
+        ]
+        .map(|field| format!("{}\0", field))
+        .concat();
+        let entries = parse_stat_output(&output).unwrap();
+        assert_eq!(entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), vec!["ssl", "local time", "passwd"]);
+        assert_eq!(entries[0].kind, FileKind::Directory);
+        assert_eq!(entries[1].kind, FileKind::Symlink);
+        assert_eq!(entries[1].link_target.as_deref(), Some("/usr/share/zoneinfo/UTC"));
+        assert_eq!(entries[2], ContainerDirEntry {
+            name: "passwd".to_string(),
+            kind: FileKind::File,
+            size: 10,
+            mode: 0o644,
+            mtime: 1_714_564_800,
+            link_target: None,
+        });
+
+        assert!(parse_stat_output("").unwrap().is_empty());
+        assert!(parse_stat_output("stat: not found\0x\0\0").is_err());
+    }
+
+    #[test]
+    fn test_list_archive_dir() {
+        let entries = list_archive_dir(etc_archive().as_slice(), "/etc").unwrap();
+        assert_eq!(entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), vec!["ssl", "localtime", "passwd"]);
+        assert_eq!(entries[1].link_target.as_deref(), Some("/usr/share/zoneinfo/UTC"));
+        assert_eq!(entries[2].size, 10);
+        assert_eq!(entries[2].mode, 0o644);
+        assert_eq!(entries[2].mtime, 1_714_564_800);
+
+        let mut builder = tar::Builder::new(Vec::new());
+        append(&mut builder, "passwd", tar::EntryType::Regular, b"root", None);
+        let file = builder.into_inner().unwrap();
+        assert_eq!(list_archive_dir(file.as_slice(), "/etc/passwd").unwrap_err(), "'/etc/passwd' is not a directory");
+    }
+
+    #[test]
+    fn test_read_archive_file() {
+        let mut builder = tar::Builder::new(Vec::new());
+        append(&mut builder, "motd", tar::EntryType::Regular, "héllo".as_bytes(), None);
+        let archive = builder.into_inner().unwrap();
+
+        let file = read_archive_file(archive.as_slice(), "/etc/motd", 1024).unwrap();
+        assert_eq!(file.content, "héllo");
+        assert_eq!(file.size, 6);
+        assert!(!file.truncated && !file.binary);
+        // Cut inside the é
+        let file = read_archive_file(archive.as_slice(), "/etc/motd", 2).unwrap();
+        assert_eq!(file.content, "h");
+        assert!(file.truncated && !file.binary);
+
+        let mut builder = tar::Builder::new(Vec::new());
+        append(&mut builder, "app", tar::EntryType::Regular, b"\x7fELF\0\0", None);
+        let binary = read_archive_file(builder.into_inner().unwrap().as_slice(), "/app", 1024).unwrap();
+        assert!(binary.binary);
+        assert_eq!(binary.content, "");
+
+        assert_eq!(read_archive_file(etc_archive().as_slice(), "/etc", 1024).unwrap_err(), "'/etc' is a directory");
+    }
+
+    #[test]
+    fn test_build_changes() {
+        let change = |path: &str, kind: ChangeType| FilesystemChange { path: path.to_string(), kind };
+        let changes = vec![
+            change("/var", ChangeType::_0),
+            change("/var/lib", ChangeType::_0),
+            change("/var/lib/app", ChangeType::_1),
+            change("/var/lib/app/data.db", ChangeType::_1),
+            change("/var/lib/app/cache", ChangeType::_1),
+            change("/etc/motd", ChangeType::_2),
+            change("/tmp/upload.bin", ChangeType::_1),
+        ];
+        let sizes = parse_file_sizes(&["4096", "/var/lib/app/data.db", "1024", "/var/lib/app/cache"].map(|field| format!("{}\0", field)).concat());
+        assert_eq!(sizes["/var/lib/app/cache"], 1024);
+
+        let result = build_changes(&changes, &sizes, Some(5120), Some(80_000_000));
+        assert_eq!((result.added, result.modified, result.deleted), (4, 2, 1));
+        assert_eq!(result.size_rw, Some(5120));
+        assert_eq!(result.tree.iter().map(|node| node.path.as_str()).collect::<Vec<_>>(), vec!["/etc", "/tmp", "/var"]);
+
+        // Docker left /etc and /tmp out, so they are filled in as modified
+        let etc = &result.tree[0];
+        assert_eq!(etc.kind, ChangeKind::Modified);
+        assert_eq!(etc.children[0].kind, ChangeKind::Deleted);
+        assert_eq!(etc.size, None);
+        assert_eq!(result.tree[1].children[0].size, None);
+
+        let var = &result.tree[2];
+        assert_eq!(var.size, Some(5120));
+        let app = &var.children[0].children[0];
+        assert_eq!(app.kind, ChangeKind::Added);
+        assert_eq!(app.children.iter().map(|node| node.name.as_str()).collect::<Vec<_>>(), vec!["cache", "data.db"]);
+        assert_eq!(app.children[1].size, Some(4096));
+    }
+}
@@ -0,0 +1,497 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// One line of container output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub stream: LogStream,
+    /// RFC 3339 timestamp the daemon recorded for the line
+    pub timestamp: Option<String>,
+    pub line: String,
+}
+
+/// Reassembles lines from the chunks the daemon sends. A chunk can hold several lines or
+/// part of one (always with TTY containers), so partial lines are kept until they complete.
+#[derive(Debug, Default)]
+pub struct LineSplitter {
+    stdout: String,
+    stderr: String,
+}
+
+impl LineSplitter {
+    pub fn push(&mut self, stream: LogStream, chunk: &str) -> Vec<LogEntry> {
+        let buffer = match stream {
+            LogStream::Stdout => &mut self.stdout,
+            LogStream::Stderr => &mut self.stderr,
+        };
+        buffer.push_str(chunk);
+
+        let mut lines = Vec::new();
+        while let Some(end) = buffer.find('\n') {
+            let line: String = buffer.drain(..=end).collect();
+            lines.push(parse_line(stream, &line));
+        }
+        lines
+    }
+
+    /// Returns what is left of unterminated lines, e.g. when the stream ends
+    pub fn finish(&mut self) -> Vec<LogEntry> {
+        let mut lines = Vec::new();
+        for (stream, buffer) in [(LogStream::Stdout, &mut self.stdout), (LogStream::Stderr, &mut self.stderr)] {
+            if !buffer.is_empty() {
+                lines.push(parse_line(stream, &std::mem::take(buffer)));
+            }
+        }
+        lines
+    }
+}
+
+fn parse_line(stream: LogStream, line: &str) -> LogEntry {
+    let line = line.trim_end_matches('\n').trim_end_matches('\r');
+    let (timestamp, message) = split_timestamp(line);
+    LogEntry {
+        stream,
+        timestamp,
+        line: message.to_string(),
+    }
+}
+
+/// Splits the timestamp the daemon prepends when logs are requested with `timestamps`
+pub fn split_timestamp(line: &str) -> (Option<String>, &str) {
+    match line.split_once(' ') {
+        Some((timestamp, message)) if looks_like_timestamp(timestamp) => (Some(timestamp.to_string()), message),
+        None if looks_like_timestamp(line) => (Some(line.to_string()), ""),
+        _ => (None, line),
+    }
+}
+
+// e.g. 2024-05-01T12:00:00.123456789Z
+fn looks_like_timestamp(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() >= 20
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[10] == b'T'
+        && (value.ends_with('Z') || value.get(19..).is_some_and(|rest| rest.contains(['+', '-'])))
+}
+
+/// Narrows down which log lines `get_container_logs` returns
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogQuery {
+    /// RFC 3339 time, Unix timestamp, or a duration back from now such as `15m`
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Include the daemon's timestamp with each entry
+    pub timestamps: Option<bool>,
+    /// Only lines from this stream; both when unset
+    pub stream: Option<LogStream>,
+    /// Substring, or regular expression when `regex` is set, that lines must contain
+    pub filter: Option<String>,
+    pub regex: Option<bool>,
+    pub case_sensitive: Option<bool>,
+}
+
+/// Matches log lines against a substring or regular expression
+pub enum LogFilter {
+    Substring { needle: String, case_sensitive: bool },
+    Regex(Regex),
+}
+
+impl LogFilter {
+    pub fn new(pattern: &str, regex: bool, case_sensitive: bool) -> Result<Self, String> {
+        if regex {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map_err(|e| format!("Invalid log filter: {}", e))?;
+            return Ok(LogFilter::Regex(regex));
+        }
+        let needle = if case_sensitive { pattern.to_string() } else { pattern.to_lowercase() };
+        Ok(LogFilter::Substring { needle, case_sensitive })
+    }
+
+    pub fn matches(&self, line: &str) -> bool {
+        match self {
+            LogFilter::Regex(regex) => regex.is_match(line),
+            LogFilter::Substring { needle, case_sensitive: true } => line.contains(needle.as_str()),
+            LogFilter::Substring { needle, case_sensitive: false } => line.to_lowercase().contains(needle.as_str()),
+        }
+    }
+}
+
+/// Parses a `since`/`until` value into a Unix timestamp, taking relative durations back from `now`
+pub fn parse_log_time(value: &str, now: i64) -> Result<i64, String> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<i64>() {
+        return Ok(seconds);
+    }
+    if value.len() >= 10 && value.as_bytes()[4] == b'-' {
+        return parse_rfc3339(value).ok_or_else(|| format!("Invalid time '{}'", value));
+    }
+    match crate::compose::parse_duration(value) {
+        Some(nanos) => Ok(now - nanos / 1_000_000_000),
+        None => Err(format!("Invalid time '{}': expected RFC 3339, a Unix timestamp or a duration like 15m", value)),
+    }
+}
+
+// `2024-05-01`, `2024-05-01T12:00:00Z`, `2024-05-01T12:00:00.5+02:00`; without a zone the time is UTC
+fn parse_rfc3339(value: &str) -> Option<i64> {
+    let number = |range: std::ops::Range<usize>| value.get(range)?.parse::<i64>().ok();
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut seconds = days_from_civil(year, month, day) * 86_400;
+
+    if value.len() > 10 {
+        if !matches!(value.as_bytes()[10], b'T' | b't' | b' ') {
+            return None;
+        }
+        seconds += number(11..13)? * 3600 + number(14..16)? * 60 + number(17..19)?;
+        let zone = value[19..].trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+        seconds -= match zone {
+            "" | "Z" | "z" => 0,
+            _ => {
+                let sign = match zone.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return None,
+                };
+                let hours: i64 = zone.get(1..3)?.parse().ok()?;
+                let minutes: i64 = zone.get(4..6)?.parse().ok()?;
+                sign * (hours * 3600 + minutes * 60)
+            }
+        };
+    }
+    Some(seconds)
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Sort key of an RFC 3339 timestamp: Unix seconds and nanoseconds.
+/// The daemon trims trailing zeros from the fraction, so the strings themselves don't sort.
+pub fn timestamp_key(timestamp: &str) -> Option<(i64, u32)> {
+    let seconds = parse_rfc3339(timestamp)?;
+    let nanos = match timestamp.get(19..).and_then(|rest| rest.strip_prefix('.')) {
+        Some(fraction) => {
+            let digits: String = fraction.chars().take_while(char::is_ascii_digit).take(9).collect();
+            format!("{:0<9}", digits).parse().ok()?
+        }
+        None => 0,
+    };
+    Some((seconds, nanos))
+}
+
+/// A log line tagged with the container it came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerLogEntry {
+    pub container_id: String,
+    pub container_name: String,
+    /// Stable per container, for the frontend to pick a colour from its palette
+    pub color_index: usize,
+    #[serde(flatten)]
+    pub entry: LogEntry,
+}
+
+impl ContainerLogEntry {
+    fn key(&self) -> (i64, u32) {
+        self.entry.timestamp.as_deref().and_then(timestamp_key).unwrap_or((i64::MAX, 0))
+    }
+}
+
+/// Interleaves lines from several containers by timestamp. Lines are held back for a short
+/// window so that slightly older lines from another container can still be put in front of them.
+#[derive(Debug, Default)]
+pub struct LogMerger {
+    // Entries with the time (in ms) they arrived
+    buffered: Vec<(u64, ContainerLogEntry)>,
+}
+
+impl LogMerger {
+    pub fn push(&mut self, entry: ContainerLogEntry, arrived_ms: u64) {
+        self.buffered.push((arrived_ms, entry));
+    }
+
+    /// Takes the entries that waited at least `hold_ms`, along with every entry not newer than them, in order
+    pub fn drain_ready(&mut self, now_ms: u64, hold_ms: u64) -> Vec<ContainerLogEntry> {
+        let cutoff = self
+            .buffered
+            .iter()
+            .filter(|(arrived, _)| arrived + hold_ms <= now_ms)
+            .map(|(_, entry)| entry.key())
+            .max();
+        let Some(cutoff) = cutoff else {
+            return Vec::new();
+        };
+
+        let (mut ready, held): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.buffered).into_iter().partition(|(_, entry)| entry.key() <= cutoff);
+        self.buffered = held;
+        // Stable, so lines of one container with equal timestamps keep their order
+        ready.sort_by_key(|(_, entry)| entry.key());
+        ready.into_iter().map(|(_, entry)| entry).collect()
+    }
+
+    /// Takes everything that is left, in order
+    pub fn drain_all(&mut self) -> Vec<ContainerLogEntry> {
+        let mut entries: Vec<ContainerLogEntry> = std::mem::take(&mut self.buffered).into_iter().map(|(_, e)| e).collect();
+        entries.sort_by_key(ContainerLogEntry::key);
+        entries
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogExportFormat {
+    /// One line per entry, prefixed with its timestamp when `timestamps` is set
+    #[default]
+    Text,
+    /// One JSON object per entry with its stream, timestamp and line
+    Jsonl,
+}
+
+/// How `export_container_logs` writes a container's logs
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LogExportOptions {
+    #[serde(default)]
+    pub format: LogExportFormat,
+    /// Compress the file with gzip
+    pub gzip: Option<bool>,
+    /// Lines to export from the end of the logs; all of them when unset
+    pub tail: Option<String>,
+    #[serde(flatten)]
+    pub query: LogQuery,
+}
+
+/// The file an export is written to, compressed or not
+pub enum ExportSink<W: Write> {
+    Plain(W),
+    Gzip(GzEncoder<W>),
+}
+
+impl<W: Write> ExportSink<W> {
+    pub fn new(inner: W, gzip: bool) -> Self {
+        if gzip {
+            ExportSink::Gzip(GzEncoder::new(inner, Compression::default()))
+        } else {
+            ExportSink::Plain(inner)
+        }
+    }
+
+    /// Writes the gzip trailer, if any, and flushes
+    pub fn finish(self) -> std::io::Result<W> {
+        let mut inner = match self {
+            ExportSink::Plain(inner) => inner,
+            ExportSink::Gzip(encoder) => encoder.finish()?,
+        };
+        inner.flush()?;
+        Ok(inner)
+    }
+}
+
+impl<W: Write> Write for ExportSink<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            ExportSink::Plain(inner) => inner.write(buf),
+            ExportSink::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            ExportSink::Plain(inner) => inner.flush(),
+            ExportSink::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Writes log entries in an export format and counts what was written
+pub struct LogExportWriter<W: Write> {
+    inner: W,
+    format: LogExportFormat,
+    timestamps: bool,
+    pub lines: u64,
+    /// Uncompressed bytes
+    pub bytes: u64,
+}
+
+impl<W: Write> LogExportWriter<W> {
+    pub fn new(inner: W, format: LogExportFormat, timestamps: bool) -> Self {
+        LogExportWriter { inner, format, timestamps, lines: 0, bytes: 0 }
+    }
+
+    pub fn write_entry(&mut self, entry: &LogEntry) -> std::io::Result<()> {
+        let mut text = match (self.format, &entry.timestamp) {
+            (LogExportFormat::Jsonl, _) => serde_json::to_string(entry)?,
+            (LogExportFormat::Text, Some(timestamp)) if self.timestamps => format!("{} {}", timestamp, entry.line),
+            (LogExportFormat::Text, _) => entry.line.clone(),
+        };
+        text.push('\n');
+        self.inner.write_all(text.as_bytes())?;
+        self.lines += 1;
+        self.bytes += text.len() as u64;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_timestamp() {
+        assert_eq!(
+            split_timestamp("2024-05-01T12:00:00.123456789Z GET / 200"),
+            (Some("2024-05-01T12:00:00.123456789Z".to_string()), "GET / 200")
+        );
+        assert_eq!(
+            split_timestamp("2024-05-01T12:00:00+02:00 hello"),
+            (Some("2024-05-01T12:00:00+02:00".to_string()), "hello")
+        );
+        assert_eq!(split_timestamp("plain line"), (None, "plain line"));
+        assert_eq!(split_timestamp("2024-05-01T12:00:00.1Z"), (Some("2024-05-01T12:00:00.1Z".to_string()), ""));
+        // TTY output without a timestamp, with a multi-byte character across byte 19
+        assert_eq!(split_timestamp("2024-05-01T12:00:0éx-1 moved"), (None, "2024-05-01T12:00:0éx-1 moved"));
+    }
+
+    #[test]
+    fn test_line_splitter() {
+        let mut splitter = LineSplitter::default();
+        assert!(splitter.push(LogStream::Stdout, "2024-05-01T12:00:00Z star").is_empty());
+        let lines = splitter.push(LogStream::Stdout, "ting\r\n2024-05-01T12:00:01Z ready\npartial");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].line, "starting");
+        assert_eq!(lines[0].timestamp.as_deref(), Some("2024-05-01T12:00:00Z"));
+        assert_eq!(lines[1].line, "ready");
+
+        let lines = splitter.push(LogStream::Stderr, "oops\n");
+        assert_eq!(lines, vec![LogEntry { stream: LogStream::Stderr, timestamp: None, line: "oops".to_string() }]);
+
+        let rest = splitter.finish();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].line, "partial");
+        assert!(splitter.finish().is_empty());
+    }
+
+    #[test]
+    fn test_parse_log_time() {
+        let now = 1_714_564_800; // 2024-05-01T12:00:00Z
+        assert_eq!(parse_log_time("2024-05-01T12:00:00Z", 0), Ok(now));
+        assert_eq!(parse_log_time("2024-05-01T14:00:00.250+02:00", 0), Ok(now));
+        assert_eq!(parse_log_time("2024-05-01", 0), Ok(now - 12 * 3600));
+        assert_eq!(parse_log_time("1714564800", 0), Ok(now));
+        assert_eq!(parse_log_time("15m", now), Ok(now - 900));
+        assert_eq!(parse_log_time("1h30m", now), Ok(now - 5400));
+        assert_eq!(parse_log_time("1970-01-01T00:00:00Z", 0), Ok(0));
+        assert!(parse_log_time("yesterday", now).is_err());
+        assert!(parse_log_time("2024-13-01", now).is_err());
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let filter = LogFilter::new("error", false, false).unwrap();
+        assert!(filter.matches("ERROR: disk full"));
+        assert!(!filter.matches("all good"));
+
+        let filter = LogFilter::new("Error", false, true).unwrap();
+        assert!(!filter.matches("ERROR: disk full"));
+
+        let filter = LogFilter::new(r"status=5\d\d", true, false).unwrap();
+        assert!(filter.matches("GET / status=503"));
+        assert!(!filter.matches("GET / status=200"));
+        assert!(LogFilter::new("(", true, false).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_key() {
+        assert_eq!(timestamp_key("2024-05-01T12:00:00Z"), Some((1_714_564_800, 0)));
+        assert_eq!(timestamp_key("2024-05-01T12:00:00.5Z"), Some((1_714_564_800, 500_000_000)));
+        assert_eq!(timestamp_key("2024-05-01T12:00:00.123456789Z"), Some((1_714_564_800, 123_456_789)));
+        assert!(timestamp_key("2024-05-01T12:00:00Z") < timestamp_key("2024-05-01T12:00:00.1Z"));
+        assert_eq!(timestamp_key("not a time"), None);
+    }
+
+    #[test]
+    fn test_log_merger() {
+        let entry = |container: &str, timestamp: &str| ContainerLogEntry {
+            container_id: container.to_string(),
+            container_name: container.to_string(),
+            color_index: 0,
+            entry: LogEntry { stream: LogStream::Stdout, timestamp: Some(timestamp.to_string()), line: String::new() },
+        };
+        let order = |entries: Vec<ContainerLogEntry>| -> Vec<String> {
+            entries.iter().map(|e| format!("{}@{}", e.container_id, e.entry.timestamp.as_deref().unwrap_or_default())).collect()
+        };
+
+        let mut merger = LogMerger::default();
+        merger.push(entry("web", "2024-05-01T12:00:02Z"), 0);
+        merger.push(entry("db", "2024-05-01T12:00:01Z"), 50);
+        merger.push(entry("db", "2024-05-01T12:00:03Z"), 60);
+        assert!(merger.drain_ready(100, 200).is_empty());
+
+        // web's line is due; db's older line goes in front of it and db's newer one waits
+        assert_eq!(
+            order(merger.drain_ready(200, 200)),
+            vec!["db@2024-05-01T12:00:01Z", "web@2024-05-01T12:00:02Z"]
+        );
+        merger.push(entry("web", "2024-05-01T12:00:02.5Z"), 210);
+        assert_eq!(
+            order(merger.drain_all()),
+            vec!["web@2024-05-01T12:00:02.5Z", "db@2024-05-01T12:00:03Z"]
+        );
+    }
+
+    #[test]
+    fn test_log_export_writer() {
+        let entries = [
+            LogEntry { stream: LogStream::Stdout, timestamp: Some("2024-05-01T12:00:00Z".to_string()), line: "ready".to_string() },
+            LogEntry { stream: LogStream::Stderr, timestamp: Some("2024-05-01T12:00:01Z".to_string()), line: "oops \"x\"".to_string() },
+        ];
+        let write = |format: LogExportFormat, timestamps: bool, gzip: bool| {
+            let mut writer = LogExportWriter::new(ExportSink::new(Vec::new(), gzip), format, timestamps);
+            for entry in &entries {
+                writer.write_entry(entry).unwrap();
+            }
+            assert_eq!(writer.lines, 2);
+            writer.into_inner().finish().unwrap()
+        };
+
+        assert_eq!(String::from_utf8(write(LogExportFormat::Text, false, false)).unwrap(), "ready\noops \"x\"\n");
+        assert_eq!(
+            String::from_utf8(write(LogExportFormat::Text, true, false)).unwrap(),
+            "2024-05-01T12:00:00Z ready\n2024-05-01T12:00:01Z oops \"x\"\n"
+        );
+
+        let jsonl = String::from_utf8(write(LogExportFormat::Jsonl, false, false)).unwrap();
+        let parsed: Vec<LogEntry> = jsonl.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(parsed, entries);
+
+        let compressed = write(LogExportFormat::Jsonl, false, true);
+        let mut decompressed = String::new();
+        std::io::Read::read_to_string(&mut flate2::read::GzDecoder::new(&compressed[..]), &mut decompressed).unwrap();
+        assert_eq!(decompressed, jsonl);
+    }
+}
@@ -1,27 +1,36 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod compose;
+mod events;
+mod filesystem;
+mod logs;
+mod notifications;
+mod processes;
+mod transfer;
 mod validation;
 
 use bollard::Docker;
-use bollard::container::{ListContainersOptions, RemoveContainerOptions, LogsOptions, StartContainerOptions, StatsOptions, CreateContainerOptions, Config};
+use bollard::container::{DownloadFromContainerOptions, UploadToContainerOptions, InspectContainerOptions, TopOptions, KillContainerOptions, StopContainerOptions, RestartContainerOptions, RenameContainerOptions, ListContainersOptions, RemoveContainerOptions, LogsOptions, StartContainerOptions, StatsOptions, CreateContainerOptions, Config, NetworkingConfig, WaitContainerOptions};
 use bollard::exec::{CreateExecOptions, StartExecResults, ResizeExecOptions};
 use bollard::image::{ListImagesOptions, RemoveImageOptions};
-use bollard::network::{CreateNetworkOptions};
-use bollard::volume::{CreateVolumeOptions, RemoveVolumeOptions};
+use bollard::network::{ConnectNetworkOptions, CreateNetworkOptions, InspectNetworkOptions, ListNetworksOptions};
+use bollard::volume::{CreateVolumeOptions, ListVolumesOptions, RemoveVolumeOptions};
+use bollard::system::EventsOptions;
 use bollard::container::PruneContainersOptions;
 use bollard::image::PruneImagesOptions;
 use bollard::network::PruneNetworksOptions;
 use bollard::volume::PruneVolumesOptions;
-use bollard::models::{ContainerSummary, HostConfig, PortBinding, Mount, MountTypeEnum, EndpointSettings};
+use bollard::models::{ContainerSummary, HostConfig, HostConfigLogConfig, HealthConfig, DeviceMapping, ResourcesUlimits, PortBinding, Mount, MountTypeEnum, MountBindOptions, MountVolumeOptions, MountTmpfsOptions, EndpointSettings, EndpointIpamConfig, Ipam, IpamConfig};
+use compose::{ComposeFile, ComposeService, DependencyCondition};
 use serde::{Deserialize, Serialize};
-use serde_yaml;
 use reqwest;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tauri::State;
+use tauri::{Emitter, Manager, State};
 use tauri::ipc::Channel;
-use std::collections::HashMap;
+use tauri_plugin_notification::NotificationExt;
+use std::collections::{BTreeMap, HashMap};
 use tokio::io::AsyncWriteExt;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +64,57 @@ struct ContainerDetails {
     mounts: Vec<MountInfo>,
     env: Vec<String>,
     network_settings: NetworkInfo,
+    health: Option<HealthInfo>,
+    exit_code: Option<i64>,
+    oom_killed: bool,
+    error: Option<String>,
+    started_at: Option<String>,
+    finished_at: Option<String>,
+    restart_count: i64,
+    labels: HashMap<String, String>,
+    entrypoint: Vec<String>,
+    cmd: Vec<String>,
+    working_dir: Option<String>,
+    user: Option<String>,
+    restart_policy: RestartPolicyInfo,
+    resources: ResourceLimits,
+    /// Local image id
+    image_id: String,
+    /// Registry digests of the image, empty for images that were built locally
+    image_digests: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HealthInfo {
+    status: String,
+    failing_streak: i64,
+    /// The most recent probes, oldest first
+    log: Vec<HealthProbe>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HealthProbe {
+    start: Option<String>,
+    end: Option<String>,
+    exit_code: Option<i64>,
+    output: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RestartPolicyInfo {
+    name: String,
+    maximum_retry_count: Option<i64>,
+}
+
+// Unset limits are None rather than Docker's 0
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ResourceLimits {
+    memory: Option<i64>,
+    memory_reservation: Option<i64>,
+    memory_swap: Option<i64>,
+    cpus: Option<f64>,
+    cpu_shares: Option<i64>,
+    pids_limit: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,7 +128,18 @@ struct MountInfo {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct NetworkInfo {
+    /// Network name to IP address
     networks: HashMap<String, String>,
+    endpoints: HashMap<String, NetworkEndpoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NetworkEndpoint {
+    ip_address: String,
+    ip_prefix_len: Option<i64>,
+    gateway: String,
+    mac_address: String,
+    aliases: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -133,6 +204,81 @@ struct CreateContainerRequest {
     command: Option<Vec<String>>,
     memory_limit: Option<i64>,  // Memory limit in bytes
     cpu_quota: Option<i64>,     // CPU quota (100000 = 1 CPU core)
+    labels: Option<HashMap<String, String>>,
+    network_aliases: Option<Vec<String>>,
+    ipv4_address: Option<String>,  // Static addresses on `network`
+    ipv6_address: Option<String>,
+    extra_networks: Option<Vec<NetworkEndpointRequest>>,  // Connected after creation
+    port_bindings: Option<Vec<PortBindingRequest>>,
+    mounts: Option<Vec<MountRequest>>,
+    healthcheck: Option<HealthcheckRequest>,
+    user: Option<String>,
+    working_dir: Option<String>,
+    entrypoint: Option<Vec<String>>,
+    hostname: Option<String>,
+    extra_hosts: Option<Vec<String>>,  // "host:ip"
+    dns: Option<Vec<String>>,
+    cap_add: Option<Vec<String>>,
+    cap_drop: Option<Vec<String>>,
+    privileged: Option<bool>,
+    devices: Option<Vec<String>>,  // "/dev/host[:/dev/container[:rwm]]"
+    tmpfs: Option<HashMap<String, String>>,  // container path -> mount options
+    ulimits: Option<Vec<UlimitRequest>>,
+    shm_size: Option<i64>,  // bytes
+    stop_signal: Option<String>,
+    stop_timeout: Option<i64>,  // seconds
+    init: Option<bool>,
+    read_only: Option<bool>,
+    log_driver: Option<String>,
+    log_options: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HealthcheckRequest {
+    test: Vec<String>,  // ["CMD", ...], ["CMD-SHELL", "..."] or ["NONE"]
+    interval: Option<i64>,  // Durations in nanoseconds
+    timeout: Option<i64>,
+    retries: Option<i64>,
+    start_period: Option<i64>,
+    start_interval: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UlimitRequest {
+    name: String,
+    soft: i64,
+    hard: i64,
+}
+
+// A network the container joins besides `network`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NetworkEndpointRequest {
+    name: String,
+    aliases: Option<Vec<String>>,
+    ipv4_address: Option<String>,
+    ipv6_address: Option<String>,
+}
+
+// Port binding with an explicit host IP; several bindings may share a container port
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PortBindingRequest {
+    container_port: String,     // "80/tcp"
+    host_ip: Option<String>,
+    host_port: Option<String>,  // "8080", "9000-9010", or none to let Docker pick
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MountRequest {
+    mount_type: String,  // "bind", "volume" or "tmpfs"
+    source: Option<String>,
+    target: String,
+    read_only: Option<bool>,
+    bind_propagation: Option<String>,
+    selinux_label: Option<String>,  // "z" or "Z"
+    volume_nocopy: Option<bool>,
+    volume_subpath: Option<String>,
+    tmpfs_size: Option<i64>,
+    tmpfs_mode: Option<i64>,
 }
 
 struct DockerState {
@@ -149,6 +295,21 @@ struct TerminalState {
     sessions: Arc<Mutex<HashMap<String, TerminalSession>>>,
 }
 
+struct NotificationState {
+    notifier: Arc<Mutex<notifications::Notifier>>,
+    settings_path: std::path::PathBuf,
+}
+
+struct LogStreamState {
+    streams: Arc<Mutex<HashMap<String, tokio::task::AbortHandle>>>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+struct TopStreamState {
+    streams: Arc<Mutex<HashMap<String, tokio::task::AbortHandle>>>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct PullProgressEvent {
@@ -162,6 +323,17 @@ struct PullProgressEvent {
     error: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BuildProgressEvent {
+    service: String,
+    image: String,
+    stream: Option<String>,
+    status: Option<String>,
+    complete: bool,
+    error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ImageUpdateInfo {
@@ -225,10 +397,17 @@ async fn start_container(state: State<'_, DockerState>, id: String) -> Result<()
 }
 
 #[tauri::command]
-async fn stop_container(state: State<'_, DockerState>, id: String) -> Result<(), String> {
-    let docker = state.docker.lock().await;
+async fn stop_container(state: State<'_, DockerState>, id: String, timeout: Option<i64>) -> Result<(), String> {
+    if let Some(timeout) = timeout {
+        validation::validate_stop_timeout(timeout)?;
+    }
+    // A stop can take as long as the timeout, so it mustn't hold the Docker mutex
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
     
-    docker.stop_container(&id, None)
+    docker.stop_container(&id, timeout.map(|t| StopContainerOptions { t }))
         .await
         .map_err(|e| format!("Failed to stop container: {}", e))?;
     
@@ -236,16 +415,59 @@ async fn stop_container(state: State<'_, DockerState>, id: String) -> Result<(),
 }
 
 #[tauri::command]
-async fn restart_container(state: State<'_, DockerState>, id: String) -> Result<(), String> {
-    let docker = state.docker.lock().await;
+async fn restart_container(state: State<'_, DockerState>, id: String, timeout: Option<i64>) -> Result<(), String> {
+    if let Some(timeout) = timeout {
+        validation::validate_stop_timeout(timeout)?;
+    }
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
     
-    docker.restart_container(&id, None)
+    docker.restart_container(&id, timeout.map(|t| RestartContainerOptions { t: t as isize }))
         .await
         .map_err(|e| format!("Failed to restart container: {}", e))?;
     
     Ok(())
 }
 
+#[tauri::command]
+async fn kill_container(state: State<'_, DockerState>, id: String, signal: Option<String>) -> Result<(), String> {
+    let signal = validation::validate_signal(signal.as_deref().unwrap_or("KILL"))?;
+    let docker = state.docker.lock().await;
+    
+    docker.kill_container(&id, Some(KillContainerOptions { signal }))
+        .await
+        .map_err(|e| format!("Failed to kill container: {}", e))?;
+    
+    Ok(())
+}
+
+/// Waits until the container reaches `condition` ("not-running" by default, "next-exit" or
+/// "removed") and returns its exit code
+#[tauri::command]
+async fn wait_container(state: State<'_, DockerState>, id: String, condition: Option<String>) -> Result<i64, String> {
+    use futures_util::stream::StreamExt;
+    
+    let condition = condition.unwrap_or_else(|| "not-running".to_string());
+    if !["not-running", "next-exit", "removed"].contains(&condition.as_str()) {
+        return Err(format!("Invalid wait condition: {}", condition));
+    }
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let mut wait_stream = docker.wait_container(&id, Some(WaitContainerOptions { condition }));
+    match wait_stream.next().await {
+        Some(Ok(response)) => Ok(response.status_code),
+        // bollard reports a non-zero exit as an error, but for a wait it is the answer
+        Some(Err(bollard::errors::Error::DockerContainerWaitError { code, .. })) => Ok(code),
+        Some(Err(e)) => Err(format!("Failed to wait for container: {}", e)),
+        None => Err("Failed to wait for container: the daemon closed the connection".to_string()),
+    }
+}
+
 #[tauri::command]
 async fn pause_container(state: State<'_, DockerState>, id: String) -> Result<(), String> {
     let docker = state.docker.lock().await;
@@ -284,32 +506,485 @@ async fn remove_container(state: State<'_, DockerState>, id: String, force: bool
     Ok(())
 }
 
-#[tauri::command]
-async fn get_container_logs(state: State<'_, DockerState>, id: String, tail: Option<String>) -> Result<String, String> {
-    let docker = state.docker.lock().await;
+// Daemon options and line filter for a log query. Timestamps are always requested
+// since they can't be told apart from the line otherwise.
+fn log_query_options(query: &logs::LogQuery, tail: String) -> Result<(LogsOptions<String>, Option<logs::LogFilter>), String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let since = query.since.as_deref().map(|s| logs::parse_log_time(s, now)).transpose()?;
+    let until = query.until.as_deref().map(|u| logs::parse_log_time(u, now)).transpose()?;
+    let filter = query.filter.as_deref()
+        .filter(|f| !f.is_empty())
+        .map(|f| logs::LogFilter::new(f, query.regex.unwrap_or(false), query.case_sensitive.unwrap_or(false)))
+        .transpose()?;
     
-    let options = Some(LogsOptions::<String> {
-        stdout: true,
-        stderr: true,
-        tail: tail.unwrap_or_else(|| "100".to_string()),
+    let options = LogsOptions::<String> {
+        stdout: query.stream != Some(logs::LogStream::Stderr),
+        stderr: query.stream != Some(logs::LogStream::Stdout),
+        since: since.unwrap_or(0),
+        until: until.unwrap_or(0),
+        timestamps: true,
+        tail,
         ..Default::default()
-    });
+    };
+    Ok((options, filter))
+}
 
+#[tauri::command]
+async fn get_container_logs(
+    state: State<'_, DockerState>,
+    id: String,
+    tail: Option<String>,
+    query: Option<logs::LogQuery>,
+) -> Result<Vec<logs::LogEntry>, String> {
+    let query = query.unwrap_or_default();
+    let (options, filter) = log_query_options(&query, tail.unwrap_or_else(|| "100".to_string()))?;
+    
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
     use futures_util::stream::StreamExt;
     
-    let mut logs_stream = docker.logs(&id, options);
-    let mut logs = String::new();
+    let mut logs_stream = docker.logs(&id, Some(options));
+    let mut splitter = logs::LineSplitter::default();
+    let mut entries = Vec::new();
+    // Lines are filtered as they arrive, so only the matches of a long log are held in memory.
+    // `tail` is applied by the daemon, so the filter narrows down the last lines rather than finding the last matches.
+    let mut keep = |batch: Vec<logs::LogEntry>| {
+        entries.extend(batch.into_iter().filter(|entry| filter.as_ref().is_none_or(|f| f.matches(&entry.line))));
+    };
     
     while let Some(log_result) = logs_stream.next().await {
-        match log_result {
-            Ok(log) => {
-                logs.push_str(&log.to_string());
-            },
+        let (stream, message) = match log_result {
+            Ok(bollard::container::LogOutput::StdErr { message }) => (logs::LogStream::Stderr, message),
+            Ok(bollard::container::LogOutput::StdOut { message })
+            | Ok(bollard::container::LogOutput::Console { message }) => (logs::LogStream::Stdout, message),
+            Ok(bollard::container::LogOutput::StdIn { .. }) => continue,
             Err(e) => return Err(format!("Failed to read logs: {}", e)),
+        };
+        keep(splitter.push(stream, &String::from_utf8_lossy(&message)));
+    }
+    keep(splitter.finish());
+    
+    if !query.timestamps.unwrap_or(false) {
+        for entry in &mut entries {
+            entry.timestamp = None;
+        }
+    }
+    
+    Ok(entries)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LogExportProgress {
+    lines_written: u64,
+    bytes_written: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LogExportResult {
+    path: String,
+    lines_written: u64,
+    /// Size of the file, after compression
+    file_size: u64,
+}
+
+// Writes logs to `<path>.part` and renames it when done, so a failed export never leaves a file that looks complete
+#[tauri::command]
+async fn export_container_logs(
+    state: State<'_, DockerState>,
+    id: String,
+    path: String,
+    options: Option<logs::LogExportOptions>,
+    on_progress: Channel<LogExportProgress>,
+) -> Result<LogExportResult, String> {
+    let options = options.unwrap_or_default();
+    let (logs_options, filter) = log_query_options(&options.query, options.tail.clone().unwrap_or_else(|| "all".to_string()))?;
+    
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let partial_path = format!("{}.part", path);
+    let file = std::fs::File::create(&partial_path)
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut writer = logs::LogExportWriter::new(
+        logs::ExportSink::new(std::io::BufWriter::new(file), options.gzip.unwrap_or(false)),
+        options.format,
+        options.query.timestamps.unwrap_or(false),
+    );
+    
+    use futures_util::stream::StreamExt;
+    
+    // Following the daemon's stream rather than the log files keeps the export correct when the files rotate
+    let mut logs_stream = docker.logs(&id, Some(logs_options));
+    let mut splitter = logs::LineSplitter::default();
+    let mut last_progress = std::time::Instant::now();
+    let progress_interval = std::time::Duration::from_millis(LOG_BATCH_INTERVAL_MS);
+    
+    let result: Result<(), String> = async {
+        loop {
+            let next = logs_stream.next().await;
+            let done = next.is_none();
+            let entries = match next {
+                Some(Ok(bollard::container::LogOutput::StdErr { message })) => splitter.push(logs::LogStream::Stderr, &String::from_utf8_lossy(&message)),
+                Some(Ok(bollard::container::LogOutput::StdOut { message }))
+                | Some(Ok(bollard::container::LogOutput::Console { message })) => splitter.push(logs::LogStream::Stdout, &String::from_utf8_lossy(&message)),
+                Some(Ok(bollard::container::LogOutput::StdIn { .. })) => continue,
+                Some(Err(e)) => return Err(format!("Failed to read logs: {}", e)),
+                None => splitter.finish(),
+            };
+            
+            for entry in entries.iter().filter(|entry| filter.as_ref().is_none_or(|f| f.matches(&entry.line))) {
+                writer.write_entry(entry).map_err(|e| format!("Failed to write logs: {}", e))?;
+            }
+            if done {
+                return Ok(());
+            }
+            if last_progress.elapsed() >= progress_interval {
+                last_progress = std::time::Instant::now();
+                let _ = on_progress.send(LogExportProgress {
+                    lines_written: writer.lines,
+                    bytes_written: writer.bytes,
+                });
+            }
+        }
+    }.await;
+    
+    let lines_written = writer.lines;
+    let _ = on_progress.send(LogExportProgress {
+        lines_written,
+        bytes_written: writer.bytes,
+    });
+    let result = result.and_then(|_| {
+        writer.into_inner().finish().map_err(|e| format!("Failed to write logs: {}", e))?;
+        std::fs::rename(&partial_path, &path).map_err(|e| format!("Failed to save file: {}", e))
+    });
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&partial_path);
+        return Err(e);
+    }
+    
+    let file_size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    Ok(LogExportResult {
+        path,
+        lines_written,
+        file_size,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LogBatchEvent {
+    stream_id: String,
+    lines: Vec<logs::LogEntry>,
+    ended: bool,
+    error: Option<String>,
+}
+
+// Lines are sent in batches so a chatty container doesn't flood the frontend with messages
+const LOG_BATCH_INTERVAL_MS: u64 = 100;
+const LOG_BATCH_MAX_LINES: usize = 500;
+
+#[tauri::command]
+async fn stream_container_logs(
+    id: String,
+    tail: Option<String>,
+    since: Option<i64>,
+    on_logs: Channel<LogBatchEvent>,
+    docker_state: State<'_, DockerState>,
+    log_state: State<'_, LogStreamState>,
+) -> Result<String, String> {
+    // The stream holds its own handle, so following logs never keeps the Docker mutex locked
+    let docker = {
+        let guard = docker_state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let stream_id = format!("logs-{}", log_state.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed));
+    let streams = log_state.streams.clone();
+    // Held until the stream is registered, so a stream that ends at once can't unregister before that
+    let mut registry = streams.lock().await;
+    
+    let task_stream_id = stream_id.clone();
+    let task_streams = streams.clone();
+    let task = tokio::spawn(async move {
+        use futures_util::stream::StreamExt;
+        
+        let options = LogsOptions::<String> {
+            follow: true,
+            stdout: true,
+            stderr: true,
+            timestamps: true,
+            since: since.unwrap_or(0),
+            tail: tail.unwrap_or_else(|| "100".to_string()),
+            ..Default::default()
+        };
+        let mut logs_stream = docker.logs(&id, Some(options));
+        let mut splitter = logs::LineSplitter::default();
+        let mut batch: Vec<logs::LogEntry> = Vec::new();
+        let mut ticker = tokio::time::interval(tokio::time::Duration::from_millis(LOG_BATCH_INTERVAL_MS));
+        let send = |lines: Vec<logs::LogEntry>, ended: bool, error: Option<String>| {
+            on_logs.send(LogBatchEvent {
+                stream_id: task_stream_id.clone(),
+                lines,
+                ended,
+                error,
+            }).is_ok()
+        };
+        
+        let error = loop {
+            tokio::select! {
+                next = logs_stream.next() => match next {
+                    Some(Ok(output)) => {
+                        let (stream, message) = match output {
+                            bollard::container::LogOutput::StdErr { message } => (logs::LogStream::Stderr, message),
+                            bollard::container::LogOutput::StdOut { message }
+                            | bollard::container::LogOutput::Console { message } => (logs::LogStream::Stdout, message),
+                            bollard::container::LogOutput::StdIn { .. } => continue,
+                        };
+                        batch.extend(splitter.push(stream, &String::from_utf8_lossy(&message)));
+                        if batch.len() >= LOG_BATCH_MAX_LINES && !send(std::mem::take(&mut batch), false, None) {
+                            break None;
+                        }
+                    }
+                    Some(Err(e)) => break Some(format!("Failed to read logs: {}", e)),
+                    None => break None,
+                },
+                _ = ticker.tick() => {
+                    // A closed channel means the log view is gone
+                    if !batch.is_empty() && !send(std::mem::take(&mut batch), false, None) {
+                        break None;
+                    }
+                }
+            }
+        };
+        
+        batch.extend(splitter.finish());
+        send(batch, true, error);
+        task_streams.lock().await.remove(&task_stream_id);
+    });
+    
+    registry.insert(stream_id.clone(), task.abort_handle());
+    Ok(stream_id)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AggregatedLogBatchEvent {
+    stream_id: String,
+    lines: Vec<logs::ContainerLogEntry>,
+    ended: bool,
+    error: Option<String>,
+}
+
+// How long lines wait for older lines from other containers before they are sent
+const LOG_MERGE_HOLD_MS: u64 = 250;
+// How often a stopped container is checked for a restart, and a project for new containers
+const LOG_REATTACH_INTERVAL_MS: u64 = 1000;
+
+// Follows one container's logs and re-attaches whenever it is started again, until it is removed
+async fn follow_container_logs(
+    docker: Docker,
+    id: String,
+    name: String,
+    color_index: usize,
+    tail: String,
+    sender: tokio::sync::mpsc::UnboundedSender<logs::ContainerLogEntry>,
+) {
+    use futures_util::stream::StreamExt;
+    
+    let mut last_key: Option<(i64, u32)> = None;
+    loop {
+        // `since` only has second precision, so lines of that second that were already sent are skipped below
+        let options = LogsOptions::<String> {
+            follow: true,
+            stdout: true,
+            stderr: true,
+            timestamps: true,
+            since: last_key.map(|(seconds, _)| seconds).unwrap_or(0),
+            tail: if last_key.is_some() { "all".to_string() } else { tail.clone() },
+            ..Default::default()
+        };
+        let mut logs_stream = docker.logs(&id, Some(options));
+        let mut splitter = logs::LineSplitter::default();
+        let mut entries = Vec::new();
+        
+        loop {
+            let next = logs_stream.next().await;
+            let done = match next {
+                Some(Ok(bollard::container::LogOutput::StdErr { message })) => {
+                    entries.extend(splitter.push(logs::LogStream::Stderr, &String::from_utf8_lossy(&message)));
+                    false
+                }
+                Some(Ok(bollard::container::LogOutput::StdOut { message }))
+                | Some(Ok(bollard::container::LogOutput::Console { message })) => {
+                    entries.extend(splitter.push(logs::LogStream::Stdout, &String::from_utf8_lossy(&message)));
+                    false
+                }
+                Some(Ok(bollard::container::LogOutput::StdIn { .. })) => false,
+                // The stream ends or fails when the container stops or goes away
+                Some(Err(_)) | None => true,
+            };
+            if done {
+                entries.extend(splitter.finish());
+            }
+            
+            for entry in entries.drain(..) {
+                let key = entry.timestamp.as_deref().and_then(logs::timestamp_key);
+                if key.is_some() && key <= last_key {
+                    continue;
+                }
+                last_key = key.or(last_key);
+                let tagged = logs::ContainerLogEntry {
+                    container_id: id.clone(),
+                    container_name: name.clone(),
+                    color_index,
+                    entry,
+                };
+                if sender.send(tagged).is_err() {
+                    return;
+                }
+            }
+            if done {
+                break;
+            }
+        }
+        
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_millis(LOG_REATTACH_INTERVAL_MS)).await;
+            match docker.inspect_container(&id, None).await {
+                Ok(container) if container.state.as_ref().and_then(|s| s.running).unwrap_or(false) => break,
+                Ok(_) => continue,
+                Err(_) => return,
+            }
         }
     }
+}
+
+#[tauri::command]
+async fn stream_aggregated_logs(
+    container_ids: Vec<String>,
+    project: Option<String>,
+    tail: Option<String>,
+    on_logs: Channel<AggregatedLogBatchEvent>,
+    docker_state: State<'_, DockerState>,
+    log_state: State<'_, LogStreamState>,
+) -> Result<String, String> {
+    let docker = {
+        let guard = docker_state.docker.lock().await;
+        guard.clone()
+    };
+    if container_ids.is_empty() && project.is_none() {
+        return Err("No containers to follow".to_string());
+    }
+    
+    let mut sources: Vec<(String, String)> = Vec::new();
+    for id in &container_ids {
+        let container = docker.inspect_container(id, None)
+            .await
+            .map_err(|e| format!("Failed to inspect container: {}", e))?;
+        let id = container.id.unwrap_or_else(|| id.clone());
+        let name = container.name.unwrap_or_default().trim_start_matches('/').to_string();
+        sources.push((id, name));
+    }
+    
+    let stream_id = format!("logs-{}", log_state.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed));
+    let streams = log_state.streams.clone();
+    let mut registry = streams.lock().await;
+    
+    let task_stream_id = stream_id.clone();
+    let task_streams = streams.clone();
+    let tail = tail.unwrap_or_else(|| "100".to_string());
+    let task = tokio::spawn(async move {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        // Dropped together with this task, which aborts the followers when the stream is stopped
+        let mut followers = tokio::task::JoinSet::new();
+        let mut attached: Vec<String> = Vec::new();
+        let attach = |followers: &mut tokio::task::JoinSet<()>,
+                      attached: &mut Vec<String>,
+                      sender: &tokio::sync::mpsc::UnboundedSender<logs::ContainerLogEntry>,
+                      id: String,
+                      name: String| {
+            if !attached.contains(&id) {
+                followers.spawn(follow_container_logs(docker.clone(), id.clone(), name, attached.len(), tail.clone(), sender.clone()));
+                attached.push(id);
+            }
+        };
+        for (id, name) in sources {
+            attach(&mut followers, &mut attached, &sender, id, name);
+        }
+        // Without a project the containers are fixed, so the stream ends once all of them are removed
+        let sender = project.as_ref().map(|_| sender);
+        
+        let send = |lines: Vec<logs::ContainerLogEntry>, ended: bool, error: Option<String>| {
+            on_logs.send(AggregatedLogBatchEvent {
+                stream_id: task_stream_id.clone(),
+                lines,
+                ended,
+                error,
+            }).is_ok()
+        };
+        let mut merger = logs::LogMerger::default();
+        let started = tokio::time::Instant::now();
+        let elapsed_ms = || started.elapsed().as_millis() as u64;
+        let mut ticker = tokio::time::interval(tokio::time::Duration::from_millis(LOG_BATCH_INTERVAL_MS));
+        let mut discovery = tokio::time::interval(tokio::time::Duration::from_millis(LOG_REATTACH_INTERVAL_MS));
+        
+        let error = loop {
+            tokio::select! {
+                entry = receiver.recv() => match entry {
+                    Some(entry) => merger.push(entry, elapsed_ms()),
+                    None => break None,
+                },
+                _ = ticker.tick() => {
+                    let lines = merger.drain_ready(elapsed_ms(), LOG_MERGE_HOLD_MS);
+                    if !lines.is_empty() && !send(lines, false, None) {
+                        break None;
+                    }
+                }
+                // Picks up containers that were created after the stream started, e.g. by a redeploy
+                _ = discovery.tick(), if sender.is_some() => {
+                    let (Some(project), Some(sender)) = (&project, &sender) else { continue };
+                    match list_project_containers(&docker, project).await {
+                        Ok(containers) => {
+                            for container in containers {
+                                let name = container.names.as_ref()
+                                    .and_then(|names| names.first())
+                                    .map(|name| name.trim_start_matches('/').to_string())
+                                    .unwrap_or_default();
+                                if let Some(id) = container.id {
+                                    attach(&mut followers, &mut attached, sender, id, name);
+                                }
+                            }
+                        }
+                        Err(e) => break Some(e),
+                    }
+                }
+            }
+        };
+        
+        send(merger.drain_all(), true, error);
+        task_streams.lock().await.remove(&task_stream_id);
+    });
     
-    Ok(logs)
+    registry.insert(stream_id.clone(), task.abort_handle());
+    Ok(stream_id)
+}
+
+#[tauri::command]
+async fn stop_log_stream(stream_id: String, log_state: State<'_, LogStreamState>) -> Result<(), String> {
+    if let Some(handle) = log_state.streams.lock().await.remove(&stream_id) {
+        handle.abort();
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -324,9 +999,9 @@ async fn get_container_details(state: State<'_, DockerState>, id: String) -> Res
     let config = container.config.unwrap_or_default();
     let state_info = container.state.unwrap_or_default();
     let network_settings = container.network_settings.unwrap_or_default();
+    let host_config = container.host_config.unwrap_or_default();
     
-    let ports = container.host_config
-        .and_then(|hc| hc.port_bindings)
+    let ports = host_config.port_bindings.clone()
         .unwrap_or_default()
         .into_iter()
         .flat_map(|(port, bindings)| {
@@ -353,36 +1028,573 @@ async fn get_container_details(state: State<'_, DockerState>, id: String) -> Res
         })
         .collect();
 
-    let networks = network_settings.networks.unwrap_or_default()
+    let endpoints: HashMap<String, NetworkEndpoint> = network_settings.networks.unwrap_or_default()
         .into_iter()
         .map(|(name, network)| {
-            let ip = network.ip_address.unwrap_or_default();
-            (name, ip)
+            let container_id = container.id.as_deref().unwrap_or_default();
+            let endpoint = NetworkEndpoint {
+                ip_address: network.ip_address.unwrap_or_default(),
+                ip_prefix_len: network.ip_prefix_len.filter(|len| *len > 0),
+                gateway: network.gateway.unwrap_or_default(),
+                mac_address: network.mac_address.unwrap_or_default(),
+                // The daemon adds the short container id as an alias on user-defined networks
+                aliases: network.aliases.unwrap_or_default()
+                    .into_iter()
+                    .filter(|alias| !container_id.starts_with(alias.as_str()))
+                    .collect(),
+            };
+            (name, endpoint)
         })
         .collect();
+    let networks = endpoints.iter()
+        .map(|(name, endpoint)| (name.clone(), endpoint.ip_address.clone()))
+        .collect();
+    
+    // Docker reports a zero time for containers that never started or finished
+    let docker_time = |time: Option<String>| time.filter(|t| !t.starts_with("0001-01-01"));
+    let health = state_info.health.clone()
+        .and_then(|health| {
+            let status = health.status.map(|s| s.to_string()).filter(|s| !s.is_empty() && s != "none")?;
+            Some(HealthInfo {
+                status,
+                failing_streak: health.failing_streak.unwrap_or(0),
+                log: health.log.unwrap_or_default()
+                    .into_iter()
+                    .map(|probe| HealthProbe {
+                        start: probe.start,
+                        end: probe.end,
+                        exit_code: probe.exit_code,
+                        output: probe.output.unwrap_or_default(),
+                    })
+                    .collect(),
+            })
+        });
+    
+    let state = state_info.status.map(|s| s.to_string()).unwrap_or_default();
+    let oom_killed = state_info.oom_killed.unwrap_or(false);
+    let exit_code = state_info.exit_code;
+    let stopped = !state_info.running.unwrap_or(false) && state != "created";
+    let status = match (&health, exit_code) {
+        (Some(health), _) => format!("{} ({})", state, health.status),
+        (None, Some(code)) if stopped && oom_killed => format!("{} ({}, OOM killed)", state, code),
+        (None, Some(code)) if stopped => format!("{} ({})", state, code),
+        _ => state.clone(),
+    };
+    
+    let restart_policy = host_config.restart_policy.clone().unwrap_or_default();
+    let positive = |value: Option<i64>| value.filter(|v| *v > 0);
+    let resources = ResourceLimits {
+        memory: positive(host_config.memory),
+        memory_reservation: positive(host_config.memory_reservation),
+        memory_swap: positive(host_config.memory_swap),
+        cpus: match (host_config.nano_cpus, host_config.cpu_quota) {
+            (Some(nano_cpus), _) if nano_cpus > 0 => Some(nano_cpus as f64 / 1e9),
+            (_, Some(quota)) if quota > 0 => Some(quota as f64 / host_config.cpu_period.filter(|p| *p > 0).unwrap_or(100000) as f64),
+            _ => None,
+        },
+        cpu_shares: positive(host_config.cpu_shares),
+        pids_limit: positive(host_config.pids_limit),
+    };
+    
+    let image_id = container.image.unwrap_or_default();
+    // A missing image only costs the digests
+    let image_digests = match docker.inspect_image(&image_id).await {
+        Ok(image) => image.repo_digests.unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
 
     Ok(ContainerDetails {
         id: container.id.unwrap_or_default(),
         name,
         image: config.image.unwrap_or_default(),
-        state: state_info.status.map(|s| s.to_string()).unwrap_or_default(),
-        status: format!("{:?}", state_info),
+        state,
+        status,
         created: container.created.unwrap_or_default(),
         ports,
         mounts,
         env: config.env.unwrap_or_default(),
-        network_settings: NetworkInfo { networks },
+        network_settings: NetworkInfo { networks, endpoints },
+        health,
+        exit_code,
+        oom_killed,
+        error: state_info.error.filter(|e| !e.is_empty()),
+        started_at: docker_time(state_info.started_at),
+        finished_at: docker_time(state_info.finished_at),
+        restart_count: container.restart_count.unwrap_or(0),
+        labels: config.labels.unwrap_or_default(),
+        entrypoint: config.entrypoint.unwrap_or_default(),
+        cmd: config.cmd.unwrap_or_default(),
+        working_dir: config.working_dir.filter(|w| !w.is_empty()),
+        user: config.user.filter(|u| !u.is_empty()),
+        restart_policy: RestartPolicyInfo {
+            name: restart_policy.name.map(|n| n.to_string()).filter(|n| !n.is_empty()).unwrap_or_else(|| "no".to_string()),
+            maximum_retry_count: positive(restart_policy.maximum_retry_count),
+        },
+        resources,
+        image_id,
+        image_digests,
     })
 }
 
-#[tauri::command]
-async fn check_docker_connection(state: State<'_, DockerState>) -> Result<bool, String> {
-    let docker = state.docker.lock().await;
-    
-    match docker.ping().await {
-        Ok(_) => Ok(true),
-        Err(e) => Err(format!("Docker connection failed: {}", e)),
-    }
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CopyProgress {
+    bytes_copied: u64,
+    /// Known when copying into a container
+    total_bytes: Option<u64>,
+    done: bool,
+}
+
+// Progress goes out at most this often, so copying many small chunks doesn't flood the frontend
+const COPY_PROGRESS_INTERVAL_MS: u64 = 100;
+
+// bollard has no call for the archive stat endpoint, so the first header of a download tells what
+// a path is. The rest of the download is dropped. None when the path doesn't exist.
+async fn container_path_is_dir(docker: &Docker, id: &str, path: &str) -> Result<Option<bool>, String> {
+    use futures_util::stream::StreamExt;
+    
+    let mut stream = docker.download_from_container(id, Some(DownloadFromContainerOptions { path }));
+    let mut archive = Vec::new();
+    loop {
+        if let Some(is_dir) = transfer::first_entry_is_dir(&archive) {
+            return Ok(Some(is_dir));
+        }
+        match stream.next().await {
+            Some(Ok(chunk)) => archive.extend_from_slice(&chunk),
+            Some(Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. })) => return Ok(None),
+            Some(Err(e)) => return Err(format!("Failed to read '{}' in container: {}", path, e)),
+            None => return Err(format!("Failed to read '{}' in container: empty archive", path)),
+        }
+    }
+}
+
+#[tauri::command]
+async fn copy_to_container(
+    state: State<'_, DockerState>,
+    id: String,
+    host_path: String,
+    container_path: String,
+    on_progress: Channel<CopyProgress>,
+) -> Result<(), String> {
+    validation::validate_volume_path(&host_path)?;
+    validation::validate_volume_path(&container_path)?;
+    let source = std::path::PathBuf::from(&host_path);
+    std::fs::symlink_metadata(&source).map_err(|e| format!("Failed to read '{}': {}", host_path, e))?;
+    
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let target_is_dir = container_path_is_dir(&docker, &id, &container_path).await?.unwrap_or(false);
+    let (directory, rename) = transfer::copy_destination(&container_path, target_is_dir);
+    let root_name = match rename {
+        Some(name) => name,
+        None => source.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| format!("Cannot copy '{}' without a file name", host_path))?,
+    };
+    // Progress counts the archive's bytes, headers and padding included
+    let total_bytes = transfer::archive_size(&source, &root_name);
+    
+    use futures_util::stream::StreamExt;
+    
+    // The archive is built on a blocking thread and streamed to the daemon as it is written
+    let (sender, receiver) = tokio::sync::mpsc::channel::<Vec<u8>>(8);
+    let archiver = tokio::task::spawn_blocking(move || {
+        transfer::write_archive(transfer::ChannelWriter::new(sender), &source, &root_name).map(|_| ())
+    });
+    
+    let mut bytes_copied = 0u64;
+    let mut last_progress = std::time::Instant::now();
+    let progress = on_progress.clone();
+    let body = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    })
+    .map(move |chunk| {
+        bytes_copied += chunk.len() as u64;
+        if last_progress.elapsed() >= std::time::Duration::from_millis(COPY_PROGRESS_INTERVAL_MS) {
+            last_progress = std::time::Instant::now();
+            let _ = progress.send(CopyProgress { bytes_copied, total_bytes: Some(total_bytes), done: false });
+        }
+        chunk.into()
+    });
+    
+    let upload = docker.upload_to_container_streaming(
+        &id,
+        Some(UploadToContainerOptions { path: directory, ..Default::default() }),
+        body,
+    ).await;
+    let archived = archiver.await.map_err(|e| format!("Failed to archive '{}': {}", host_path, e))?;
+    // A failed upload stops the archiver too, so its error is the one that explains what happened
+    upload.map_err(|e| format!("Failed to copy to container: {}", e))?;
+    archived?;
+    
+    let _ = on_progress.send(CopyProgress { bytes_copied: total_bytes, total_bytes: Some(total_bytes), done: true });
+    Ok(())
+}
+
+#[tauri::command]
+async fn copy_from_container(
+    state: State<'_, DockerState>,
+    id: String,
+    container_path: String,
+    host_path: String,
+    on_progress: Channel<CopyProgress>,
+) -> Result<(), String> {
+    validation::validate_volume_path(&container_path)?;
+    validation::validate_volume_path(&host_path)?;
+    let target = std::path::PathBuf::from(&host_path);
+    let (directory, rename) = if target.is_dir() {
+        (target, None)
+    } else {
+        let name = target.file_name().map(|name| name.to_string_lossy().to_string());
+        (target.parent().map(|parent| parent.to_path_buf()).unwrap_or_default(), name)
+    };
+    if !directory.is_dir() {
+        return Err(format!("Directory '{}' does not exist", directory.display()));
+    }
+    
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    use futures_util::stream::StreamExt;
+    
+    // The archive is extracted on a blocking thread as it downloads
+    let (sender, receiver) = tokio::sync::mpsc::channel::<Vec<u8>>(8);
+    let unpacker = tokio::task::spawn_blocking(move || {
+        transfer::unpack_archive(transfer::ChannelReader::new(receiver), &directory, rename.as_deref())
+    });
+    
+    let mut stream = docker.download_from_container(&id, Some(DownloadFromContainerOptions { path: container_path.as_str() }));
+    let mut bytes_copied = 0u64;
+    let mut last_progress = std::time::Instant::now();
+    let mut download_error = None;
+    while let Some(result) = stream.next().await {
+        let chunk = match result {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                download_error = Some(format!("Failed to copy from container: {}", e));
+                break;
+            }
+        };
+        bytes_copied += chunk.len() as u64;
+        // A closed channel means extraction failed; its error is reported below
+        if sender.send(chunk.to_vec()).await.is_err() {
+            break;
+        }
+        if last_progress.elapsed() >= std::time::Duration::from_millis(COPY_PROGRESS_INTERVAL_MS) {
+            last_progress = std::time::Instant::now();
+            let _ = on_progress.send(CopyProgress { bytes_copied, total_bytes: None, done: false });
+        }
+    }
+    drop(sender);
+    
+    let unpacked = unpacker.await.map_err(|e| format!("Failed to extract '{}': {}", container_path, e))?;
+    if let Some(e) = download_error {
+        return Err(e);
+    }
+    unpacked?;
+    
+    let _ = on_progress.send(CopyProgress { bytes_copied, total_bytes: None, done: true });
+    Ok(())
+}
+
+// Streams the archive of `path` to `read` on a blocking thread, as it downloads. Once `read` returns,
+// the rest of the download is dropped.
+async fn read_container_archive<T, F>(docker: &Docker, id: &str, path: &str, read: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(transfer::ChannelReader) -> Result<T, String> + Send + 'static,
+{
+    use futures_util::stream::StreamExt;
+    
+    let (sender, receiver) = tokio::sync::mpsc::channel::<Vec<u8>>(8);
+    let reader = tokio::task::spawn_blocking(move || read(transfer::ChannelReader::new(receiver)));
+    
+    let mut stream = docker.download_from_container(id, Some(DownloadFromContainerOptions { path }));
+    let mut download_error = None;
+    while let Some(result) = stream.next().await {
+        match result {
+            Ok(chunk) => {
+                if sender.send(chunk.to_vec()).await.is_err() {
+                    break;
+                }
+            }
+            Err(e) => {
+                download_error = Some(format!("Failed to read '{}' in container: {}", path, e));
+                break;
+            }
+        }
+    }
+    drop(sender);
+    
+    let read = reader.await.map_err(|e| format!("Failed to read '{}' in container: {}", path, e))?;
+    if let Some(e) = download_error {
+        return Err(e);
+    }
+    read
+}
+
+// Runs a command without a TTY and collects its stdout; None when it can't run or exits non-zero
+async fn exec_output(docker: &Docker, id: &str, cmd: Vec<String>) -> Option<String> {
+    use futures_util::stream::StreamExt;
+    
+    let exec = docker.create_exec(
+        id,
+        CreateExecOptions {
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            cmd: Some(cmd),
+            ..Default::default()
+        },
+    ).await.ok()?;
+    
+    let mut stdout = Vec::new();
+    match docker.start_exec(&exec.id, None).await.ok()? {
+        StartExecResults::Attached { mut output, .. } => {
+            while let Some(Ok(chunk)) = output.next().await {
+                if let bollard::container::LogOutput::StdOut { message } = chunk {
+                    stdout.extend_from_slice(&message);
+                }
+            }
+        }
+        StartExecResults::Detached => return None,
+    }
+    
+    let inspect = docker.inspect_exec(&exec.id).await.ok()?;
+    if inspect.exit_code != Some(0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&stdout).to_string())
+}
+
+#[tauri::command]
+async fn list_container_dir(
+    state: State<'_, DockerState>,
+    id: String,
+    path: String,
+) -> Result<Vec<filesystem::ContainerDirEntry>, String> {
+    validation::validate_volume_path(&path)?;
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    // stat only lists the one directory, so it is tried first. Stopped containers and images
+    // without a shell or stat fall back to the archive, which holds the whole tree below it.
+    let cmd = vec!["sh".to_string(), "-c".to_string(), filesystem::LIST_DIR_SCRIPT.to_string(), "sh".to_string(), path.clone()];
+    if let Some(output) = exec_output(&docker, &id, cmd).await {
+        if let Ok(entries) = filesystem::parse_stat_output(&output) {
+            return Ok(entries);
+        }
+    }
+    
+    // The trailing slash makes the daemon follow a symlinked directory such as /bin
+    let archive_path = if path.ends_with('/') { path.clone() } else { format!("{}/", path) };
+    read_container_archive(&docker, &id, &archive_path, move |reader| filesystem::list_archive_dir(reader, &path)).await
+}
+
+// What read_container_file returns when no limit is given, and the most it returns at all
+const DEFAULT_PREVIEW_BYTES: u64 = 1024 * 1024;
+const MAX_PREVIEW_BYTES: u64 = 16 * 1024 * 1024;
+
+#[tauri::command]
+async fn read_container_file(
+    state: State<'_, DockerState>,
+    id: String,
+    path: String,
+    max_bytes: Option<u64>,
+) -> Result<filesystem::ContainerFile, String> {
+    validation::validate_volume_path(&path)?;
+    let max_bytes = max_bytes.unwrap_or(DEFAULT_PREVIEW_BYTES).min(MAX_PREVIEW_BYTES);
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let file_path = path.clone();
+    read_container_archive(&docker, &id, &path, move |reader| filesystem::read_archive_file(reader, &file_path, max_bytes)).await
+}
+
+// Paths per stat exec when sizing changes, well below the argument length limit
+const CHANGE_SIZE_BATCH: usize = 500;
+
+#[tauri::command]
+async fn container_changes(
+    state: State<'_, DockerState>,
+    id: String,
+) -> Result<filesystem::ContainerChanges, String> {
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let inspect = docker.inspect_container(&id, Some(InspectContainerOptions { size: true }))
+        .await
+        .map_err(|e| format!("Failed to inspect container: {}", e))?;
+    let changes = docker.container_changes(&id)
+        .await
+        .map_err(|e| format!("Failed to get container changes: {}", e))?
+        .unwrap_or_default();
+    
+    // Only a running container can stat its files; otherwise the tree comes without sizes
+    let mut sizes = HashMap::new();
+    let running = inspect.state.as_ref().and_then(|s| s.running).unwrap_or(false);
+    if running {
+        let paths: Vec<String> = changes
+            .iter()
+            .filter(|change| filesystem::ChangeKind::from(change.kind) != filesystem::ChangeKind::Deleted)
+            .map(|change| change.path.clone())
+            .collect();
+        for batch in paths.chunks(CHANGE_SIZE_BATCH) {
+            let mut cmd = vec!["sh".to_string(), "-c".to_string(), filesystem::FILE_SIZES_SCRIPT.to_string(), "sh".to_string()];
+            cmd.extend(batch.iter().cloned());
+            match exec_output(&docker, &id, cmd).await {
+                Some(output) => sizes.extend(filesystem::parse_file_sizes(&output)),
+                None => break,
+            }
+        }
+    }
+    
+    Ok(filesystem::build_changes(&changes, &sizes, inspect.size_rw, inspect.size_root_fs))
+}
+
+async fn top_processes(docker: &Docker, id: &str, ps_args: &str) -> Result<processes::ProcessList, String> {
+    docker.top_processes(id, Some(TopOptions { ps_args }))
+        .await
+        .map(processes::parse_top)
+        .map_err(|e| format!("Failed to list processes: {}", e))
+}
+
+#[tauri::command]
+async fn container_top(
+    state: State<'_, DockerState>,
+    id: String,
+    ps_args: Option<String>,
+) -> Result<processes::ProcessList, String> {
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    top_processes(&docker, &id, ps_args.as_deref().unwrap_or(processes::DEFAULT_PS_ARGS)).await
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProcessSnapshotEvent {
+    stream_id: String,
+    snapshot: Option<processes::ProcessList>,
+    /// Set on the last event, when the container stopped or listing failed
+    ended: bool,
+    error: Option<String>,
+}
+
+const TOP_DEFAULT_INTERVAL_MS: u64 = 2000;
+const TOP_MIN_INTERVAL_MS: u64 = 500;
+
+#[tauri::command]
+async fn stream_container_top(
+    id: String,
+    ps_args: Option<String>,
+    interval_ms: Option<u64>,
+    on_snapshot: Channel<ProcessSnapshotEvent>,
+    docker_state: State<'_, DockerState>,
+    top_state: State<'_, TopStreamState>,
+) -> Result<String, String> {
+    let docker = {
+        let guard = docker_state.docker.lock().await;
+        guard.clone()
+    };
+    let ps_args = ps_args.unwrap_or_else(|| processes::DEFAULT_PS_ARGS.to_string());
+    let interval = interval_ms.unwrap_or(TOP_DEFAULT_INTERVAL_MS).max(TOP_MIN_INTERVAL_MS);
+    
+    let stream_id = format!("top-{}", top_state.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed));
+    let streams = top_state.streams.clone();
+    // Held until the stream is registered, so a stream that ends at once can't unregister before that
+    let mut registry = streams.lock().await;
+    
+    let task_stream_id = stream_id.clone();
+    let task_streams = streams.clone();
+    let task = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(tokio::time::Duration::from_millis(interval));
+        let error = loop {
+            ticker.tick().await;
+            match top_processes(&docker, &id, &ps_args).await {
+                Ok(snapshot) => {
+                    // A closed channel means the process view is gone
+                    let event = ProcessSnapshotEvent { stream_id: task_stream_id.clone(), snapshot: Some(snapshot), ended: false, error: None };
+                    if on_snapshot.send(event).is_err() {
+                        break None;
+                    }
+                }
+                Err(e) => break Some(e),
+            }
+        };
+        
+        let _ = on_snapshot.send(ProcessSnapshotEvent { stream_id: task_stream_id.clone(), snapshot: None, ended: true, error });
+        task_streams.lock().await.remove(&task_stream_id);
+    });
+    
+    registry.insert(stream_id.clone(), task.abort_handle());
+    Ok(stream_id)
+}
+
+#[tauri::command]
+async fn stop_top_stream(stream_id: String, top_state: State<'_, TopStreamState>) -> Result<(), String> {
+    if let Some(handle) = top_state.streams.lock().await.remove(&stream_id) {
+        handle.abort();
+    }
+    Ok(())
+}
+
+/// Signals one process, given by the host PID `container_top` reports, from inside the container.
+/// Needs `sh` in the container.
+#[tauri::command]
+async fn kill_container_process(
+    state: State<'_, DockerState>,
+    id: String,
+    pid: i64,
+    signal: Option<String>,
+) -> Result<(), String> {
+    let signal = validation::validate_signal(signal.as_deref().unwrap_or("TERM"))?;
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    // The host tree is listed on both sides of the container's, so a process that starts or exits
+    // in between can't shift the match onto another one
+    let host_before = processes::host_tree(&top_processes(&docker, &id, processes::TREE_PS_ARGS).await?);
+    let cmd = vec!["sh".to_string(), "-c".to_string(), processes::PROC_STAT_SCRIPT.to_string()];
+    let output = exec_output(&docker, &id, cmd)
+        .await
+        .ok_or_else(|| "Failed to list processes inside the container: it has no shell or is not running".to_string())?;
+    let host_after = processes::host_tree(&top_processes(&docker, &id, processes::TREE_PS_ARGS).await?);
+    if !processes::same_processes(&host_before, &host_after) {
+        return Err("The processes changed while looking up the process; try again".to_string());
+    }
+    let container_pid = processes::container_pid(&host_after, &processes::container_tree(&output), pid)?;
+    
+    // The shell's builtin, as minimal images often have no `kill` binary. It takes names without the SIG prefix.
+    let name = signal.trim_start_matches("SIG");
+    let cmd = ["sh", "-c", r#"kill -s "$1" "$2""#, "sh", name, &container_pid.to_string()].map(String::from).to_vec();
+    exec_output(&docker, &id, cmd)
+        .await
+        .map(|_| ())
+        .ok_or_else(|| format!("Failed to send signal {} to process {}: the container has no shell or the process is gone", signal, pid))
+}
+
+#[tauri::command]
+async fn check_docker_connection(state: State<'_, DockerState>) -> Result<bool, String> {
+    let docker = state.docker.lock().await;
+    
+    match docker.ping().await {
+        Ok(_) => Ok(true),
+        Err(e) => Err(format!("Docker connection failed: {}", e)),
+    }
 }
 
 #[tauri::command]
@@ -764,6 +1976,9 @@ async fn create_container(state: State<'_, DockerState>, request: CreateContaine
     if let Some(ref network) = request.network {
         validation::validate_network_name(network)?;
     }
+    for endpoint in request.extra_networks.iter().flatten() {
+        validation::validate_network_name(&endpoint.name)?;
+    }
     
     // Validate environment variables
     if let Some(ref env_vars) = request.env {
@@ -784,6 +1999,18 @@ async fn create_container(state: State<'_, DockerState>, request: CreateContaine
         }
     }
     
+    if let Some(ref bindings) = request.port_bindings {
+        for binding in bindings {
+            let port_num = binding.container_port.split('/').next().unwrap_or(&binding.container_port);
+            validation::validate_port_string(port_num)?;
+            if let Some(host_port) = binding.host_port.as_deref().filter(|p| !p.is_empty()) {
+                for part in host_port.split('-') {
+                    validation::validate_port_string(part)?;
+                }
+            }
+        }
+    }
+    
     // Validate volume paths
     if let Some(ref volumes) = request.volumes {
         for volume_spec in volumes {
@@ -803,6 +2030,29 @@ async fn create_container(state: State<'_, DockerState>, request: CreateContaine
         }
     }
     
+    for device in request.devices.iter().flatten() {
+        for path in device.split(':').take(2) {
+            validation::validate_volume_path(path)?;
+        }
+    }
+    
+    for path in request.tmpfs.iter().flat_map(|t| t.keys()) {
+        validation::validate_volume_path(path)?;
+    }
+    
+    if let Some(ref mount_requests) = request.mounts {
+        for mount in mount_requests {
+            validation::validate_volume_path(&mount.target)?;
+            match (mount.mount_type.as_str(), &mount.source) {
+                ("bind", Some(source)) => validation::validate_volume_path(source)?,
+                ("bind", None) => return Err(format!("Bind mount for '{}' has no source", mount.target)),
+                ("volume", Some(source)) => validation::validate_name(source)?,
+                ("volume", None) | ("tmpfs", _) => {}
+                (other, _) => return Err(format!("Unsupported mount type: {}", other)),
+            }
+        }
+    }
+    
     let docker = state.docker.lock().await;
     
     // Parse port bindings
@@ -822,6 +2072,18 @@ async fn create_container(state: State<'_, DockerState>, request: CreateContaine
         }
     }
     
+    for binding in request.port_bindings.iter().flatten() {
+        exposed_ports.insert(binding.container_port.clone(), HashMap::new());
+        port_bindings
+            .entry(binding.container_port.clone())
+            .or_insert_with(|| Some(Vec::new()))
+            .get_or_insert_with(Vec::new)
+            .push(PortBinding {
+                host_ip: Some(binding.host_ip.clone().unwrap_or_else(|| "0.0.0.0".to_string())),
+                host_port: binding.host_port.clone(),
+            });
+    }
+    
     // Parse volume bindings
     let mut binds = Vec::new();
     let mut mounts = Vec::new();
@@ -852,6 +2114,52 @@ async fn create_container(state: State<'_, DockerState>, request: CreateContaine
         }
     }
     
+    for mount in request.mounts.iter().flatten() {
+        let read_only = mount.read_only.unwrap_or(false);
+        match mount.mount_type.as_str() {
+            // SELinux relabeling is only available through the legacy bind syntax
+            "bind" if mount.selinux_label.is_some() => {
+                let mut options = vec![if read_only { "ro" } else { "rw" }.to_string()];
+                options.extend(mount.selinux_label.clone());
+                options.extend(mount.bind_propagation.clone());
+                binds.push(format!("{}:{}:{}", mount.source.clone().unwrap_or_default(), mount.target, options.join(",")));
+            }
+            "bind" => mounts.push(Mount {
+                target: Some(mount.target.clone()),
+                source: mount.source.clone(),
+                typ: Some(MountTypeEnum::BIND),
+                read_only: Some(read_only),
+                bind_options: mount.bind_propagation.as_deref().map(|propagation| MountBindOptions {
+                    propagation: propagation.parse().ok(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            "volume" => mounts.push(Mount {
+                target: Some(mount.target.clone()),
+                source: mount.source.clone(),
+                typ: Some(MountTypeEnum::VOLUME),
+                read_only: Some(read_only),
+                volume_options: Some(MountVolumeOptions {
+                    no_copy: mount.volume_nocopy,
+                    subpath: mount.volume_subpath.clone(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            _ => mounts.push(Mount {
+                target: Some(mount.target.clone()),
+                typ: Some(MountTypeEnum::TMPFS),
+                read_only: Some(read_only),
+                tmpfs_options: Some(MountTmpfsOptions {
+                    size_bytes: mount.tmpfs_size,
+                    mode: mount.tmpfs_mode,
+                }),
+                ..Default::default()
+            }),
+        }
+    }
+    
     // Build host config
     let mut host_config = HostConfig {
         port_bindings: if port_bindings.is_empty() { None } else { Some(port_bindings) },
@@ -859,11 +2167,39 @@ async fn create_container(state: State<'_, DockerState>, request: CreateContaine
         mounts: if mounts.is_empty() { None } else { Some(mounts) },
         memory: request.memory_limit,
         cpu_quota: request.cpu_quota,
+        network_mode: request.network.clone(),
+        extra_hosts: request.extra_hosts.clone(),
+        dns: request.dns.clone(),
+        cap_add: request.cap_add.clone(),
+        cap_drop: request.cap_drop.clone(),
+        privileged: request.privileged,
+        devices: request.devices.as_ref().map(|devices| devices.iter().map(|device| {
+            let parts: Vec<&str> = device.split(':').collect();
+            DeviceMapping {
+                path_on_host: Some(parts[0].to_string()),
+                path_in_container: Some(parts.get(1).unwrap_or(&parts[0]).to_string()),
+                cgroup_permissions: Some(parts.get(2).unwrap_or(&"rwm").to_string()),
+            }
+        }).collect()),
+        tmpfs: request.tmpfs.clone(),
+        ulimits: request.ulimits.as_ref().map(|ulimits| ulimits.iter().map(|ulimit| ResourcesUlimits {
+            name: Some(ulimit.name.clone()),
+            soft: Some(ulimit.soft),
+            hard: Some(ulimit.hard),
+        }).collect()),
+        shm_size: request.shm_size,
+        init: request.init,
+        readonly_rootfs: request.read_only,
+        log_config: request.log_driver.as_ref().map(|driver| HostConfigLogConfig {
+            typ: Some(driver.clone()),
+            config: request.log_options.clone(),
+        }),
         ..Default::default()
     };
     
     // Set restart policy
     if let Some(policy) = &request.restart_policy {
+        let (policy, retries) = validation::validate_restart_policy(policy)?;
         let policy_name = match policy.as_str() {
             "always" => bollard::models::RestartPolicyNameEnum::ALWAYS,
             "unless-stopped" => bollard::models::RestartPolicyNameEnum::UNLESS_STOPPED,
@@ -872,205 +2208,1628 @@ async fn create_container(state: State<'_, DockerState>, request: CreateContaine
         };
         host_config.restart_policy = Some(bollard::models::RestartPolicy {
             name: Some(policy_name),
-            maximum_retry_count: if policy == "on-failure" { Some(5) } else { None },
+            // A bare on-failure gives up after 5 tries
+            maximum_retry_count: if policy == "on-failure" { retries.or(Some(5)) } else { None },
             ..Default::default()
         });
     }
     
-    // Build network config
-    let mut endpoints_config = HashMap::new();
-    if let Some(network) = &request.network {
-        endpoints_config.insert(
-            network.clone(),
-            EndpointSettings {
-                ..Default::default()
-            },
-        );
+    // Build network config; `host`, `none` and `container:<name>` are modes without endpoints
+    let mut endpoints_config = HashMap::new();
+    let network_mode = |network: &str| ["host", "none"].contains(&network) || network.starts_with("container:");
+    if let Some(network) = request.network.as_ref().filter(|network| !network_mode(network)) {
+        endpoints_config.insert(
+            network.clone(),
+            endpoint_settings(request.network_aliases.clone(), request.ipv4_address.clone(), request.ipv6_address.clone()),
+        );
+    }
+    
+    // Build container config
+    let config = Config {
+        image: Some(request.image.clone()),
+        env: request.env.clone(),
+        exposed_ports: if exposed_ports.is_empty() { None } else { Some(exposed_ports) },
+        host_config: Some(host_config),
+        cmd: request.command.clone(),
+        labels: request.labels.clone(),
+        networking_config: if endpoints_config.is_empty() { None } else { Some(NetworkingConfig { endpoints_config }) },
+        healthcheck: request.healthcheck.as_ref().map(|healthcheck| HealthConfig {
+            test: Some(healthcheck.test.clone()),
+            interval: healthcheck.interval,
+            timeout: healthcheck.timeout,
+            retries: healthcheck.retries,
+            start_period: healthcheck.start_period,
+            start_interval: healthcheck.start_interval,
+        }),
+        user: request.user.clone(),
+        working_dir: request.working_dir.clone(),
+        entrypoint: request.entrypoint.clone(),
+        hostname: request.hostname.clone(),
+        stop_signal: request.stop_signal.clone(),
+        stop_timeout: request.stop_timeout,
+        ..Default::default()
+    };
+    
+    let options = request.name.as_ref().map(|name| CreateContainerOptions {
+        name: name.as_str(),
+        ..Default::default()
+    });
+    
+    let container = docker.create_container(options, config)
+        .await
+        .map_err(|e| format!("Failed to create container: {}", e))?;
+    
+    let extra_networks = request.extra_networks.iter().flatten()
+        .map(|endpoint| (
+            endpoint.name.clone(),
+            endpoint_settings(endpoint.aliases.clone(), endpoint.ipv4_address.clone(), endpoint.ipv6_address.clone()),
+        ))
+        .collect();
+    connect_networks(&docker, &container.id, extra_networks).await?;
+    
+    Ok(container.id)
+}
+
+// Endpoint settings with the aliases and static addresses a container has on a network
+fn endpoint_settings(aliases: Option<Vec<String>>, ipv4_address: Option<String>, ipv6_address: Option<String>) -> EndpointSettings {
+    let ipam_config = (ipv4_address.is_some() || ipv6_address.is_some())
+        .then(|| EndpointIpamConfig { ipv4_address, ipv6_address, ..Default::default() });
+    EndpointSettings {
+        aliases,
+        ipam_config,
+        ..Default::default()
+    }
+}
+
+// Joins a created container to more networks. The container is removed if one fails,
+// rather than left running cut off from some of them.
+async fn connect_networks(docker: &Docker, id: &str, endpoints: Vec<(String, EndpointSettings)>) -> Result<(), String> {
+    for (network, endpoint_config) in endpoints {
+        let connected = docker.connect_network(&network, ConnectNetworkOptions { container: id, endpoint_config }).await;
+        if let Err(e) = connected {
+            let _ = docker.remove_container(id, Some(RemoveContainerOptions {
+                force: true,
+                ..Default::default()
+            })).await;
+            return Err(format!("Failed to connect container to network '{}': {}", network, e));
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn create_and_start_container(state: State<'_, DockerState>, request: CreateContainerRequest) -> Result<String, String> {
+    // First, create the container
+    let container_id = create_container(state.clone(), request).await?;
+    
+    // Then, start it immediately
+    let docker = state.docker.lock().await;
+    docker.start_container::<String>(&container_id, None)
+        .await
+        .map_err(|e| format!("Failed to start container after creation: {}", e))?;
+    
+    Ok(container_id)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ComposeDeployResult {
+    service_name: String,
+    container_id: Option<String>,
+    success: bool,
+    error: Option<String>,
+    // Variables referenced by the service that were not set and resolved to an empty string
+    unset_variables: Vec<String>,
+    action: Option<compose::PlanAction>,
+    // Parts of the service that were accepted but not applied: unsupported keys, and the uid, gid
+    // and mode of secrets and configs, which are bind-mounted with their host ownership
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ServicePlan {
+    service_name: String,
+    action: compose::PlanAction,
+    container_id: Option<String>,
+    container_name: Option<String>,
+    // Settings that differ from the existing container, e.g. "image" or "environment"
+    changes: Vec<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ComposePlan {
+    project: String,
+    services: Vec<ServicePlan>,
+    networks_to_create: Vec<String>,
+    volumes_to_create: Vec<String>,
+}
+
+// How long to wait for a dependency to become healthy or to finish before giving up
+const DEPENDENCY_TIMEOUT_SECS: u64 = 300;
+
+// Inputs besides the main file that shape the compose model
+struct ComposeLoadOptions {
+    override_contents: Option<Vec<String>>,
+    profiles: Option<Vec<String>>,
+    project_name: Option<String>,
+    env_content: Option<String>,
+}
+
+// Parse, merge and interpolate a compose file the way the compose commands all need it
+fn prepare_compose(
+    yaml_content: &str,
+    options: ComposeLoadOptions,
+    base_dir: Option<&std::path::Path>,
+) -> Result<(ComposeFile, BTreeMap<String, compose::InterpolationIssues>), String> {
+    let ComposeLoadOptions { override_contents, profiles, project_name, env_content } = options;
+    // Interpolation variables: the supplied .env text (or the project's .env file), overridden by the process environment
+    let env_content = match (env_content, base_dir) {
+        (Some(content), _) => Some(content),
+        (None, Some(dir)) => std::fs::read_to_string(dir.join(".env")).ok(),
+        (None, None) => None,
+    };
+    let mut vars: HashMap<String, String> = env_content
+        .map(|content| compose::parse_env_file(&content).into_iter().collect())
+        .unwrap_or_default();
+    vars.extend(std::env::vars());
+    
+    let mut documents = vec![yaml_content];
+    documents.extend(override_contents.iter().flatten().map(String::as_str));
+    let (mut compose, issues) = compose::load(&documents, &vars)?;
+    
+    if let Some(project) = project_name {
+        compose.name = Some(project);
+    }
+    // Like the compose CLI, an unnamed file takes its directory's name. Without a directory there
+    // is nothing to tell its containers from another unnamed deployment's, so a name is required.
+    if compose.name.is_none() {
+        let from_dir = base_dir.and_then(compose::project_name_from_dir)
+            .ok_or("The compose file has no `name:`; add one or choose a project name")?;
+        compose.name = Some(from_dir);
+    }
+    // Without explicit profiles, COMPOSE_PROFILES decides like it does for the compose CLI
+    let profiles = profiles.unwrap_or_else(|| {
+        vars.get("COMPOSE_PROFILES")
+            .map(|p| p.split(',').map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect())
+            .unwrap_or_default()
+    });
+    compose.apply_profiles(&profiles)?;
+    validation::validate_project_name(compose.project_name())?;
+    compose.add_default_network();
+    
+    Ok((compose, issues))
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn deploy_compose(
+    state: State<'_, DockerState>,
+    yaml_content: String,
+    override_contents: Option<Vec<String>>,
+    profiles: Option<Vec<String>>,
+    project_name: Option<String>,
+    env_content: Option<String>,
+    working_dir: Option<String>,
+    on_build_progress: Channel<BuildProgressEvent>,
+) -> Result<Vec<ComposeDeployResult>, String> {
+    let base_dir = working_dir.as_deref().map(std::path::Path::new);
+    let options = ComposeLoadOptions { override_contents, profiles, project_name, env_content };
+    let (compose, issues) = prepare_compose(&yaml_content, options, base_dir)?;
+    
+    let order = compose::deployment_order(&compose.services)?;
+    
+    {
+        let docker = state.docker.lock().await;
+        create_compose_networks(&docker, &compose).await?;
+        create_compose_volumes(&docker, &compose).await?;
+    }
+    
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let mut results = Vec::new();
+    // Container IDs of each deployed service, one per replica
+    let mut deployed: HashMap<String, Vec<String>> = HashMap::new();
+    
+    for service_name in order {
+        let service = &compose.services[&service_name];
+        let service_issues = issues.get(&service_name).cloned().unwrap_or_default();
+        
+        let prepared = if !service_issues.required.is_empty() {
+            Err(service_issues.required.join("; "))
+        } else {
+            let built = match &service.build {
+                Some(build) => build_service_image(&state, &compose, &service_name, service, build, base_dir, &on_build_progress).await,
+                None => Ok(()),
+            };
+            match built {
+                Ok(()) => wait_for_dependencies(&state, service, &deployed).await,
+                Err(e) => Err(e),
+            }
+        };
+        
+        let result = ComposeDeployResult {
+            service_name: service_name.clone(),
+            container_id: None,
+            success: false,
+            error: None,
+            unset_variables: service_issues.unset.into_iter().collect(),
+            action: None,
+            warnings: service.warnings(),
+        };
+        if let Err(e) = prepared {
+            results.push(ComposeDeployResult { error: Some(e), ..result });
+            continue;
+        }
+        
+        let replicas = service.replicas();
+        for number in 1..=replicas {
+            let mut replica_result = result.clone();
+            match apply_service_plan(&state, &compose, &service_name, service, number, base_dir).await {
+                Ok((container_id, action)) => {
+                    replica_result.action = Some(action);
+                    deployed.entry(service_name.clone()).or_default().push(container_id.clone());
+                    replica_result.container_id = Some(container_id);
+                    replica_result.success = true;
+                }
+                Err(e) => replica_result.error = Some(e),
+            }
+            results.push(replica_result);
+        }
+        
+        // Scaling down: remove the replicas the service no longer asks for
+        for container in list_service_containers(&docker, compose.project_name(), &service_name).await? {
+            let number = container_number(&container);
+            if number > 0 && number <= replicas {
+                continue;
+            }
+            let container_id = container.id.clone().unwrap_or_default();
+            let mut replica_result = result.clone();
+            replica_result.action = Some(compose::PlanAction::Remove);
+            match docker.remove_container(&container_id, Some(RemoveContainerOptions {
+                force: true,
+                ..Default::default()
+            })).await {
+                Ok(()) => replica_result.success = true,
+                Err(e) => replica_result.error = Some(format!("Failed to remove container: {}", e)),
+            }
+            replica_result.container_id = Some(container_id);
+            results.push(replica_result);
+        }
+    }
+    
+    Ok(results)
+}
+
+// User labels of a network or volume plus the labels that tie it to its project
+fn compose_resource_labels(
+    compose: &ComposeFile,
+    labels: Option<&compose::MappingOrList>,
+    key_label: &str,
+    key: &str,
+) -> HashMap<String, String> {
+    let mut labels = labels.map(|l| l.to_map()).unwrap_or_default();
+    labels.insert(compose::PROJECT_LABEL.to_string(), compose.project_name().to_string());
+    labels.insert(key_label.to_string(), key.to_string());
+    labels
+}
+
+// Create the top-level networks of a compose file that don't exist yet
+async fn create_compose_networks(docker: &Docker, compose: &ComposeFile) -> Result<(), String> {
+    let mut keys: Vec<&String> = compose.networks.keys().collect();
+    keys.sort();
+    
+    for key in keys {
+        let network = compose.networks[key].clone().unwrap_or_default();
+        let name = compose.network_name(key);
+        let exists = docker.inspect_network(&name, None::<InspectNetworkOptions<String>>).await.is_ok();
+        
+        if network.is_external() {
+            if !exists {
+                return Err(format!("External network '{}' does not exist", name));
+            }
+            continue;
+        }
+        if exists {
+            continue;
+        }
+        
+        validation::validate_network_name(&name)?;
+        
+        let ipam = network.ipam.map(|ipam| Ipam {
+            driver: ipam.driver,
+            config: ipam.config.map(|pools| {
+                pools.into_iter().map(|pool| IpamConfig {
+                    subnet: pool.subnet,
+                    ip_range: pool.ip_range,
+                    gateway: pool.gateway,
+                    auxiliary_addresses: pool.aux_addresses,
+                }).collect()
+            }),
+            options: ipam.options,
+        }).unwrap_or_default();
+        
+        let config = CreateNetworkOptions {
+            name: name.clone(),
+            driver: network.driver.unwrap_or_else(|| "bridge".to_string()),
+            internal: network.internal.unwrap_or(false),
+            attachable: network.attachable.unwrap_or(false),
+            ipam,
+            options: network.driver_opts.unwrap_or_default(),
+            labels: compose_resource_labels(compose, network.labels.as_ref(), compose::NETWORK_LABEL, key),
+            ..Default::default()
+        };
+        
+        docker.create_network(config)
+            .await
+            .map_err(|e| format!("Failed to create network '{}': {}", name, e))?;
+    }
+    
+    Ok(())
+}
+
+// Create the top-level volumes of a compose file that don't exist yet
+async fn create_compose_volumes(docker: &Docker, compose: &ComposeFile) -> Result<(), String> {
+    let mut keys: Vec<&String> = compose.volumes.keys().collect();
+    keys.sort();
+    
+    for key in keys {
+        let volume = compose.volumes[key].clone().unwrap_or_default();
+        let name = compose.volume_name(key);
+        let exists = docker.inspect_volume(&name).await.is_ok();
+        
+        if volume.is_external() {
+            if !exists {
+                return Err(format!("External volume '{}' does not exist", name));
+            }
+            continue;
+        }
+        if exists {
+            continue;
+        }
+        
+        validation::validate_name(&name)?;
+        
+        let config = CreateVolumeOptions {
+            name: name.clone(),
+            driver: volume.driver.unwrap_or_else(|| "local".to_string()),
+            driver_opts: volume.driver_opts.unwrap_or_default(),
+            labels: compose_resource_labels(compose, volume.labels.as_ref(), compose::VOLUME_LABEL, key),
+        };
+        
+        docker.create_volume(config)
+            .await
+            .map_err(|e| format!("Failed to create volume '{}': {}", name, e))?;
+    }
+    
+    Ok(())
+}
+
+async fn wait_for_dependencies(
+    state: &State<'_, DockerState>,
+    service: &ComposeService,
+    deployed: &HashMap<String, Vec<String>>,
+) -> Result<(), String> {
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    for (dependency, condition) in service.dependencies() {
+        let container_ids = deployed.get(&dependency)
+            .ok_or_else(|| format!("Dependency '{}' failed to deploy", dependency))?;
+        
+        for container_id in container_ids {
+            match condition {
+                // Dependencies are started as part of their own deployment
+                DependencyCondition::Started => {}
+                DependencyCondition::Healthy => {
+                    wait_for_healthy(&docker, &dependency, container_id).await?;
+                }
+                DependencyCondition::CompletedSuccessfully => {
+                    wait_for_completion(&docker, &dependency, container_id).await?;
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+async fn wait_for_healthy(docker: &Docker, service_name: &str, container_id: &str) -> Result<(), String> {
+    use bollard::models::HealthStatusEnum;
+    
+    let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(DEPENDENCY_TIMEOUT_SECS);
+    
+    loop {
+        let container = docker.inspect_container(container_id, None)
+            .await
+            .map_err(|e| format!("Failed to inspect dependency '{}': {}", service_name, e))?;
+        let container_state = container.state.unwrap_or_default();
+        
+        match container_state.health.and_then(|h| h.status) {
+            Some(HealthStatusEnum::HEALTHY) => return Ok(()),
+            Some(HealthStatusEnum::UNHEALTHY) => {
+                return Err(format!("Dependency '{}' is unhealthy", service_name));
+            }
+            Some(HealthStatusEnum::STARTING) => {}
+            _ => return Err(format!("Dependency '{}' has no healthcheck to wait for", service_name)),
+        }
+        
+        if !container_state.running.unwrap_or(false) {
+            return Err(format!("Dependency '{}' exited before becoming healthy", service_name));
+        }
+        
+        if tokio::time::Instant::now() >= deadline {
+            return Err(format!("Timed out waiting for dependency '{}' to become healthy", service_name));
+        }
+        
+        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    }
+}
+
+async fn wait_for_completion(docker: &Docker, service_name: &str, container_id: &str) -> Result<(), String> {
+    use futures_util::stream::StreamExt;
+    
+    let mut wait_stream = docker.wait_container(container_id, None::<WaitContainerOptions<String>>);
+    let result = tokio::time::timeout(
+        tokio::time::Duration::from_secs(DEPENDENCY_TIMEOUT_SECS),
+        wait_stream.next(),
+    )
+        .await
+        .map_err(|_| format!("Timed out waiting for dependency '{}' to complete", service_name))?;
+    
+    match result {
+        Some(Ok(_)) | None => Ok(()),
+        Some(Err(bollard::errors::Error::DockerContainerWaitError { code, .. })) => {
+            Err(format!("Dependency '{}' exited with code {}", service_name, code))
+        }
+        Some(Err(e)) => Err(format!("Failed to wait for dependency '{}': {}", service_name, e)),
+    }
+}
+
+// Show the merged and normalized compose model, like `docker compose config`
+#[tauri::command]
+async fn render_compose_config(
+    yaml_content: String,
+    override_contents: Option<Vec<String>>,
+    profiles: Option<Vec<String>>,
+    project_name: Option<String>,
+    env_content: Option<String>,
+    working_dir: Option<String>,
+) -> Result<String, String> {
+    let base_dir = working_dir.as_deref().map(std::path::Path::new);
+    let options = ComposeLoadOptions { override_contents, profiles, project_name, env_content };
+    let (compose, issues) = prepare_compose(&yaml_content, options, base_dir)?;
+    
+    let required: Vec<String> = issues.into_values().flat_map(|i| i.required).collect();
+    if !required.is_empty() {
+        return Err(required.join("; "));
+    }
+    compose.render()
+}
+
+#[tauri::command]
+async fn generate_compose(state: State<'_, DockerState>, container_ids: Vec<String>) -> Result<String, String> {
+    if container_ids.is_empty() {
+        return Err("No containers selected".to_string());
+    }
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let mut snapshots = Vec::new();
+    for id in &container_ids {
+        let inspect = docker.inspect_container(id, None)
+            .await
+            .map_err(|e| format!("Failed to inspect container: {}", e))?;
+        let image_config = match &inspect.image {
+            Some(image_id) => docker.inspect_image(image_id)
+                .await
+                .map_err(|e| format!("Failed to inspect image: {}", e))?
+                .config
+                .unwrap_or_default(),
+            None => Default::default(),
+        };
+        snapshots.push(container_snapshot(inspect, image_config));
+    }
+    
+    compose::generate_compose(&snapshots)
+}
+
+fn healthcheck_snapshot(healthcheck: Option<HealthConfig>) -> Option<compose::HealthcheckSnapshot> {
+    let healthcheck = healthcheck?;
+    Some(compose::HealthcheckSnapshot {
+        test: healthcheck.test.filter(|test| !test.is_empty())?,
+        interval: healthcheck.interval,
+        timeout: healthcheck.timeout,
+        retries: healthcheck.retries,
+        start_period: healthcheck.start_period,
+    })
+}
+
+fn container_snapshot(
+    inspect: bollard::models::ContainerInspectResponse,
+    image_config: bollard::models::ContainerConfig,
+) -> compose::ContainerSnapshot {
+    let config = inspect.config.unwrap_or_default();
+    let host_config = inspect.host_config.unwrap_or_default();
+    
+    let mut ports = Vec::new();
+    for (container_port, bindings) in host_config.port_bindings.unwrap_or_default() {
+        for binding in bindings.unwrap_or_default() {
+            ports.push((container_port.clone(), binding.host_ip.unwrap_or_default(), binding.host_port.unwrap_or_default()));
+        }
+    }
+    ports.sort();
+    
+    // Inspect lists every mount, including the ones created from `binds`
+    let mounts = inspect.mounts.unwrap_or_default()
+        .into_iter()
+        .map(|mount| {
+            let kind = mount.typ.map(|t| t.to_string()).unwrap_or_default();
+            compose::MountSnapshot {
+                source: if kind == "volume" { mount.name } else { mount.source },
+                kind,
+                target: mount.destination.unwrap_or_default(),
+                read_only: !mount.rw.unwrap_or(true),
+            }
+        })
+        .collect();
+    
+    let networks = inspect.network_settings
+        .and_then(|settings| settings.networks)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, endpoint)| (name, endpoint.aliases.unwrap_or_default()))
+        .collect();
+    
+    let restart_policy = host_config.restart_policy.unwrap_or_default();
+    let cpus = match (host_config.nano_cpus, host_config.cpu_quota) {
+        (Some(nano_cpus), _) if nano_cpus > 0 => Some(nano_cpus as f64 / 1e9),
+        (_, Some(quota)) if quota > 0 => Some(quota as f64 / host_config.cpu_period.filter(|p| *p > 0).unwrap_or(100000) as f64),
+        _ => None,
+    };
+    
+    compose::ContainerSnapshot {
+        id: inspect.id.unwrap_or_default(),
+        name: inspect.name.unwrap_or_default().trim_start_matches('/').to_string(),
+        image: config.image.clone().unwrap_or_default(),
+        config: compose::ProcessConfig {
+            env: config.env.unwrap_or_default(),
+            command: config.cmd,
+            entrypoint: config.entrypoint,
+            user: config.user,
+            working_dir: config.working_dir,
+            labels: config.labels.unwrap_or_default(),
+            healthcheck: healthcheck_snapshot(config.healthcheck),
+        },
+        image_config: compose::ProcessConfig {
+            env: image_config.env.unwrap_or_default(),
+            command: image_config.cmd,
+            entrypoint: image_config.entrypoint,
+            user: image_config.user,
+            working_dir: image_config.working_dir,
+            labels: image_config.labels.unwrap_or_default(),
+            healthcheck: healthcheck_snapshot(image_config.healthcheck),
+        },
+        ports,
+        mounts,
+        network_mode: host_config.network_mode,
+        networks,
+        restart_policy: restart_policy.name.map(|name| name.to_string()),
+        restart_retries: restart_policy.maximum_retry_count,
+        memory: host_config.memory,
+        cpus,
+    }
+}
+
+#[tauri::command]
+async fn plan_compose(
+    state: State<'_, DockerState>,
+    yaml_content: String,
+    override_contents: Option<Vec<String>>,
+    profiles: Option<Vec<String>>,
+    project_name: Option<String>,
+    env_content: Option<String>,
+    working_dir: Option<String>,
+) -> Result<ComposePlan, String> {
+    let base_dir = working_dir.as_deref().map(std::path::Path::new);
+    let options = ComposeLoadOptions { override_contents, profiles, project_name, env_content };
+    let (compose, issues) = prepare_compose(&yaml_content, options, base_dir)?;
+    let order = compose::deployment_order(&compose.services)?;
+    
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let mut services = Vec::new();
+    for service_name in &order {
+        let service = &compose.services[service_name];
+        let replicas = service.replicas();
+        for number in 1..=replicas {
+            let mut plan = plan_service(&docker, &compose, service_name, service, number, base_dir).await?;
+            if let Some(service_issues) = issues.get(service_name).filter(|i| !i.required.is_empty()) {
+                plan.error = Some(service_issues.required.join("; "));
+            }
+            services.push(plan);
+        }
+        for container in list_service_containers(&docker, compose.project_name(), service_name).await? {
+            let number = container_number(&container);
+            if number > 0 && number <= replicas {
+                continue;
+            }
+            let info = convert_container_summary(container);
+            services.push(ServicePlan {
+                service_name: service_name.clone(),
+                action: compose::PlanAction::Remove,
+                container_id: Some(info.id),
+                container_name: Some(info.name),
+                changes: Vec::new(),
+                error: None,
+            });
+        }
+    }
+    
+    for container in list_project_containers(&docker, compose.project_name()).await? {
+        let service_name = container.labels.as_ref()
+            .and_then(|l| l.get(compose::SERVICE_LABEL))
+            .cloned()
+            .unwrap_or_default();
+        if compose.services.contains_key(&service_name) {
+            continue;
+        }
+        let info = convert_container_summary(container);
+        services.push(ServicePlan {
+            service_name,
+            action: compose::PlanAction::Orphaned,
+            container_id: Some(info.id),
+            container_name: Some(info.name),
+            changes: Vec::new(),
+            error: None,
+        });
+    }
+    
+    let mut networks_to_create = Vec::new();
+    let mut keys: Vec<&String> = compose.networks.keys().collect();
+    keys.sort();
+    for key in keys {
+        let external = compose.networks[key].as_ref().is_some_and(|n| n.is_external());
+        let name = compose.network_name(key);
+        if !external && docker.inspect_network(&name, None::<InspectNetworkOptions<String>>).await.is_err() {
+            networks_to_create.push(name);
+        }
+    }
+    
+    let mut volumes_to_create = Vec::new();
+    let mut keys: Vec<&String> = compose.volumes.keys().collect();
+    keys.sort();
+    for key in keys {
+        let external = compose.volumes[key].as_ref().is_some_and(|v| v.is_external());
+        let name = compose.volume_name(key);
+        if !external && docker.inspect_volume(&name).await.is_err() {
+            volumes_to_create.push(name);
+        }
+    }
+    
+    Ok(ComposePlan {
+        project: compose.project_name().to_string(),
+        services,
+        networks_to_create,
+        volumes_to_create,
+    })
+}
+
+// Compare a service with the container deployed for it earlier, if any
+async fn plan_service(
+    docker: &Docker,
+    compose: &ComposeFile,
+    service_name: &str,
+    service: &ComposeService,
+    number: u32,
+    base_dir: Option<&std::path::Path>,
+) -> Result<ServicePlan, String> {
+    let mut plan = ServicePlan {
+        service_name: service_name.to_string(),
+        action: compose::PlanAction::Create,
+        container_id: None,
+        container_name: None,
+        changes: Vec::new(),
+        error: None,
+    };
+    
+    let request = match service_container_request(compose, service_name, service, number, base_dir) {
+        Ok((request, _)) => request,
+        Err(e) => {
+            plan.error = Some(e);
+            return Ok(plan);
+        }
+    };
+    plan.container_name = request.name.clone();
+    
+    let existing = list_service_containers(docker, compose.project_name(), service_name).await?
+        .into_iter()
+        .find(|container| container_number(container) == number);
+    
+    let Some(existing) = existing else {
+        let name = request.name.clone().unwrap_or_default();
+        if docker.inspect_container(&name, None).await.is_ok() {
+            plan.error = Some(format!(
+                "Container name '{}' is already in use by a container outside project '{}'",
+                name,
+                compose.project_name()
+            ));
+        }
+        return Ok(plan);
+    };
+    
+    let container_id = existing.id.clone().unwrap_or_default();
+    let inspect = docker.inspect_container(&container_id, None)
+        .await
+        .map_err(|e| format!("Failed to inspect container: {}", e))?;
+    let image = docker.inspect_image(&request.image).await.ok();
+    
+    let mut desired = container_spec_from_request(&request);
+    desired.image_id = image.as_ref().and_then(|i| i.id.clone());
+    let actual = container_spec_from_inspect(&inspect);
+    let image_env = image.as_ref().and_then(|i| i.config.as_ref()).map(|c| c.env.clone().unwrap_or_default());
+    
+    plan.changes = compose::diff_specs(&desired, &actual, image_env.as_deref());
+    let actual_name = inspect.name.as_deref().map(|n| n.trim_start_matches('/'));
+    if actual_name != request.name.as_deref() {
+        plan.changes.push("container_name".to_string());
+    }
+    plan.action = compose::PlanAction::for_changes(&plan.changes);
+    plan.container_id = Some(container_id);
+    Ok(plan)
+}
+
+fn container_spec_from_request(request: &CreateContainerRequest) -> compose::ContainerSpec {
+    let mut spec = compose::ContainerSpec {
+        image: request.image.clone(),
+        env: request.env.clone().unwrap_or_default(),
+        command: request.command.clone(),
+        network: request.network.clone(),
+        memory: request.memory_limit,
+        cpu_quota: request.cpu_quota,
+        config_hash: request.labels.as_ref().and_then(|l| l.get(compose::CONFIG_HASH_LABEL)).cloned(),
+        ..Default::default()
+    };
+    for binding in request.port_bindings.iter().flatten() {
+        spec.add_port(&binding.container_port, binding.host_ip.as_deref(), binding.host_port.as_deref());
+    }
+    for mount in request.mounts.iter().flatten() {
+        spec.add_mount(&mount.mount_type, mount.source.as_deref(), &mount.target, mount.read_only.unwrap_or(false));
+    }
+    spec
+}
+
+fn container_spec_from_inspect(inspect: &bollard::models::ContainerInspectResponse) -> compose::ContainerSpec {
+    let config = inspect.config.clone().unwrap_or_default();
+    let host_config = inspect.host_config.clone().unwrap_or_default();
+    
+    let mut spec = compose::ContainerSpec {
+        image: config.image.unwrap_or_default(),
+        image_id: inspect.image.clone(),
+        env: config.env.unwrap_or_default(),
+        command: config.cmd,
+        network: host_config.network_mode,
+        memory: host_config.memory,
+        cpu_quota: host_config.cpu_quota,
+        config_hash: config.labels.as_ref().and_then(|l| l.get(compose::CONFIG_HASH_LABEL)).cloned(),
+        ..Default::default()
+    };
+    for (container_port, bindings) in host_config.port_bindings.unwrap_or_default() {
+        for binding in bindings.unwrap_or_default() {
+            spec.add_port(&container_port, binding.host_ip.as_deref(), binding.host_port.as_deref());
+        }
+    }
+    for mount in host_config.mounts.unwrap_or_default() {
+        let kind = mount.typ.map(|t| t.to_string()).unwrap_or_default();
+        spec.add_mount(&kind, mount.source.as_deref(), &mount.target.unwrap_or_default(), mount.read_only.unwrap_or(false));
+    }
+    for bind in host_config.binds.unwrap_or_default() {
+        spec.add_bind(&bind);
+    }
+    spec
+}
+
+// Deploy a service according to its plan: keep, replace or create its container
+async fn apply_service_plan(
+    state: &State<'_, DockerState>,
+    compose: &ComposeFile,
+    service_name: &str,
+    service: &ComposeService,
+    number: u32,
+    base_dir: Option<&std::path::Path>,
+) -> Result<(String, compose::PlanAction), String> {
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let plan = plan_service(&docker, compose, service_name, service, number, base_dir).await?;
+    if let Some(error) = plan.error {
+        return Err(error);
+    }
+    
+    match (plan.action, plan.container_id) {
+        (compose::PlanAction::Unchanged, Some(container_id)) => {
+            let inspect = docker.inspect_container(&container_id, None)
+                .await
+                .map_err(|e| format!("Failed to inspect container: {}", e))?;
+            let running = inspect.state.and_then(|s| s.running).unwrap_or(false);
+            if !running {
+                docker.start_container::<String>(&container_id, None)
+                    .await
+                    .map_err(|e| format!("Failed to start container: {}", e))?;
+            }
+            Ok((container_id, compose::PlanAction::Unchanged))
+        }
+        (compose::PlanAction::Recreate, Some(old_id)) => {
+            // The replacement is created under a temporary name while the old container still runs,
+            // so a request the daemon refuses leaves the service as it was
+            let mut request = service_request_with_paths(compose, service_name, service, number, base_dir)?;
+            let name = request.name.take();
+            request.name = name.as_ref().map(|name| format!("{}_{}", &old_id[..old_id.len().min(12)], name));
+            let container_id = create_container(state.clone(), request).await?;
+            
+            let _ = docker.stop_container(&old_id, None).await;
+            let force = Some(RemoveContainerOptions { force: true, ..Default::default() });
+            if let Err(e) = docker.remove_container(&old_id, force).await {
+                let _ = docker.remove_container(&container_id, force).await;
+                return Err(format!("Failed to remove container: {}", e));
+            }
+            if let Some(name) = name {
+                docker.rename_container(&container_id, RenameContainerOptions { name })
+                    .await
+                    .map_err(|e| format!("Failed to rename container: {}", e))?;
+            }
+            docker.start_container::<String>(&container_id, None)
+                .await
+                .map_err(|e| format!("Failed to start container after creation: {}", e))?;
+            Ok((container_id, compose::PlanAction::Recreate))
+        }
+        _ => {
+            let container_id = deploy_service(state, compose, service_name, service, number, base_dir).await?;
+            Ok((container_id, compose::PlanAction::Create))
+        }
+    }
+}
+
+// Build the image of a service with a `build` section, streaming the daemon's output
+async fn build_service_image(
+    state: &State<'_, DockerState>,
+    compose: &ComposeFile,
+    service_name: &str,
+    service: &ComposeService,
+    build: &compose::ComposeBuild,
+    base_dir: Option<&std::path::Path>,
+    on_progress: &Channel<BuildProgressEvent>,
+) -> Result<(), String> {
+    use futures_util::stream::StreamExt;
+    
+    let build = build.config();
+    let image = service.image_name(compose.project_name(), service_name).ok_or("No image specified")?;
+    validation::validate_image_name(&image)?;
+    
+    let send = |stream: Option<String>, status: Option<String>, complete: bool, error: Option<String>| {
+        let _ = on_progress.send(BuildProgressEvent {
+            service: service_name.to_string(),
+            image: image.clone(),
+            stream,
+            status,
+            complete,
+            error,
+        });
+    };
+    
+    let (remote, context) = if build.is_remote() {
+        if build.target.is_some() {
+            return Err("Build targets are not supported for remote build contexts".to_string());
+        }
+        (build.context().to_string(), None)
+    } else {
+        let context_dir = match base_dir {
+            Some(dir) => dir.join(build.context()),
+            None if std::path::Path::new(build.context()).is_absolute() => build.context().into(),
+            None => return Err(format!("Build context '{}' is relative but no project directory was given", build.context())),
+        };
+        let dockerfile = build.dockerfile().to_string();
+        let target = build.target.clone();
+        // Walking and packing the context is blocking file I/O
+        let archive = tokio::task::spawn_blocking(move || compose::tar_context(&context_dir, &dockerfile, target.as_deref()))
+            .await
+            .map_err(|e| format!("Failed to create build context archive: {}", e))??;
+        (String::new(), Some(archive))
+    };
+    
+    let mut labels = build.labels.as_ref().map(|l| l.to_map()).unwrap_or_default();
+    labels.insert(compose::PROJECT_LABEL.to_string(), compose.project_name().to_string());
+    labels.insert(compose::SERVICE_LABEL.to_string(), service_name.to_string());
+    
+    let options = bollard::image::BuildImageOptions {
+        dockerfile: build.dockerfile().to_string(),
+        t: image.clone(),
+        remote,
+        buildargs: build.args.as_ref().map(|a| a.to_map()).unwrap_or_default(),
+        labels,
+        cachefrom: build.cache_from.clone().unwrap_or_default(),
+        networkmode: build.network.clone().unwrap_or_default(),
+        pull: build.pull.unwrap_or(false),
+        nocache: build.no_cache.unwrap_or(false),
+        rm: true,
+        ..Default::default()
+    };
+    
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    send(None, Some(format!("Building {}", image)), false, None);
+    let mut stream = docker.build_image(options, None, context.map(Into::into));
+    while let Some(result) = stream.next().await {
+        let info = match result {
+            Ok(info) => info,
+            Err(e) => {
+                let error = format!("Failed to build image: {}", e);
+                send(None, None, true, Some(error.clone()));
+                return Err(error);
+            }
+        };
+        if let Some(error) = info.error {
+            send(None, None, true, Some(error.clone()));
+            return Err(format!("Failed to build image: {}", error));
+        }
+        if info.stream.is_some() || info.status.is_some() {
+            send(info.stream, info.status, false, None);
+        }
+    }
+    
+    send(None, Some("Build complete".to_string()), true, None);
+    Ok(())
+}
+
+async fn deploy_service(
+    state: &State<'_, DockerState>,
+    compose: &ComposeFile,
+    service_name: &str,
+    service: &ComposeService,
+    number: u32,
+    base_dir: Option<&std::path::Path>,
+) -> Result<String, String> {
+    let request = service_request_with_paths(compose, service_name, service, number, base_dir)?;
+    create_and_start_container(state.clone(), request).await
+}
+
+// The container request for a replica, once the bind sources it needs exist
+fn service_request_with_paths(
+    compose: &ComposeFile,
+    service_name: &str,
+    service: &ComposeService,
+    number: u32,
+    base_dir: Option<&std::path::Path>,
+) -> Result<CreateContainerRequest, String> {
+    let (request, host_paths) = service_container_request(compose, service_name, service, number, base_dir)?;
+    for path in host_paths {
+        std::fs::create_dir_all(&path)
+            .map_err(|e| format!("Failed to create host path '{}': {}", path, e))?;
+    }
+    Ok(request)
+}
+
+// Translate a replica of a compose service into a container request, along with the bind sources that must be created first
+fn service_container_request(
+    compose: &ComposeFile,
+    service_name: &str,
+    service: &ComposeService,
+    number: u32,
+    base_dir: Option<&std::path::Path>,
+) -> Result<(CreateContainerRequest, Vec<String>), String> {
+    let image = service.image_name(compose.project_name(), service_name).ok_or("No image specified")?;
+    
+    // Variables from env_file come first so that `environment` entries override them
+    let mut env: Vec<String> = service.read_env_files(base_dir)?
+        .into_iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    for (key, value) in service.environment_vars() {
+        env.retain(|existing| existing.split('=').next() != Some(key.as_str()));
+        env.push(format!("{}={}", key, value));
+    }
+    
+    let mut port_bindings = Vec::new();
+    for port in service.ports.iter().flatten() {
+        for publish in compose::parse_port(port)? {
+            port_bindings.push(PortBindingRequest {
+                container_port: publish.key(),
+                host_ip: publish.host_ip,
+                host_port: publish.host_port,
+            });
+        }
+    }
+    
+    // `network_mode` if set, else the service's networks; the container is created on the first one
+    let mut endpoints = match compose.network_mode(service)? {
+        Some(mode) => vec![(mode, compose::ServiceNetworkConfig::default())],
+        None => compose.service_endpoints(service_name, service),
+    };
+    let (network, primary) = endpoints.remove(0);
+    let extra_networks: Vec<NetworkEndpointRequest> = endpoints.into_iter()
+        .map(|(name, config)| NetworkEndpointRequest {
+            name,
+            aliases: config.aliases,
+            ipv4_address: config.ipv4_address,
+            ipv6_address: config.ipv6_address,
+        })
+        .collect();
+    
+    let host_ports: Vec<String> = port_bindings.iter().filter_map(|b| b.host_port.clone()).collect();
+    let addresses: Vec<String> = [&primary.ipv4_address, &primary.ipv6_address].into_iter()
+        .chain(extra_networks.iter().flat_map(|endpoint| [&endpoint.ipv4_address, &endpoint.ipv6_address]))
+        .flatten()
+        .cloned()
+        .collect();
+    compose::check_scalable(service_name, service.container_name.as_deref(), &host_ports, &addresses, service.replicas())?;
+    
+    let command = service.command_args()?;
+    let entrypoint = service.entrypoint_args()?;
+    
+    // Parse resource limits
+    let memory_limit = service.mem_limit.as_ref().and_then(|m| compose::parse_memory_limit(m));
+    let cpu_quota = service.cpus.map(|cpus| (cpus * 100000.0) as i64);
+    
+    
+    let mut mounts = Vec::new();
+    let mut host_paths = Vec::new();
+    for volume in service.volumes.iter().flatten() {
+        let mount = compose::parse_volume(volume)?;
+        let source = match (mount.kind, &mount.source) {
+            (compose::MountKind::Bind, Some(source)) => {
+                let path = compose::resolve_host_path(source, base_dir)?;
+                if mount.create_host_path {
+                    host_paths.push(path.clone());
+                }
+                Some(path)
+            }
+            // Point named volumes at the volumes declared at the top level
+            (compose::MountKind::Volume, Some(source)) => Some(compose.volume_name(source)),
+            (_, source) => source.clone(),
+        };
+        mounts.push(MountRequest {
+            mount_type: mount.kind.as_str().to_string(),
+            source,
+            target: mount.target,
+            read_only: Some(mount.read_only),
+            bind_propagation: mount.propagation,
+            selinux_label: mount.selinux,
+            volume_nocopy: Some(mount.nocopy),
+            volume_subpath: mount.subpath,
+            tmpfs_size: mount.tmpfs_size,
+            tmpfs_mode: mount.tmpfs_mode,
+        });
+    }
+    
+    // File-based secrets and configs are bind-mounted read-only
+    let file_references = [
+        (compose::FileObjectKind::Secret, &service.secrets, &compose.secrets),
+        (compose::FileObjectKind::Config, &service.configs, &compose.configs),
+    ];
+    for (kind, references, definitions) in file_references {
+        let references = references.as_deref().unwrap_or_default();
+        for file in compose::resolve_file_mounts(kind, references, definitions, base_dir)? {
+            mounts.push(MountRequest {
+                mount_type: "bind".to_string(),
+                source: Some(file.source),
+                target: file.target,
+                read_only: Some(true),
+                bind_propagation: None,
+                selinux_label: None,
+                volume_nocopy: None,
+                volume_subpath: None,
+                tmpfs_size: None,
+                tmpfs_mode: None,
+            });
+        }
     }
     
-    // Build container config
-    let config = Config {
-        image: Some(request.image.clone()),
-        env: request.env.clone(),
-        exposed_ports: if exposed_ports.is_empty() { None } else { Some(exposed_ports) },
-        host_config: Some(host_config),
-        cmd: request.command.clone(),
-        ..Default::default()
+    let healthcheck = match &service.healthcheck {
+        Some(healthcheck) => {
+            let duration = |value: &Option<String>| -> Result<Option<i64>, String> {
+                match value {
+                    Some(value) => compose::parse_duration(value)
+                        .map(Some)
+                        .ok_or_else(|| format!("Invalid duration '{}' in healthcheck", value)),
+                    None => Ok(None),
+                }
+            };
+            healthcheck.test_command().map(|test| -> Result<HealthcheckRequest, String> {
+                Ok(HealthcheckRequest {
+                    test,
+                    interval: duration(&healthcheck.interval)?,
+                    timeout: duration(&healthcheck.timeout)?,
+                    retries: healthcheck.retries,
+                    start_period: duration(&healthcheck.start_period)?,
+                    start_interval: duration(&healthcheck.start_interval)?,
+                })
+            }).transpose()?
+        }
+        None => None,
     };
     
-    let options = request.name.as_ref().map(|name| CreateContainerOptions {
-        name: name.as_str(),
-        ..Default::default()
-    });
+    // tmpfs entries are "path" or "path:options"
+    let tmpfs: HashMap<String, String> = service.tmpfs.iter()
+        .flat_map(|t| t.to_vec())
+        .map(|entry| match entry.split_once(':') {
+            Some((path, options)) => (path.to_string(), options.to_string()),
+            None => (entry, String::new()),
+        })
+        .collect();
     
-    let container = docker.create_container(options, config)
-        .await
-        .map_err(|e| format!("Failed to create container: {}", e))?;
+    let ulimits: Vec<UlimitRequest> = service.ulimits.iter()
+        .flatten()
+        .map(|(name, ulimit)| {
+            let (soft, hard) = ulimit.limits();
+            UlimitRequest { name: name.clone(), soft, hard }
+        })
+        .collect();
     
-    Ok(container.id)
-}
-
-#[tauri::command]
-async fn create_and_start_container(state: State<'_, DockerState>, request: CreateContainerRequest) -> Result<String, String> {
-    // First, create the container
-    let container_id = create_container(state.clone(), request).await?;
+    let shm_size = match &service.shm_size {
+        Some(size) => Some(compose::parse_memory_limit(size).ok_or_else(|| format!("Invalid shm_size '{}'", size))?),
+        None => None,
+    };
+    let stop_timeout = match &service.stop_grace_period {
+        Some(period) => Some(compose::parse_duration_secs(period)
+            .ok_or_else(|| format!("Invalid stop_grace_period '{}'", period))?),
+        None => None,
+    };
+    let extra_hosts = service.extra_hosts_list();
     
-    // Then, start it immediately
-    let docker = state.docker.lock().await;
-    docker.start_container::<String>(&container_id, None)
-        .await
-        .map_err(|e| format!("Failed to start container after creation: {}", e))?;
+    let project = compose.project_name();
+    // User labels first so the compose labels that tie the container to its project can't be overridden
+    let mut labels = service.labels.as_ref().map(|l| l.to_map()).unwrap_or_default();
+    labels.insert(compose::PROJECT_LABEL.to_string(), project.to_string());
+    labels.insert(compose::SERVICE_LABEL.to_string(), service_name.to_string());
+    labels.insert(compose::CONTAINER_NUMBER_LABEL.to_string(), number.to_string());
+    labels.insert(compose::ONEOFF_LABEL.to_string(), "False".to_string());
+    labels.insert(compose::DEPENDS_ON_LABEL.to_string(), compose::depends_on_label(service));
     
-    Ok(container_id)
+    let mut request = CreateContainerRequest {
+        name: service.container_name.clone().or(Some(compose::replica_name(project, service_name, number))),
+        image: image.clone(),
+        env: if env.is_empty() { None } else { Some(env) },
+        ports: None,
+        volumes: None,
+        network: Some(network),
+        restart_policy: service.restart.clone(),
+        command,
+        memory_limit,
+        cpu_quota,
+        labels: Some(labels),
+        network_aliases: primary.aliases,
+        ipv4_address: primary.ipv4_address,
+        ipv6_address: primary.ipv6_address,
+        extra_networks: if extra_networks.is_empty() { None } else { Some(extra_networks) },
+        port_bindings: if port_bindings.is_empty() { None } else { Some(port_bindings) },
+        mounts: if mounts.is_empty() { None } else { Some(mounts) },
+        healthcheck,
+        user: service.user.clone(),
+        working_dir: service.working_dir.clone(),
+        entrypoint,
+        hostname: service.hostname.clone(),
+        extra_hosts: if extra_hosts.is_empty() { None } else { Some(extra_hosts) },
+        dns: service.dns.as_ref().map(|d| d.to_vec()),
+        cap_add: service.cap_add.clone(),
+        cap_drop: service.cap_drop.clone(),
+        privileged: service.privileged,
+        devices: service.devices.clone(),
+        tmpfs: if tmpfs.is_empty() { None } else { Some(tmpfs) },
+        ulimits: if ulimits.is_empty() { None } else { Some(ulimits) },
+        shm_size,
+        stop_signal: service.stop_signal.clone(),
+        stop_timeout,
+        init: service.init,
+        read_only: service.read_only,
+        log_driver: service.logging.as_ref().and_then(|l| l.driver.clone()),
+        log_options: service.logging.as_ref().and_then(|l| l.options.as_ref()).map(|o| o.to_map()),
+    };
+    
+    // The hash leaves out the name: a renamed container is caught on its own, and recreating
+    // creates the replacement under a temporary name
+    let config = serde_json::to_value(&request)
+        .map_err(|e| format!("Failed to hash the service configuration: {}", e))?;
+    request.labels.get_or_insert_with(HashMap::new)
+        .insert(compose::CONFIG_HASH_LABEL.to_string(), compose::config_hash(&config));
+    
+    Ok((request, host_paths))
 }
 
-// Docker Compose structures
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct ComposeService {
-    image: Option<String>,
-    container_name: Option<String>,
-    environment: Option<Vec<String>>,
-    ports: Option<Vec<String>>,
-    volumes: Option<Vec<String>>,
-    networks: Option<Vec<String>>,
-    restart: Option<String>,
-    command: Option<serde_yaml::Value>,
-    mem_limit: Option<String>,
-    cpus: Option<f64>,
+struct ComposeProjectInfo {
+    name: String,
+    status: String,
+    services: Vec<String>,
+    containers: Vec<ContainerInfo>,
+    config_files: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct ComposeFile {
-    version: Option<String>,
-    services: HashMap<String, ComposeService>,
+struct ComposeScaleResult {
+    containers_created: Vec<String>,
+    containers_removed: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct ComposeDeployResult {
-    service_name: String,
-    container_id: Option<String>,
-    success: bool,
-    error: Option<String>,
+struct ComposeDownResult {
+    containers_removed: Vec<String>,
+    networks_removed: Vec<String>,
+    volumes_removed: Vec<String>,
 }
 
-// Parse memory limit string (e.g., "512m", "1g") to bytes
-fn parse_memory_limit(limit_str: &str) -> Option<i64> {
-    let limit_str = limit_str.trim().to_lowercase();
+fn compose_project_filter(project: &str) -> HashMap<String, Vec<String>> {
+    let mut filters = HashMap::new();
+    filters.insert("label".to_string(), vec![format!("{}={}", compose::PROJECT_LABEL, project)]);
+    filters
+}
+
+// All containers of one service of a compose project, running or not
+async fn list_service_containers(docker: &Docker, project: &str, service_name: &str) -> Result<Vec<ContainerSummary>, String> {
+    let mut filters = compose_project_filter(project);
+    filters.entry("label".to_string()).or_default()
+        .push(format!("{}={}", compose::SERVICE_LABEL, service_name));
+    docker.list_containers(Some(ListContainersOptions {
+        all: true,
+        filters,
+        ..Default::default()
+    }))
+        .await
+        .map_err(|e| format!("Failed to list containers: {}", e))
+}
+
+// The replica number compose gave a container; 0 when the label is missing
+fn container_number(container: &ContainerSummary) -> u32 {
+    container.labels.as_ref()
+        .and_then(|l| l.get(compose::CONTAINER_NUMBER_LABEL))
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(0)
+}
+
+// List the containers of a compose project with dependencies ahead of their dependents
+async fn list_project_containers(docker: &Docker, project: &str) -> Result<Vec<ContainerSummary>, String> {
+    let mut containers = docker.list_containers(Some(ListContainersOptions {
+        all: true,
+        filters: compose_project_filter(project),
+        ..Default::default()
+    }))
+        .await
+        .map_err(|e| format!("Failed to list containers: {}", e))?;
     
-    if let Some(stripped) = limit_str.strip_suffix('g') {
-        stripped.parse::<i64>().ok().map(|v| v * 1024 * 1024 * 1024)
-    } else if let Some(stripped) = limit_str.strip_suffix('m') {
-        stripped.parse::<i64>().ok().map(|v| v * 1024 * 1024)
-    } else if let Some(stripped) = limit_str.strip_suffix('k') {
-        stripped.parse::<i64>().ok().map(|v| v * 1024)
-    } else {
-        limit_str.parse::<i64>().ok()
+    let service_of = |container: &ContainerSummary| {
+        container.labels.as_ref()
+            .and_then(|l| l.get(compose::SERVICE_LABEL))
+            .cloned()
+            .unwrap_or_default()
+    };
+    
+    let mut graph: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for container in &containers {
+        let dependencies = container.labels.as_ref()
+            .and_then(|l| l.get(compose::DEPENDS_ON_LABEL))
+            .map(|label| compose::parse_depends_on_label(label))
+            .unwrap_or_default();
+        graph.entry(service_of(container)).or_default().extend(dependencies);
+    }
+    
+    // A cycle can only come from hand-edited labels; fall back to name order then
+    let order = compose::topological_order(&graph)
+        .unwrap_or_else(|_| graph.keys().cloned().collect());
+    let position = |container: &ContainerSummary| {
+        let service = service_of(container);
+        order.iter().position(|s| *s == service).unwrap_or(usize::MAX)
+    };
+    
+    containers.sort_by(|a, b| position(a).cmp(&position(b)).then_with(|| a.names.cmp(&b.names)));
+    Ok(containers)
+}
+
+async fn require_project_containers(docker: &Docker, project: &str) -> Result<Vec<ContainerSummary>, String> {
+    let containers = list_project_containers(docker, project).await?;
+    if containers.is_empty() {
+        return Err(format!("No containers found for compose project '{}'", project));
     }
+    Ok(containers)
 }
 
 #[tauri::command]
-async fn deploy_compose(state: State<'_, DockerState>, yaml_content: String) -> Result<Vec<ComposeDeployResult>, String> {
-    let compose: ComposeFile = serde_yaml::from_str(&yaml_content)
-        .map_err(|e| format!("Failed to parse compose file: {}", e))?;
+async fn list_compose_projects(state: State<'_, DockerState>) -> Result<Vec<ComposeProjectInfo>, String> {
+    let docker = state.docker.lock().await;
     
-    let mut results = Vec::new();
+    let mut filters = HashMap::new();
+    filters.insert("label".to_string(), vec![compose::PROJECT_LABEL.to_string()]);
+    
+    let containers = docker.list_containers(Some(ListContainersOptions {
+        all: true,
+        filters,
+        ..Default::default()
+    }))
+        .await
+        .map_err(|e| format!("Failed to list containers: {}", e))?;
     
-    for (service_name, service) in compose.services {
-        let result = deploy_service(&state, &service_name, &service).await;
-        results.push(result);
+    let mut projects: BTreeMap<String, ComposeProjectInfo> = BTreeMap::new();
+    for container in containers {
+        let labels = container.labels.clone().unwrap_or_default();
+        let name = labels.get(compose::PROJECT_LABEL).cloned().unwrap_or_default();
+        let project = projects.entry(name.clone()).or_insert_with(|| ComposeProjectInfo {
+            name,
+            status: String::new(),
+            services: Vec::new(),
+            containers: Vec::new(),
+            config_files: None,
+        });
+        
+        if let Some(service) = labels.get(compose::SERVICE_LABEL) {
+            if !project.services.contains(service) {
+                project.services.push(service.clone());
+            }
+        }
+        if project.config_files.is_none() {
+            project.config_files = labels.get(compose::CONFIG_FILES_LABEL).cloned();
+        }
+        project.containers.push(convert_container_summary(container));
     }
     
-    Ok(results)
+    // Summarise container states the way `docker compose ls` does, e.g. "running(2), exited(1)"
+    for project in projects.values_mut() {
+        project.services.sort();
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for container in &project.containers {
+            *counts.entry(container.state.as_str()).or_default() += 1;
+        }
+        project.status = counts.iter()
+            .map(|(state, count)| format!("{}({})", state, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+    }
+    
+    Ok(projects.into_values().collect())
 }
 
-async fn deploy_service(
-    state: &State<'_, DockerState>,
-    service_name: &str,
-    service: &ComposeService,
-) -> ComposeDeployResult {
-    let image = match &service.image {
-        Some(img) => img.clone(),
-        None => {
-            return ComposeDeployResult {
-                service_name: service_name.to_string(),
-                container_id: None,
-                success: false,
-                error: Some("No image specified".to_string()),
-            };
+#[tauri::command]
+async fn compose_start(state: State<'_, DockerState>, project: String) -> Result<(), String> {
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    for container in require_project_containers(&docker, &project).await? {
+        if container.state.as_deref() == Some("running") {
+            continue;
+        }
+        let id = container.id.unwrap_or_default();
+        docker.start_container(&id, None::<StartContainerOptions<String>>)
+            .await
+            .map_err(|e| format!("Failed to start container: {}", e))?;
+    }
+    
+    Ok(())
+}
+
+#[tauri::command]
+async fn compose_stop(state: State<'_, DockerState>, project: String) -> Result<(), String> {
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    // Stop dependents before the services they rely on
+    for container in require_project_containers(&docker, &project).await?.into_iter().rev() {
+        if !matches!(container.state.as_deref(), Some("running") | Some("paused") | Some("restarting")) {
+            continue;
         }
+        let id = container.id.unwrap_or_default();
+        docker.stop_container(&id, None)
+            .await
+            .map_err(|e| format!("Failed to stop container: {}", e))?;
+    }
+    
+    Ok(())
+}
+
+#[tauri::command]
+async fn compose_restart(state: State<'_, DockerState>, project: String) -> Result<(), String> {
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
     };
     
-    // Parse ports from compose format ("8080:80") to HashMap
-    let mut ports_map = HashMap::new();
-    if let Some(ports) = &service.ports {
-        for port_spec in ports {
-            let parts: Vec<&str> = port_spec.split(':').collect();
-            if parts.len() == 2 {
-                let host_port = parts[0];
-                let container_port = parts[1];
-                let key = if container_port.contains('/') {
-                    container_port.to_string()
-                } else {
-                    format!("{}/tcp", container_port)
-                };
-                ports_map.insert(key, host_port.to_string());
+    for container in require_project_containers(&docker, &project).await? {
+        let id = container.id.unwrap_or_default();
+        docker.restart_container(&id, None)
+            .await
+            .map_err(|e| format!("Failed to restart container: {}", e))?;
+    }
+    
+    Ok(())
+}
+
+#[tauri::command]
+async fn compose_scale(
+    state: State<'_, DockerState>,
+    project: String,
+    service: String,
+    count: u32,
+) -> Result<ComposeScaleResult, String> {
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
+    
+    let mut containers = list_service_containers(&docker, &project, &service).await?;
+    if containers.is_empty() {
+        return Err(format!("No containers found for service '{}' in compose project '{}'", service, project));
+    }
+    containers.sort_by_key(container_number);
+    
+    let numbers: Vec<u32> = containers.iter().map(container_number).collect();
+    let (to_add, to_remove) = compose::scale_numbers(&numbers, count);
+    let mut result = ComposeScaleResult {
+        containers_created: Vec::new(),
+        containers_removed: Vec::new(),
+    };
+    
+    if !to_add.is_empty() {
+        // New replicas copy the configuration of the lowest-numbered one
+        let template_id = containers[0].id.clone().unwrap_or_default();
+        let template = docker.inspect_container(&template_id, None)
+            .await
+            .map_err(|e| format!("Failed to inspect container: {}", e))?;
+        let template_name = template.name.as_deref().unwrap_or_default().trim_start_matches('/').to_string();
+        let template_number = container_number(&containers[0]);
+        let host_config = template.host_config.clone().unwrap_or_default();
+        
+        let fixed_name = Some(template_name.as_str())
+            .filter(|name| *name != compose::replica_name(&project, &service, template_number));
+        let host_ports: Vec<String> = host_config.port_bindings.iter()
+            .flat_map(|bindings| bindings.values())
+            .flatten()
+            .flatten()
+            .filter_map(|binding| binding.host_port.clone())
+            .collect();
+        let endpoints = template.network_settings.as_ref()
+            .and_then(|settings| settings.networks.clone())
+            .unwrap_or_default();
+        // Only configured addresses count; the ones the daemon assigned are reported elsewhere
+        let addresses: Vec<String> = endpoints.values()
+            .filter_map(|endpoint| endpoint.ipam_config.as_ref())
+            .flat_map(|ipam| [ipam.ipv4_address.clone(), ipam.ipv6_address.clone()])
+            .flatten()
+            .filter(|address| !address.is_empty())
+            .collect();
+        compose::check_scalable(&service, fixed_name, &host_ports, &addresses, count)?;
+        
+        // The container is created on its network mode's network and connected to the others
+        let network = host_config.network_mode.clone().unwrap_or_default();
+        let copy_endpoint = |endpoint: &EndpointSettings| EndpointSettings {
+            // The daemon adds the short container ID as an alias of its own
+            aliases: endpoint.aliases.clone()
+                .map(|aliases| aliases.into_iter().filter(|alias| !template_id.starts_with(alias.as_str())).collect()),
+            ..Default::default()
+        };
+        
+        for number in to_add {
+            let name = compose::replica_name(&project, &service, number);
+            let mut config: Config<String> = template.config.clone().unwrap_or_default().into();
+            // Without an explicit hostname the daemon uses the container ID, which must not be copied
+            if config.hostname.as_deref().is_some_and(|hostname| template_id.starts_with(hostname)) {
+                config.hostname = None;
+            }
+            config.labels.get_or_insert_with(HashMap::new)
+                .insert(compose::CONTAINER_NUMBER_LABEL.to_string(), number.to_string());
+            config.host_config = Some(host_config.clone());
+            if let Some(endpoint) = endpoints.get(&network) {
+                let endpoints_config = HashMap::from([(network.clone(), copy_endpoint(endpoint))]);
+                config.networking_config = Some(NetworkingConfig { endpoints_config });
             }
+            
+            let created = docker.create_container(Some(CreateContainerOptions {
+                name: name.as_str(),
+                platform: None,
+            }), config)
+                .await
+                .map_err(|e| format!("Failed to create container: {}", e))?;
+            let other_networks = endpoints.iter()
+                .filter(|(other, _)| **other != network)
+                .map(|(other, endpoint)| (other.clone(), copy_endpoint(endpoint)))
+                .collect();
+            connect_networks(&docker, &created.id, other_networks).await?;
+            docker.start_container(&created.id, None::<StartContainerOptions<String>>)
+                .await
+                .map_err(|e| format!("Failed to start container: {}", e))?;
+            result.containers_created.push(name);
         }
     }
     
-    // Parse command
-    let command = service.command.as_ref().and_then(|cmd| {
-        match cmd {
-            serde_yaml::Value::String(s) => Some(vec![s.clone()]),
-            serde_yaml::Value::Sequence(seq) => {
-                Some(seq.iter().filter_map(|v| {
-                    if let serde_yaml::Value::String(s) = v {
-                        Some(s.clone())
-                    } else {
-                        None
-                    }
-                }).collect())
-            },
-            _ => None,
+    for container in containers.into_iter().rev() {
+        if !to_remove.contains(&container_number(&container)) {
+            continue;
         }
-    });
-    
-    // Parse resource limits
-    let memory_limit = service.mem_limit.as_ref().and_then(|m| parse_memory_limit(m));
-    let cpu_quota = service.cpus.map(|cpus| (cpus * 100000.0) as i64);
+        let id = container.id.clone().unwrap_or_default();
+        docker.remove_container(&id, Some(RemoveContainerOptions {
+            force: true,
+            ..Default::default()
+        }))
+            .await
+            .map_err(|e| format!("Failed to remove container: {}", e))?;
+        result.containers_removed.push(convert_container_summary(container).name);
+    }
     
-    // Get first network if specified
-    let network = service.networks.as_ref()
-        .and_then(|nets| nets.first())
-        .map(|n| n.clone());
+    Ok(result)
+}
+
+#[tauri::command]
+async fn compose_down(
+    state: State<'_, DockerState>,
+    project: String,
+    remove_volumes: bool,
+) -> Result<ComposeDownResult, String> {
+    let docker = {
+        let guard = state.docker.lock().await;
+        guard.clone()
+    };
     
-    let request = CreateContainerRequest {
-        name: service.container_name.clone().or(Some(service_name.to_string())),
-        image: image.clone(),
-        env: service.environment.clone(),
-        ports: if ports_map.is_empty() { None } else { Some(ports_map) },
-        volumes: service.volumes.clone(),
-        network,
-        restart_policy: service.restart.clone(),
-        command,
-        memory_limit,
-        cpu_quota,
+    let mut result = ComposeDownResult {
+        containers_removed: Vec::new(),
+        networks_removed: Vec::new(),
+        volumes_removed: Vec::new(),
     };
     
-    match create_container(state.clone(), request).await {
-        Ok(container_id) => ComposeDeployResult {
-            service_name: service_name.to_string(),
-            container_id: Some(container_id),
-            success: true,
-            error: None,
-        },
-        Err(e) => ComposeDeployResult {
-            service_name: service_name.to_string(),
-            container_id: None,
-            success: false,
-            error: Some(e),
-        },
+    for container in list_project_containers(&docker, &project).await?.into_iter().rev() {
+        let id = container.id.clone().unwrap_or_default();
+        docker.remove_container(&id, Some(RemoveContainerOptions {
+            force: true,
+            v: remove_volumes,
+            ..Default::default()
+        }))
+            .await
+            .map_err(|e| format!("Failed to remove container: {}", e))?;
+        result.containers_removed.push(convert_container_summary(container).name);
+    }
+    
+    let networks = docker.list_networks(Some(ListNetworksOptions {
+        filters: compose_project_filter(&project),
+    }))
+        .await
+        .map_err(|e| format!("Failed to list networks: {}", e))?;
+    
+    for network in networks {
+        let name = network.name.unwrap_or_default();
+        docker.remove_network(&name)
+            .await
+            .map_err(|e| format!("Failed to remove network: {}", e))?;
+        result.networks_removed.push(name);
+    }
+    
+    if remove_volumes {
+        let volumes = docker.list_volumes(Some(ListVolumesOptions {
+            filters: compose_project_filter(&project),
+        }))
+            .await
+            .map_err(|e| format!("Failed to list volumes: {}", e))?;
+        
+        for volume in volumes.volumes.unwrap_or_default() {
+            docker.remove_volume(&volume.name, None)
+                .await
+                .map_err(|e| format!("Failed to remove volume: {}", e))?;
+            result.volumes_removed.push(volume.name);
+        }
     }
+    
+    Ok(result)
 }
 
 #[tauri::command]
@@ -1708,6 +4467,119 @@ fn get_podman_socket_paths() -> Vec<String> {
     paths
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EventsStatus {
+    connected: bool,
+    error: Option<String>,
+}
+
+const DOCKER_EVENT: &str = "docker-event";
+const DOCKER_EVENTS_STATUS: &str = "docker-events-status";
+const EVENTS_RECONNECT_MIN_MS: u64 = 1000;
+const EVENTS_RECONNECT_MAX_MS: u64 = 30_000;
+// Event history is rotated at 5 MB, keeping 4 older files
+const EVENT_STORE_MAX_BYTES: u64 = 5 * 1024 * 1024;
+const EVENT_STORE_KEEP: usize = 4;
+
+// Keeps one events subscription for the lifetime of the app, recording each event and emitting it to the frontend.
+// When the daemon goes away it resubscribes with backoff, resuming from the last event it saw.
+async fn watch_docker_events(
+    app: tauri::AppHandle,
+    docker: Arc<Mutex<Docker>>,
+    store: Arc<events::EventStore>,
+    notifier: Arc<Mutex<notifications::Notifier>>,
+) {
+    use futures_util::stream::StreamExt;
+    
+    let mut last_time_nano: Option<i64> = None;
+    let mut delay = EVENTS_RECONNECT_MIN_MS;
+    loop {
+        // Looked up on every attempt, so a reconnect picks up a switched runtime
+        let docker = {
+            let guard = docker.lock().await;
+            guard.clone()
+        };
+        
+        // The stream sends nothing until an event happens, so ping to know the daemon is there
+        let error = match docker.ping().await {
+            Err(e) => Some(format!("Failed to connect to Docker: {}", e)),
+            Ok(_) => {
+                let _ = app.emit(DOCKER_EVENTS_STATUS, EventsStatus { connected: true, error: None });
+                delay = EVENTS_RECONNECT_MIN_MS;
+                
+                // `since` only has second precision, so events of that second that were already sent are skipped below
+                let mut events_stream = docker.events(Some(EventsOptions::<String> {
+                    since: last_time_nano.map(|nanos| (nanos / 1_000_000_000).to_string()),
+                    until: None,
+                    filters: HashMap::from([(
+                        "type".to_string(),
+                        events::EVENT_TYPES.iter().map(|t| t.to_string()).collect(),
+                    )]),
+                }));
+                loop {
+                    match events_stream.next().await {
+                        Some(Ok(message)) => {
+                            let Some(event) = events::DockerEvent::from_message(message) else { continue };
+                            if Some(event.time_nano) <= last_time_nano {
+                                continue;
+                            }
+                            last_time_nano = Some(event.time_nano);
+                            // The frontend refreshes on every event it gets, and the history would fill with them
+                            if event.is_noise() {
+                                continue;
+                            }
+                            // History is best effort; a full disk shouldn't stop live events
+                            let _ = store.append(&event);
+                            if let Some(notification) = notifier.lock().await.check(&event) {
+                                let _ = app.notification()
+                                    .builder()
+                                    .title(notification.title)
+                                    .body(notification.body)
+                                    .show();
+                            }
+                            let _ = app.emit(DOCKER_EVENT, event);
+                        }
+                        Some(Err(e)) => break Some(format!("Docker events stream failed: {}", e)),
+                        None => break None,
+                    }
+                }
+            }
+        };
+        
+        let _ = app.emit(DOCKER_EVENTS_STATUS, EventsStatus { connected: false, error });
+        tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+        delay = (delay * 2).min(EVENTS_RECONNECT_MAX_MS);
+    }
+}
+
+#[tauri::command]
+async fn query_events(
+    store: State<'_, Arc<events::EventStore>>,
+    filter: Option<events::EventFilter>,
+) -> Result<Vec<events::DockerEvent>, String> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    store.query(&filter.unwrap_or_default(), now)
+}
+
+#[tauri::command]
+async fn get_notification_settings(state: State<'_, NotificationState>) -> Result<notifications::NotificationSettings, String> {
+    Ok(state.notifier.lock().await.settings.clone())
+}
+
+#[tauri::command]
+async fn set_notification_settings(
+    state: State<'_, NotificationState>,
+    settings: notifications::NotificationSettings,
+) -> Result<(), String> {
+    notifications::save_settings(&state.settings_path, &settings)?;
+    state.notifier.lock().await.settings = settings;
+    Ok(())
+}
+
 fn main() {
     let (docker, runtime) = match Docker::connect_with_local_defaults() {
         Ok(d) => (d, "docker".to_string()),
@@ -1723,27 +4595,77 @@ fn main() {
         }
     };
 
+    let docker = Arc::new(Mutex::new(docker));
+    let events_docker = docker.clone();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_notification::init())
         .manage(DockerState {
-            docker: Arc::new(Mutex::new(docker)),
+            docker,
             runtime: Arc::new(Mutex::new(runtime)),
         })
         .manage(TerminalState {
             sessions: Arc::new(Mutex::new(HashMap::new())),
         })
+        .manage(LogStreamState {
+            streams: Arc::new(Mutex::new(HashMap::new())),
+            next_id: std::sync::atomic::AtomicU64::new(1),
+        })
+        .manage(TopStreamState {
+            streams: Arc::new(Mutex::new(HashMap::new())),
+            next_id: std::sync::atomic::AtomicU64::new(1),
+        })
+        .setup(move |app| {
+            let store = Arc::new(events::EventStore::new(
+                app.path().app_data_dir()?.join("events"),
+                EVENT_STORE_MAX_BYTES,
+                EVENT_STORE_KEEP,
+            ));
+            app.manage(store.clone());
+            
+            let settings_path = app.path().app_data_dir()?.join("notifications.json");
+            // Broken settings shouldn't keep the app from starting; saving new ones replaces them
+            let settings = notifications::load_settings(&settings_path).unwrap_or_default();
+            let notifier = Arc::new(Mutex::new(notifications::Notifier::new(settings)));
+            app.manage(NotificationState {
+                notifier: notifier.clone(),
+                settings_path,
+            });
+            
+            tauri::async_runtime::spawn(watch_docker_events(app.handle().clone(), events_docker, store, notifier));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             list_containers,
             start_container,
             stop_container,
             restart_container,
+            kill_container,
+            wait_container,
             pause_container,
             unpause_container,
             remove_container,
             get_container_logs,
+            export_container_logs,
+            stream_container_logs,
+            stream_aggregated_logs,
+            stop_log_stream,
+            query_events,
+            get_notification_settings,
+            set_notification_settings,
             get_container_details,
+            copy_to_container,
+            copy_from_container,
+            list_container_dir,
+            read_container_file,
+            container_changes,
+            container_top,
+            stream_container_top,
+            stop_top_stream,
+            kill_container_process,
             check_docker_connection,
             get_container_runtime,
             list_images,
@@ -1760,6 +4682,15 @@ fn main() {
             create_container,
             create_and_start_container,
             deploy_compose,
+            plan_compose,
+            render_compose_config,
+            generate_compose,
+            list_compose_projects,
+            compose_start,
+            compose_stop,
+            compose_restart,
+            compose_scale,
+            compose_down,
             check_image_exists,
             search_docker_hub,
             system_prune,
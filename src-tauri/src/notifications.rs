@@ -0,0 +1,222 @@
+use crate::events::{DockerEvent, EventKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Which problems raise a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationTriggers {
+    /// The container exited with a non-zero code
+    pub crash: bool,
+    /// The container was OOM-killed
+    pub oom: bool,
+    /// The healthcheck reported unhealthy
+    pub unhealthy: bool,
+}
+
+impl Default for NotificationTriggers {
+    fn default() -> Self {
+        NotificationTriggers { crash: true, oom: true, unhealthy: true }
+    }
+}
+
+/// Triggers for the containers matching `container` and `label`; unset selectors match any container
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRule {
+    /// Container name
+    pub container: Option<String>,
+    /// `key` or `key=value`
+    pub label: Option<String>,
+    pub triggers: NotificationTriggers,
+}
+
+impl NotificationRule {
+    fn matches(&self, event: &DockerEvent) -> bool {
+        let container_matches = self.container.as_deref().is_none_or(|name| event.name.as_deref() == Some(name));
+        // Docker sends a container's labels along with its other event attributes
+        let label_matches = self.label.as_deref().is_none_or(|label| match label.split_once('=') {
+            Some((key, value)) => event.attributes.get(key).map(String::as_str) == Some(value),
+            None => event.attributes.contains_key(label),
+        });
+        container_matches && label_matches
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// Least time between two notifications about the same container
+    pub rate_limit_secs: u64,
+    /// For containers no rule matches
+    pub defaults: NotificationTriggers,
+    /// The first matching rule decides
+    pub rules: Vec<NotificationRule>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings {
+            enabled: true,
+            rate_limit_secs: 300,
+            defaults: NotificationTriggers::default(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Reads the settings, falling back to the defaults when the file doesn't exist yet
+pub fn load_settings(path: &Path) -> Result<NotificationSettings, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| format!("Failed to parse notification settings: {}", e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(NotificationSettings::default()),
+        Err(e) => Err(format!("Failed to read notification settings: {}", e)),
+    }
+}
+
+pub fn save_settings(path: &Path, settings: &NotificationSettings) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(settings).map_err(|e| format!("Failed to serialize notification settings: {}", e))?;
+    std::fs::write(path, content).map_err(|e| format!("Failed to write notification settings: {}", e))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+/// Turns daemon events into notifications according to the settings, rate limited per container
+#[derive(Debug)]
+pub struct Notifier {
+    pub settings: NotificationSettings,
+    // Container id to the time of its last notification, in seconds
+    last_sent: HashMap<String, i64>,
+}
+
+impl Notifier {
+    pub fn new(settings: NotificationSettings) -> Self {
+        Notifier { settings, last_sent: HashMap::new() }
+    }
+
+    pub fn check(&mut self, event: &DockerEvent) -> Option<Notification> {
+        if !self.settings.enabled || event.kind != EventKind::Container {
+            return None;
+        }
+
+        let triggers = self
+            .settings
+            .rules
+            .iter()
+            .find(|rule| rule.matches(event))
+            .map(|rule| rule.triggers)
+            .unwrap_or(self.settings.defaults);
+        let name = event.name.clone().unwrap_or_else(|| event.id.chars().take(12).collect());
+        let (title, body) = match (event.action.as_str(), event.detail.as_deref()) {
+            ("die", _) if triggers.crash => match event.attributes.get("exitCode").map(String::as_str) {
+                Some("0") | None => return None,
+                Some(code) => (format!("{} crashed", name), format!("Exited with code {}", code)),
+            },
+            ("oom", _) if triggers.oom => (format!("{} ran out of memory", name), "Killed by the OOM killer".to_string()),
+            ("health_status", Some("unhealthy")) if triggers.unhealthy => {
+                (format!("{} is unhealthy", name), "Its healthcheck is failing".to_string())
+            }
+            _ => return None,
+        };
+
+        // A crash-looping container notifies once per window; an OOM kill and the exit that follows it count as one
+        let now = event.time_nano / 1_000_000_000;
+        let limit = self.settings.rate_limit_secs as i64;
+        if self.last_sent.get(&event.id).is_some_and(|last| now - last < limit) {
+            return None;
+        }
+        self.last_sent.insert(event.id.clone(), now);
+        Some(Notification { title, body })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn event(action: &str, detail: Option<&str>, name: &str, attributes: &[(&str, &str)], seconds: i64) -> DockerEvent {
+        let mut attributes: BTreeMap<String, String> = attributes.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        attributes.insert("name".to_string(), name.to_string());
+        DockerEvent {
+            kind: EventKind::Container,
+            action: action.to_string(),
+            detail: detail.map(str::to_string),
+            id: format!("{}-id", name),
+            name: Some(name.to_string()),
+            attributes,
+            time_nano: seconds * 1_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_notifier_triggers() {
+        let mut notifier = Notifier::new(NotificationSettings::default());
+        let crash = notifier.check(&event("die", None, "web", &[("exitCode", "1")], 0)).unwrap();
+        assert_eq!(crash.title, "web crashed");
+        assert_eq!(crash.body, "Exited with code 1");
+        assert!(notifier.check(&event("die", None, "db", &[("exitCode", "0")], 0)).is_none());
+        assert!(notifier.check(&event("start", None, "db", &[], 0)).is_none());
+        assert!(notifier.check(&event("health_status", Some("healthy"), "db", &[], 0)).is_none());
+        assert_eq!(notifier.check(&event("health_status", Some("unhealthy"), "db", &[], 0)).unwrap().title, "db is unhealthy");
+        assert_eq!(notifier.check(&event("oom", None, "cache", &[], 0)).unwrap().title, "cache ran out of memory");
+
+        notifier.settings.enabled = false;
+        assert!(notifier.check(&event("oom", None, "queue", &[], 0)).is_none());
+    }
+
+    #[test]
+    fn test_notifier_rate_limit() {
+        let mut notifier = Notifier::new(NotificationSettings { rate_limit_secs: 60, ..Default::default() });
+        assert!(notifier.check(&event("oom", None, "web", &[], 100)).is_some());
+        assert!(notifier.check(&event("die", None, "web", &[("exitCode", "137")], 100)).is_none());
+        assert!(notifier.check(&event("die", None, "web", &[("exitCode", "1")], 159)).is_none());
+        assert!(notifier.check(&event("die", None, "other", &[("exitCode", "1")], 159)).is_some());
+        assert!(notifier.check(&event("die", None, "web", &[("exitCode", "1")], 160)).is_some());
+    }
+
+    #[test]
+    fn test_notifier_rules() {
+        let quiet = NotificationTriggers { crash: false, oom: false, unhealthy: false };
+        let settings = NotificationSettings {
+            defaults: NotificationTriggers { crash: false, ..Default::default() },
+            rules: vec![
+                NotificationRule { container: Some("worker".to_string()), label: None, triggers: quiet },
+                NotificationRule { container: None, label: Some("tier=critical".to_string()), triggers: NotificationTriggers::default() },
+                NotificationRule { container: None, label: Some("ci".to_string()), triggers: quiet },
+            ],
+            ..Default::default()
+        };
+        let mut notifier = Notifier::new(settings);
+        assert!(notifier.check(&event("oom", None, "worker", &[("tier", "critical")], 0)).is_none());
+        assert!(notifier.check(&event("die", None, "api", &[("exitCode", "1"), ("tier", "critical")], 0)).is_some());
+        assert!(notifier.check(&event("oom", None, "runner", &[("ci", "")], 0)).is_none());
+        // The defaults leave crashes out
+        assert!(notifier.check(&event("die", None, "web", &[("exitCode", "1"), ("tier", "web")], 0)).is_none());
+        assert!(notifier.check(&event("oom", None, "web", &[], 0)).is_some());
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let path = std::env::temp_dir().join(format!("dock-notifications-test-{}", std::process::id())).join("notifications.json");
+        assert_eq!(load_settings(&path).unwrap(), NotificationSettings::default());
+
+        let settings = NotificationSettings { rate_limit_secs: 30, ..Default::default() };
+        save_settings(&path, &settings).unwrap();
+        assert_eq!(load_settings(&path).unwrap(), settings);
+        // Keys missing from the file keep their defaults
+        std::fs::write(&path, r#"{"enabled": false}"#).unwrap();
+        assert_eq!(load_settings(&path).unwrap(), NotificationSettings { enabled: false, ..Default::default() });
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
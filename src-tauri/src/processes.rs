@@ -0,0 +1,338 @@
+use bollard::models::ContainerTopResponse;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// `ps` arguments used when none are given; they include %CPU and %MEM
+pub const DEFAULT_PS_ARGS: &str = "aux";
+
+/// `ps` arguments for the process tree used to find a process's PID inside the container
+pub const TREE_PS_ARGS: &str = "-o pid,ppid,etimes,comm";
+
+/// Prints its own PID, then the `stat` line of every process the container can see
+pub const PROC_STAT_SCRIPT: &str = r#"echo $$
+for f in /proc/[0-9]*/stat; do
+  read -r line 2>/dev/null < "$f" && printf '%s\n' "$line"
+done
+exit 0"#;
+
+/// One process from `ps`. The known columns are parsed; `columns` has every column by title.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessRow {
+    /// As the host sees it
+    pub pid: i64,
+    pub ppid: Option<i64>,
+    pub user: Option<String>,
+    pub cpu_percent: Option<f64>,
+    pub mem_percent: Option<f64>,
+    /// Virtual and resident memory in KiB
+    pub vsz: Option<u64>,
+    pub rss: Option<u64>,
+    pub tty: Option<String>,
+    pub stat: Option<String>,
+    pub started: Option<String>,
+    /// Cumulative CPU time
+    pub time: Option<String>,
+    pub command: String,
+    pub columns: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessList {
+    pub titles: Vec<String>,
+    pub processes: Vec<ProcessRow>,
+}
+
+// The first column whose title is one of `titles`
+fn column<'a>(row: &'a BTreeMap<String, String>, titles: &[&str]) -> Option<&'a String> {
+    titles.iter().find_map(|title| row.get(*title))
+}
+
+/// Turns the daemon's `ps` table into rows. Rows without a PID are skipped.
+pub fn parse_top(response: ContainerTopResponse) -> ProcessList {
+    let titles = response.titles.unwrap_or_default();
+    let processes = response
+        .processes
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|values| {
+            let columns: BTreeMap<String, String> = titles.iter().cloned().zip(values).collect();
+            let text = |names: &[&str]| column(&columns, names).cloned();
+            let number = |names: &[&str]| column(&columns, names).and_then(|value| value.parse::<f64>().ok());
+            Some(ProcessRow {
+                pid: column(&columns, &["PID"])?.parse().ok()?,
+                ppid: column(&columns, &["PPID"]).and_then(|value| value.parse().ok()),
+                user: text(&["USER", "UID", "RUSER", "EUSER"]),
+                // `C` is what `ps -ef` calls it
+                cpu_percent: number(&["%CPU", "C"]),
+                mem_percent: number(&["%MEM"]),
+                vsz: number(&["VSZ", "VSIZE"]).map(|value| value as u64),
+                rss: number(&["RSS", "RSZ"]).map(|value| value as u64),
+                tty: text(&["TTY", "TT"]),
+                stat: text(&["STAT", "S"]),
+                started: text(&["START", "STIME", "STARTED"]),
+                time: text(&["TIME"]),
+                command: text(&["COMMAND", "CMD", "ARGS", "COMM"]).unwrap_or_default(),
+                columns,
+            })
+        })
+        .collect();
+    ProcessList { titles, processes }
+}
+
+/// A process as the process tree sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeProcess {
+    pub pid: i64,
+    pub ppid: i64,
+    /// The executable name, cut to 15 characters as the kernel keeps it
+    pub comm: String,
+    /// When the process started, only comparable within one tree; later processes have higher values.
+    /// The host tree has seconds, the container tree clock ticks.
+    pub started: i64,
+}
+
+/// Reads the tree from a `ps` table made with `TREE_PS_ARGS`
+pub fn host_tree(list: &ProcessList) -> Vec<TreeProcess> {
+    list.processes
+        .iter()
+        .filter_map(|row| {
+            let elapsed: i64 = column(&row.columns, &["ELAPSED"])?.parse().ok()?;
+            Some(TreeProcess {
+                pid: row.pid,
+                ppid: row.ppid?,
+                comm: column(&row.columns, &["COMMAND", "COMM", "CMD"])?.chars().take(15).collect(),
+                started: -elapsed,
+            })
+        })
+        .collect()
+}
+
+/// Whether two listings of a tree have the same processes. Start times are left out,
+/// as the host reports them as time elapsed since.
+pub fn same_processes(a: &[TreeProcess], b: &[TreeProcess]) -> bool {
+    let key = |process: &TreeProcess| (process.pid, process.ppid, process.comm.clone());
+    a.iter().map(key).eq(b.iter().map(key))
+}
+
+/// Reads the tree from what `PROC_STAT_SCRIPT` prints, leaving out the script itself
+pub fn container_tree(output: &str) -> Vec<TreeProcess> {
+    let mut lines = output.lines();
+    let script_pid = lines.next().and_then(|line| line.trim().parse::<i64>().ok());
+    lines
+        .filter_map(|line| {
+            // The name sits in parentheses and may itself contain spaces and parentheses
+            let (pid, rest) = line.split_once(" (")?;
+            let (comm, fields) = rest.rsplit_once(") ")?;
+            // The fields after the name start with the third, the state; the start time is the 22nd
+            let fields: Vec<&str> = fields.split_whitespace().collect();
+            Some(TreeProcess {
+                pid: pid.trim().parse().ok()?,
+                ppid: fields.get(1)?.parse().ok()?,
+                comm: comm.to_string(),
+                started: fields.get(19)?.parse().ok()?,
+            })
+        })
+        .filter(|process| Some(process.pid) != script_pid)
+        .collect()
+}
+
+// The processes under `parent` named `comm`, in the order they started. Processes whose parent is
+// outside the container count as siblings.
+fn siblings<'a>(tree: &'a [TreeProcess], parent: Option<i64>, comm: &str) -> Vec<&'a TreeProcess> {
+    let pids: HashSet<i64> = tree.iter().map(|process| process.pid).collect();
+    let mut siblings: Vec<&TreeProcess> = tree
+        .iter()
+        .filter(|process| pids.contains(&process.ppid).then_some(process.ppid) == parent && process.comm == comm)
+        .collect();
+    siblings.sort_by_key(|process| process.started);
+    siblings
+}
+
+// The position of `process` among `siblings`, or an error when a sibling started at the same time
+fn position_of(siblings: &[&TreeProcess], process: &TreeProcess, host_pid: i64) -> Result<usize, String> {
+    if siblings.iter().any(|sibling| sibling.pid != process.pid && sibling.started == process.started) {
+        return Err(format!(
+            "Process {} can't be told apart from another '{}' process that started at the same time; signal it from a terminal in the container",
+            host_pid, process.comm
+        ));
+    }
+    siblings
+        .iter()
+        .position(|sibling| sibling.pid == process.pid)
+        .ok_or_else(|| format!("Process {} is not running in the container", host_pid))
+}
+
+// Where a process sits in its tree: for it and each ancestor, the name and the position among
+// the siblings of that name
+fn tree_position(tree: &[TreeProcess], pid: i64) -> Result<Vec<(String, usize)>, String> {
+    let not_running = || format!("Process {} is not running in the container", pid);
+    let by_pid: HashMap<i64, &TreeProcess> = tree.iter().map(|process| (process.pid, process)).collect();
+    let parent = |process: &TreeProcess| by_pid.contains_key(&process.ppid).then_some(process.ppid);
+
+    let mut position = Vec::new();
+    let mut seen = HashSet::new();
+    let mut current = *by_pid.get(&pid).ok_or_else(not_running)?;
+    loop {
+        if !seen.insert(current.pid) {
+            return Err(not_running());
+        }
+        let index = position_of(&siblings(tree, parent(current), &current.comm), current, pid)?;
+        position.push((current.comm.clone(), index));
+        match parent(current) {
+            Some(ppid) => current = by_pid[&ppid],
+            None => break,
+        }
+    }
+    position.reverse();
+    Ok(position)
+}
+
+// The process at a position from tree_position
+fn find_position(tree: &[TreeProcess], position: &[(String, usize)], host_pid: i64) -> Result<i64, String> {
+    let refresh = || format!("Process {} could not be found inside the container; refresh the process list and try again", host_pid);
+    let mut parent: Option<i64> = None;
+    for (comm, index) in position {
+        let siblings = siblings(tree, parent, comm);
+        let process = *siblings.get(*index).ok_or_else(refresh)?;
+        position_of(&siblings, process, host_pid)?;
+        parent = Some(process.pid);
+    }
+    parent.ok_or_else(refresh)
+}
+
+/// `ps` on the host reports host PIDs, while a process is signalled from inside the container by
+/// the PID it has there. The two trees have the same shape, so the host process is found at the
+/// same place in the container's tree. Siblings of one name are told apart by when they started,
+/// since PIDs wrap around on the host long before they do in the container. Siblings that started
+/// too close together to order, within a second on the host, are refused rather than guessed.
+pub fn container_pid(host: &[TreeProcess], container: &[TreeProcess], host_pid: i64) -> Result<i64, String> {
+    let position = tree_position(host, host_pid)?;
+    find_position(container, &position, host_pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aux() -> ContainerTopResponse {
+        let titles = ["USER", "PID", "%CPU", "%MEM", "VSZ", "RSS", "TTY", "STAT", "START", "TIME", "COMMAND"];
+        let rows = [
+            ["root", "4120", "0.0", "0.1", "10648", "6020", "?", "Ss", "10:02", "0:00", "nginx: master process nginx -g daemon off;"],
+            ["101", "4188", "97.3", "2.4", "11112", "2704", "?", "R", "10:02", "5:31", "nginx: worker process"],
+        ];
+        ContainerTopResponse {
+            titles: Some(titles.iter().map(|title| title.to_string()).collect()),
+            processes: Some(rows.iter().map(|row| row.iter().map(|value| value.to_string()).collect()).collect()),
+        }
+    }
+
+    #[test]
+    fn test_parse_top() {
+        let list = parse_top(aux());
+        assert_eq!(list.titles.len(), 11);
+        let worker = &list.processes[1];
+        assert_eq!(worker.pid, 4188);
+        assert_eq!(worker.ppid, None);
+        assert_eq!(worker.user.as_deref(), Some("101"));
+        assert_eq!(worker.cpu_percent, Some(97.3));
+        assert_eq!(worker.mem_percent, Some(2.4));
+        assert_eq!((worker.vsz, worker.rss), (Some(11112), Some(2704)));
+        assert_eq!(worker.stat.as_deref(), Some("R"));
+        assert_eq!(worker.time.as_deref(), Some("5:31"));
+        assert_eq!(worker.command, "nginx: worker process");
+        assert_eq!(worker.columns["START"], "10:02");
+
+        // The `ps -ef` layout
+        let ef = ContainerTopResponse {
+            titles: Some(["UID", "PID", "PPID", "C", "STIME", "TTY", "TIME", "CMD"].map(String::from).to_vec()),
+            processes: Some(vec![
+                ["root", "812", "790", "3", "09:58", "?", "00:00:01", "postgres"].map(String::from).to_vec(),
+                ["root", "-", "790", "0", "09:58", "?", "00:00:00", "bad"].map(String::from).to_vec(),
+            ]),
+        };
+        let list = parse_top(ef);
+        assert_eq!(list.processes.len(), 1);
+        assert_eq!(list.processes[0].ppid, Some(790));
+        assert_eq!(list.processes[0].cpu_percent, Some(3.0));
+        assert_eq!(list.processes[0].user.as_deref(), Some("root"));
+        assert_eq!(list.processes[0].started.as_deref(), Some("09:58"));
+    }
+
+    // A /proc/<pid>/stat line
+    fn stat(pid: i64, comm: &str, ppid: i64, started: i64) -> String {
+        format!("{} ({}) S {} 1 1 0 -1 4194560 120 0 0 0 3 1 0 0 20 0 1 0 {} 12345678 900 18446744073709551615", pid, comm, ppid, started)
+    }
+
+    #[test]
+    fn test_container_tree() {
+        let lines = [stat(1, "gunicorn", 0, 500), stat(7, "gunicorn", 1, 620), stat(9, "my (odd) name", 1, 700), stat(57, "sh", 0, 9000)];
+        let output = format!("57\n{}\n", lines.join("\n"));
+        let tree = container_tree(&output);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree[2], TreeProcess { pid: 9, ppid: 1, comm: "my (odd) name".to_string(), started: 700 });
+    }
+
+    #[test]
+    fn test_container_pid() {
+        let process = |pid: i64, ppid: i64, comm: &str, started: i64| TreeProcess { pid, ppid, comm: comm.to_string(), started };
+        // Host PIDs with seconds since they started, negated; the shim at 3900 is outside the container.
+        // The host's PIDs wrapped between the two workers.
+        let host = vec![
+            process(4_194_000, 3900, "gunicorn", -600),
+            process(4_194_010, 4_194_000, "gunicorn", -590),
+            process(310, 4_194_000, "gunicorn", -20),
+            process(4_194_020, 4_194_000, "cron", -590),
+            process(4500, 3901, "sh", -5),
+            process(4501, 4500, "python3", -4),
+        ];
+        // Clock ticks since boot
+        let container = vec![
+            process(1, 0, "gunicorn", 1000),
+            process(7, 1, "gunicorn", 2000),
+            process(8, 1, "gunicorn", 59000),
+            process(9, 1, "cron", 2000),
+            process(30, 0, "sh", 60500),
+            process(31, 30, "python3", 60600),
+        ];
+        assert_eq!(container_pid(&host, &container, 4_194_000), Ok(1));
+        assert_eq!(container_pid(&host, &container, 310), Ok(8));
+        assert_eq!(container_pid(&host, &container, 4_194_010), Ok(7));
+        assert_eq!(container_pid(&host, &container, 4_194_020), Ok(9));
+        assert_eq!(container_pid(&host, &container, 4501), Ok(31));
+        assert!(container_pid(&host, &container, 1234).unwrap_err().contains("not running"));
+
+        // A worker exited between the two listings
+        assert!(container_pid(&host, &container[..2], 310).unwrap_err().contains("refresh"));
+    }
+
+    #[test]
+    fn test_container_pid_ambiguous() {
+        let process = |pid: i64, ppid: i64, comm: &str, started: i64| TreeProcess { pid, ppid, comm: comm.to_string(), started };
+        // Two workers forked within the same second can't be ordered on the host
+        let host = vec![
+            process(4000, 3900, "gunicorn", -600),
+            process(4010, 4000, "gunicorn", -590),
+            process(4012, 4000, "gunicorn", -590),
+            process(4020, 4000, "gunicorn", -300),
+        ];
+        let container = vec![
+            process(1, 0, "gunicorn", 1000),
+            process(7, 1, "gunicorn", 2000),
+            process(8, 1, "gunicorn", 2010),
+            process(9, 1, "gunicorn", 31000),
+        ];
+        assert!(container_pid(&host, &container, 4012).unwrap_err().contains("can't be told apart"));
+        assert!(container_pid(&host, &container, 4010).unwrap_err().contains("can't be told apart"));
+        // A later one still has a definite place
+        assert_eq!(container_pid(&host, &container, 4020), Ok(9));
+
+        // Nor can processes that started within the same clock tick inside the container
+        let mut host = host;
+        host[1].started = -595;
+        let mut container = container;
+        container[2].started = 2000;
+        assert!(container_pid(&host, &container, 4012).unwrap_err().contains("can't be told apart"));
+    }
+}
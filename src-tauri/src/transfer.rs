@@ -0,0 +1,307 @@
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tokio::sync::mpsc::{Receiver, Sender};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Where a copy lands, following `docker cp`: into `target` when it is an existing directory,
+/// otherwise next to it under the name `target` gives. Returns the directory to extract into
+/// and the new name of the archive's top entry.
+pub fn copy_destination(target: &str, target_is_dir: bool) -> (String, Option<String>) {
+    let trimmed = target.trim_end_matches('/');
+    if target_is_dir || trimmed.is_empty() {
+        return (target.to_string(), None);
+    }
+    match trimmed.rsplit_once('/') {
+        Some((parent, name)) => {
+            let parent = if parent.is_empty() { "/" } else { parent };
+            (parent.to_string(), Some(name.to_string()))
+        }
+        None => (".".to_string(), Some(trimmed.to_string())),
+    }
+}
+
+/// Whether the first entry of an archive is a directory; None until a whole header has arrived
+pub fn first_entry_is_dir(archive: &[u8]) -> Option<bool> {
+    let header = archive.get(..512)?;
+    Some(tar::Header::from_byte_slice(header).entry_type().is_dir())
+}
+
+const BLOCK_SIZE: u64 = 512;
+
+/// Size of the archive `write_archive` makes of `path`, for progress totals: a header block per
+/// entry, file content padded to whole blocks, and two zero blocks at the end
+pub fn archive_size(path: &Path, root_name: &str) -> u64 {
+    entries_size(path, Path::new(root_name)) + 2 * BLOCK_SIZE
+}
+
+fn entries_size(path: &Path, name: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else { return 0 };
+    let mut size = BLOCK_SIZE + long_name_size(name);
+    if metadata.is_dir() {
+        if let Ok(entries) = std::fs::read_dir(path) {
+            size += entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entries_size(&entry.path(), &name.join(entry.file_name())))
+                .sum::<u64>();
+        }
+    } else if metadata.is_file() {
+        size += metadata.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+    } else if let Ok(target) = std::fs::read_link(path) {
+        size += long_name_size(&target);
+    }
+    size
+}
+
+// A name too long for its header field goes in a GNU extension entry of its own, NUL-terminated
+fn long_name_size(name: &Path) -> u64 {
+    let length = name.as_os_str().len() as u64;
+    if length > 100 {
+        BLOCK_SIZE + (length + 1).div_ceil(BLOCK_SIZE) * BLOCK_SIZE
+    } else {
+        0
+    }
+}
+
+/// Archives the file or directory at `path` with `root_name` as its top entry.
+/// Permissions, times and ownership are kept, and symlinks are stored as links rather than followed.
+pub fn write_archive<W: Write>(writer: W, path: &Path, root_name: &str) -> Result<W, String> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+    builder.mode(tar::HeaderMode::Complete);
+
+    let metadata = std::fs::symlink_metadata(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let result = if metadata.is_dir() {
+        builder.append_dir_all(root_name, path)
+    } else {
+        builder.append_path_with_name(path, root_name)
+    };
+    result
+        .and_then(|_| builder.into_inner())
+        .and_then(|mut writer| writer.flush().map(|_| writer))
+        .map_err(|e| format!("Failed to archive '{}': {}", path.display(), e))
+}
+
+// The entry's path below the destination, with the top entry renamed; None for paths that would leave it
+fn entry_destination(path: &Path, rename_root: Option<&str>) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) if relative.as_os_str().is_empty() => relative.push(rename_root.map(Path::new).unwrap_or(Path::new(name))),
+            Component::Normal(name) => relative.push(name),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
+
+/// Extracts an archive into the existing directory `dest`, renaming its top entry to `rename_root`.
+/// Entries whose path, or a symlink on the way to it, would lead outside `dest` fail the extraction.
+/// Returns the number of entries extracted.
+pub fn unpack_archive<R: Read>(reader: R, dest: &Path, rename_root: Option<&str>) -> Result<u64, String> {
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+    archive.set_preserve_mtime(true);
+    archive.set_overwrite(true);
+    let root = dest.canonicalize().map_err(|e| format!("Failed to open '{}': {}", dest.display(), e))?;
+
+    let mut count = 0;
+    let entries = archive.entries().map_err(|e| format!("Failed to read archive: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read archive: {}", e))?;
+        let path = entry.path().map_err(|e| format!("Failed to read archive: {}", e))?.into_owned();
+        let relative = entry_destination(&path, rename_root)
+            .ok_or_else(|| format!("Refusing to extract '{}' outside the destination", path.display()))?;
+        let target = root.join(&relative);
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+            let parent = parent.canonicalize().map_err(|e| format!("Failed to open '{}': {}", parent.display(), e))?;
+            if !parent.starts_with(&root) {
+                return Err(format!("Refusing to extract '{}' through a symlink outside the destination", path.display()));
+            }
+        }
+
+        if entry.header().entry_type().is_hard_link() {
+            // Hard links name another entry of the archive, which has to be resolved the same way
+            let link = entry
+                .link_name()
+                .map_err(|e| format!("Failed to read archive: {}", e))?
+                .and_then(|link| entry_destination(&link, rename_root))
+                .ok_or_else(|| format!("Refusing to extract '{}' linking outside the destination", path.display()))?;
+            let _ = std::fs::remove_file(&target);
+            std::fs::hard_link(root.join(link), &target).map_err(|e| format!("Failed to extract '{}': {}", path.display(), e))?;
+        } else {
+            entry.unpack(&target).map_err(|e| format!("Failed to extract '{}': {}", path.display(), e))?;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Hands what is written to it over to an async task in chunks
+pub struct ChannelWriter {
+    sender: Sender<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl ChannelWriter {
+    pub fn new(sender: Sender<Vec<u8>>) -> Self {
+        ChannelWriter { sender, buffer: Vec::with_capacity(CHUNK_SIZE) }
+    }
+
+    fn send_buffer(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(CHUNK_SIZE));
+        self.sender
+            .blocking_send(chunk)
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "the receiving side stopped"))
+    }
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.send_buffer()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.send_buffer()
+    }
+}
+
+/// Reads the chunks an async task sends, until it drops its sender
+pub struct ChannelReader {
+    receiver: Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl ChannelReader {
+    pub fn new(receiver: Receiver<Vec<u8>>) -> Self {
+        ChannelReader { receiver, chunk: Vec::new(), position: 0 }
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let count = buf.len().min(self.chunk.len() - self.position);
+        buf[..count].copy_from_slice(&self.chunk[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dock-transfer-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_copy_destination() {
+        assert_eq!(copy_destination("/etc/nginx", true), ("/etc/nginx".to_string(), None));
+        assert_eq!(copy_destination("/etc/app.conf", false), ("/etc".to_string(), Some("app.conf".to_string())));
+        assert_eq!(copy_destination("/app.conf", false), ("/".to_string(), Some("app.conf".to_string())));
+        assert_eq!(copy_destination("/srv/site/", false), ("/srv".to_string(), Some("site".to_string())));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_archive_round_trip() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let source = temp_dir("source");
+        std::fs::create_dir_all(source.join("site/assets")).unwrap();
+        std::fs::write(source.join("site/index.html"), "<h1>hi</h1>").unwrap();
+        std::fs::write(source.join("site/run.sh"), "#!/bin/sh").unwrap();
+        std::fs::set_permissions(source.join("site/run.sh"), std::fs::Permissions::from_mode(0o750)).unwrap();
+        std::os::unix::fs::symlink("index.html", source.join("site/assets/home.html")).unwrap();
+        let long_name = "a".repeat(120);
+        std::fs::write(source.join("site").join(&long_name), "x".repeat(600)).unwrap();
+        std::os::unix::fs::symlink(source.join("site").join(&long_name), source.join("site/assets/long")).unwrap();
+
+        // Through the channels, as the commands do
+        let (sender, receiver) = tokio::sync::mpsc::channel(2);
+        let site = source.join("site");
+        let writer = std::thread::spawn(move || {
+            // Dropping the writer closes the channel, which ends the reader
+            write_archive(ChannelWriter::new(sender), &site, "site").unwrap();
+        });
+        let mut archive = Vec::new();
+        ChannelReader::new(receiver).read_to_end(&mut archive).unwrap();
+        writer.join().unwrap();
+        assert_eq!(archive.len() as u64, archive_size(&source.join("site"), "site"));
+        assert_eq!(first_entry_is_dir(&archive), Some(true));
+        assert_eq!(first_entry_is_dir(&archive[..100]), None);
+
+        let dest = temp_dir("dest");
+        assert_eq!(unpack_archive(archive.as_slice(), &dest, Some("copy")).unwrap(), 7);
+        assert_eq!(std::fs::read_to_string(dest.join("copy/index.html")).unwrap(), "<h1>hi</h1>");
+        let mode = std::fs::metadata(dest.join("copy/run.sh")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+        assert_eq!(std::fs::read_link(dest.join("copy/assets/home.html")).unwrap(), Path::new("index.html"));
+
+        std::fs::remove_dir_all(&source).unwrap();
+        std::fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unpack_rejects_escapes() {
+        let dest = temp_dir("escape");
+        let outside = temp_dir("outside");
+
+        let archive = |build: &dyn Fn(&mut tar::Builder<Vec<u8>>)| {
+            let mut builder = tar::Builder::new(Vec::new());
+            build(&mut builder);
+            builder.into_inner().unwrap()
+        };
+        let file = |builder: &mut tar::Builder<Vec<u8>>, path: &str| {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(2);
+            header.set_mode(0o644);
+            header.set_entry_type(tar::EntryType::Regular);
+            // set_path refuses `..`, so the name goes into the header directly
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_cksum();
+            builder.append(&header, &b"hi"[..]).unwrap();
+        };
+
+        let traversal = archive(&|builder| file(builder, "../evil"));
+        assert!(unpack_archive(traversal.as_slice(), &dest, None).unwrap_err().contains("outside the destination"));
+
+        let through_symlink = archive(&|builder| {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            builder.append_link(&mut header, "link", &outside).unwrap();
+            file(builder, "link/evil");
+        });
+        assert!(unpack_archive(through_symlink.as_slice(), &dest, None).unwrap_err().contains("symlink"));
+        assert!(!outside.join("evil").exists());
+
+        std::fs::remove_dir_all(&dest).unwrap();
+        std::fs::remove_dir_all(&outside).unwrap();
+    }
+}
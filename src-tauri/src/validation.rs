@@ -18,6 +18,20 @@ pub fn validate_name(name: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Validates compose project name (lowercase alphanumeric, hyphens, underscores)
+pub fn validate_project_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Project name cannot be empty".to_string());
+    }
+    
+    let valid_pattern = Regex::new(r"^[a-z0-9][a-z0-9_-]*$").unwrap();
+    if !valid_pattern.is_match(name) {
+        return Err("Project name must start with a lowercase letter or digit and contain only lowercase letters, digits, hyphens, or underscores".to_string());
+    }
+    
+    Ok(())
+}
+
 /// Validates Docker image name format
 pub fn validate_image_name(image: &str) -> Result<(), String> {
     if image.is_empty() {
@@ -155,6 +169,74 @@ pub fn validate_network_name(network: &str) -> Result<(), String> {
     validate_name(network)
 }
 
+/// Validates a signal given by name (`KILL`, `SIGKILL`, `sigterm`) or number,
+/// returning it in the `SIGKILL` form Docker expects, or the number as given
+pub fn validate_signal(signal: &str) -> Result<String, String> {
+    let signal = signal.trim();
+    if signal.is_empty() {
+        return Err("Signal cannot be empty".to_string());
+    }
+    
+    if let Ok(number) = signal.parse::<u32>() {
+        // Linux numbers its signals 1 to 64, counting the real-time ones
+        if (1..=64).contains(&number) {
+            return Ok(number.to_string());
+        }
+        return Err(format!("Invalid signal number: {} (must be 1-64)", number));
+    }
+    
+    let signals = [
+        "HUP", "INT", "QUIT", "ILL", "TRAP", "ABRT", "BUS", "FPE", "KILL", "USR1", "SEGV", "USR2", "PIPE",
+        "ALRM", "TERM", "STKFLT", "CHLD", "CONT", "STOP", "TSTP", "TTIN", "TTOU", "URG", "XCPU", "XFSZ",
+        "VTALRM", "PROF", "WINCH", "IO", "PWR", "SYS",
+    ];
+    let upper = signal.to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    if !signals.contains(&name) {
+        return Err(format!("Unknown signal: {}", signal));
+    }
+    Ok(format!("SIG{}", name))
+}
+
+/// Validates a restart policy (`no`, `always`, `unless-stopped`, `on-failure[:N]`),
+/// splitting off the retry count that only `on-failure` takes
+pub fn validate_restart_policy(policy: &str) -> Result<(String, Option<i64>), String> {
+    let (name, retries) = match policy.trim().split_once(':') {
+        Some((name, retries)) => (name, Some(retries)),
+        None => (policy.trim(), None),
+    };
+    
+    if !["no", "always", "unless-stopped", "on-failure"].contains(&name) {
+        return Err(format!("Invalid restart policy: {}", policy));
+    }
+    
+    let retries = match retries {
+        Some(_) if name != "on-failure" => {
+            return Err(format!("Only on-failure takes a retry count: {}", policy));
+        }
+        Some(retries) => match retries.parse::<i64>() {
+            Ok(count) if count >= 0 => Some(count),
+            _ => return Err(format!("Invalid restart retry count: {}", retries)),
+        },
+        None => None,
+    };
+    
+    Ok((name.to_string(), retries))
+}
+
+/// Validates how long a stop waits before the container is killed
+pub fn validate_stop_timeout(secs: i64) -> Result<(), String> {
+    if secs < 0 {
+        return Err("Stop timeout cannot be negative".to_string());
+    }
+    
+    if secs > 86400 {
+        return Err("Stop timeout unreasonably long (max 1 day)".to_string());
+    }
+    
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,6 +250,15 @@ mod tests {
         assert!(validate_name("with space").is_err());
     }
 
+    #[test]
+    fn test_validate_project_name() {
+        assert!(validate_project_name("shop").is_ok());
+        assert!(validate_project_name("my_app-2").is_ok());
+        assert!(validate_project_name("").is_err());
+        assert!(validate_project_name("MyApp").is_err());
+        assert!(validate_project_name("-app").is_err());
+    }
+
     #[test]
     fn test_validate_port() {
         assert!(validate_port(80).is_ok());
@@ -191,4 +282,34 @@ mod tests {
         assert!(validate_volume_path("../etc").is_err());
         assert!(validate_volume_path("relative/path").is_err());
     }
+
+    #[test]
+    fn test_validate_signal() {
+        assert_eq!(validate_signal("KILL").unwrap(), "SIGKILL");
+        assert_eq!(validate_signal("sigterm").unwrap(), "SIGTERM");
+        assert_eq!(validate_signal("SIGUSR1").unwrap(), "SIGUSR1");
+        assert_eq!(validate_signal("9").unwrap(), "9");
+        assert!(validate_signal("0").is_err());
+        assert!(validate_signal("65").is_err());
+        assert!(validate_signal("SIGFOO").is_err());
+        assert!(validate_signal("").is_err());
+    }
+
+    #[test]
+    fn test_validate_restart_policy() {
+        assert_eq!(validate_restart_policy("always").unwrap(), ("always".to_string(), None));
+        assert_eq!(validate_restart_policy("on-failure").unwrap(), ("on-failure".to_string(), None));
+        assert_eq!(validate_restart_policy("on-failure:3").unwrap(), ("on-failure".to_string(), Some(3)));
+        assert!(validate_restart_policy("on-failure:-1").is_err());
+        assert!(validate_restart_policy("always:3").is_err());
+        assert!(validate_restart_policy("sometimes").is_err());
+    }
+
+    #[test]
+    fn test_validate_stop_timeout() {
+        assert!(validate_stop_timeout(0).is_ok());
+        assert!(validate_stop_timeout(600).is_ok());
+        assert!(validate_stop_timeout(-1).is_err());
+        assert!(validate_stop_timeout(86401).is_err());
+    }
 }
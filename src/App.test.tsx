@@ -3,16 +3,24 @@ import { render, screen, fireEvent, waitFor, within } from '@testing-library/rea
 import userEvent from '@testing-library/user-event';
 import App from './App';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 vi.mock('@tauri-apps/api/core', () => ({
   invoke: vi.fn(),
 }));
 
 const mockInvoke = vi.mocked(invoke);
+const mockListen = vi.mocked(listen);
+const eventHandlers = new Map<string, (event: { payload: unknown }) => void>();
 
 beforeEach(() => {
   vi.clearAllMocks();
   localStorage.clear();
+  eventHandlers.clear();
+  mockListen.mockImplementation(async (name, handler) => {
+    eventHandlers.set(name, handler as (event: { payload: unknown }) => void);
+    return () => {};
+  });
 });
 
 function setupConnectedApp() {
@@ -183,6 +191,25 @@ describe('App', () => {
       });
     });
 
+    it('refreshes containers once after a burst of container events', async () => {
+      setupConnectedApp();
+      render(<App />);
+
+      await waitFor(() => expect(eventHandlers.has('docker-event')).toBe(true));
+      const listCalls = () => mockInvoke.mock.calls.filter(([cmd]) => cmd === 'list_containers').length;
+      const before = listCalls();
+
+      const emit = eventHandlers.get('docker-event')!;
+      for (let i = 0; i < 30; i++) {
+        emit({ payload: { kind: 'container', action: i % 2 ? 'start' : 'create', id: `c${i}`, attributes: {}, timeNano: i } });
+      }
+      expect(listCalls()).toBe(before);
+
+      await waitFor(() => expect(listCalls()).toBe(before + 1));
+      await new Promise(resolve => setTimeout(resolve, 300));
+      expect(listCalls()).toBe(before + 1);
+    });
+
     it('shows "Show all containers" checkbox', async () => {
       setupConnectedApp();
       render(<App />);
@@ -23,6 +23,9 @@ import './App.css';
 
 type View = 'templates' | 'dashboard' | 'list' | 'details' | 'logs' | 'terminal' | 'images' | 'volumes' | 'networks' | 'settings';
 
+// How long container events must pause before the list is refreshed
+const EVENT_REFRESH_DELAY_MS = 250;
+
 function App() {
   const [containers, setContainers] = useState<ContainerInfo[]>([]);
   const [selectedContainer, setSelectedContainer] = useState<string | null>(null);
@@ -132,6 +135,25 @@ function App() {
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, [showAll, dockerConnected, refreshMs]);
 
+  // Refresh as soon as a container changes instead of waiting for the next poll. A deploy or
+  // compose down sends a burst of events, which are waited out so they cause a single refresh.
+  useEffect(() => {
+    if (!dockerConnected) return;
+
+    let timer: ReturnType<typeof setTimeout> | undefined;
+    const unlisten = dockerApi.onDockerEvent((event) => {
+      if (event.kind === 'container') {
+        clearTimeout(timer);
+        timer = setTimeout(loadContainers, EVENT_REFRESH_DELAY_MS);
+      }
+    });
+    return () => {
+      clearTimeout(timer);
+      unlisten.then(stop => stop());
+    };
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [showAll, dockerConnected]);
+
   useEffect(() => {
     const handleKeyDown = (e: KeyboardEvent) => {
       const target = e.target as HTMLElement;
@@ -1,5 +1,6 @@
 import { describe, it, expect, vi, beforeEach } from 'vitest';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { dockerApi } from './api';
 
 vi.mock('@tauri-apps/api/core', () => ({
@@ -73,6 +74,29 @@ describe('dockerApi', () => {
       expect(mockInvoke).toHaveBeenCalledWith('restart_container', { id: 'abc123' });
     });
 
+    it('stopContainer and restartContainer pass a timeout', async () => {
+      mockInvoke.mockResolvedValue(undefined);
+      await dockerApi.stopContainer('abc123', 60);
+      expect(mockInvoke).toHaveBeenCalledWith('stop_container', { id: 'abc123', timeout: 60 });
+      await dockerApi.restartContainer('abc123', 30);
+      expect(mockInvoke).toHaveBeenCalledWith('restart_container', { id: 'abc123', timeout: 30 });
+    });
+
+    it('killContainer passes signal names and numbers as strings', async () => {
+      mockInvoke.mockResolvedValue(undefined);
+      await dockerApi.killContainer('abc123', 'SIGTERM');
+      expect(mockInvoke).toHaveBeenCalledWith('kill_container', { id: 'abc123', signal: 'SIGTERM' });
+      await dockerApi.killContainer('abc123', 9);
+      expect(mockInvoke).toHaveBeenCalledWith('kill_container', { id: 'abc123', signal: '9' });
+    });
+
+    it('waitContainer returns the exit code', async () => {
+      mockInvoke.mockResolvedValue(137);
+      const code = await dockerApi.waitContainer('abc123', 'next-exit');
+      expect(mockInvoke).toHaveBeenCalledWith('wait_container', { id: 'abc123', condition: 'next-exit' });
+      expect(code).toBe(137);
+    });
+
     it('pauseContainer calls pause_container', async () => {
       mockInvoke.mockResolvedValue(undefined);
       await dockerApi.pauseContainer('abc123');
@@ -100,19 +124,127 @@ describe('dockerApi', () => {
 
   describe('getContainerLogs', () => {
     it('passes id and tail parameter', async () => {
-      mockInvoke.mockResolvedValue('log line 1\nlog line 2');
+      const entries = [
+        { stream: 'stdout', timestamp: null, line: 'log line 1' },
+        { stream: 'stderr', timestamp: null, line: 'log line 2' },
+      ];
+      mockInvoke.mockResolvedValue(entries);
       const result = await dockerApi.getContainerLogs('abc123', '100');
       expect(mockInvoke).toHaveBeenCalledWith('get_container_logs', { id: 'abc123', tail: '100' });
-      expect(result).toBe('log line 1\nlog line 2');
+      expect(result).toEqual(entries);
+    });
+
+    it('passes a log query', async () => {
+      mockInvoke.mockResolvedValue([]);
+      const query = { since: '15m', stream: 'stderr' as const, filter: 'timeout|refused', regex: true };
+      await dockerApi.getContainerLogs('abc123', 'all', query);
+      expect(mockInvoke).toHaveBeenCalledWith('get_container_logs', { id: 'abc123', tail: 'all', query });
     });
 
     it('works without tail parameter', async () => {
-      mockInvoke.mockResolvedValue('logs');
+      mockInvoke.mockResolvedValue([]);
       await dockerApi.getContainerLogs('abc123');
       expect(mockInvoke).toHaveBeenCalledWith('get_container_logs', { id: 'abc123', tail: undefined });
     });
   });
 
+  describe('exportContainerLogs', () => {
+    it('passes id, path, options and a progress channel', async () => {
+      const result = { path: '/tmp/web.log.gz', linesWritten: 2, fileSize: 64 };
+      mockInvoke.mockResolvedValue(result);
+      const onProgress = vi.fn();
+      const exported = await dockerApi.exportContainerLogs('abc123', '/tmp/web.log.gz', onProgress, { format: 'jsonl', gzip: true });
+      expect(mockInvoke).toHaveBeenCalledWith('export_container_logs', {
+        id: 'abc123',
+        path: '/tmp/web.log.gz',
+        options: { format: 'jsonl', gzip: true },
+        onProgress: expect.objectContaining({ onmessage: onProgress }),
+      });
+      expect(exported).toEqual(result);
+    });
+  });
+
+  describe('streamContainerLogs', () => {
+    it('passes id, options and a channel', async () => {
+      mockInvoke.mockResolvedValue('logs-1');
+      const onLogs = vi.fn();
+      const streamId = await dockerApi.streamContainerLogs('abc123', onLogs, { tail: '50' });
+      expect(mockInvoke).toHaveBeenCalledWith('stream_container_logs', {
+        id: 'abc123',
+        tail: '50',
+        onLogs: expect.objectContaining({ onmessage: onLogs }),
+      });
+      expect(streamId).toBe('logs-1');
+    });
+
+    it('streamAggregatedLogs passes container ids, options and a channel', async () => {
+      mockInvoke.mockResolvedValue('logs-2');
+      const onLogs = vi.fn();
+      const streamId = await dockerApi.streamAggregatedLogs(['abc123', 'def456'], onLogs, { project: 'shop' });
+      expect(mockInvoke).toHaveBeenCalledWith('stream_aggregated_logs', {
+        containerIds: ['abc123', 'def456'],
+        project: 'shop',
+        onLogs: expect.objectContaining({ onmessage: onLogs }),
+      });
+      expect(streamId).toBe('logs-2');
+    });
+
+    it('stopLogStream passes the stream id', async () => {
+      mockInvoke.mockResolvedValue(undefined);
+      await dockerApi.stopLogStream('logs-1');
+      expect(mockInvoke).toHaveBeenCalledWith('stop_log_stream', { streamId: 'logs-1' });
+    });
+  });
+
+  describe('onDockerEvent', () => {
+    it('listens for docker-event and passes the payload on', async () => {
+      const handler = vi.fn();
+      await dockerApi.onDockerEvent(handler);
+      expect(listen).toHaveBeenCalledWith('docker-event', expect.any(Function));
+      const callback = vi.mocked(listen).mock.calls[0][1];
+      const payload = { kind: 'container', action: 'die', detail: null, id: 'abc123', name: 'web', attributes: {}, timeNano: 1 };
+      callback({ event: 'docker-event', id: 1, payload });
+      expect(handler).toHaveBeenCalledWith(payload);
+    });
+
+    it('onDockerEventsStatus listens for docker-events-status', async () => {
+      await dockerApi.onDockerEventsStatus(vi.fn());
+      expect(listen).toHaveBeenCalledWith('docker-events-status', expect.any(Function));
+    });
+  });
+
+  describe('queryEvents', () => {
+    it('passes the filter and returns events', async () => {
+      const events = [{ kind: 'container', action: 'die', detail: null, id: 'abc123', name: 'web', attributes: { exitCode: '137' }, timeNano: 1 }];
+      mockInvoke.mockResolvedValue(events);
+      const result = await dockerApi.queryEvents({ container: 'abc123', action: 'die', limit: 1 });
+      expect(mockInvoke).toHaveBeenCalledWith('query_events', { filter: { container: 'abc123', action: 'die', limit: 1 } });
+      expect(result).toEqual(events);
+    });
+  });
+
+  describe('notification settings', () => {
+    const settings = {
+      enabled: true,
+      rateLimitSecs: 300,
+      defaults: { crash: true, oom: true, unhealthy: true },
+      rules: [{ label: 'tier=batch', triggers: { crash: false, oom: true, unhealthy: false } }],
+    };
+
+    it('getNotificationSettings returns the settings', async () => {
+      mockInvoke.mockResolvedValue(settings);
+      const result = await dockerApi.getNotificationSettings();
+      expect(mockInvoke).toHaveBeenCalledWith('get_notification_settings');
+      expect(result).toEqual(settings);
+    });
+
+    it('setNotificationSettings passes the settings', async () => {
+      mockInvoke.mockResolvedValue(undefined);
+      await dockerApi.setNotificationSettings(settings);
+      expect(mockInvoke).toHaveBeenCalledWith('set_notification_settings', { settings });
+    });
+  });
+
   describe('getContainerDetails', () => {
     it('returns container details', async () => {
       const details = {
@@ -134,6 +266,129 @@ describe('dockerApi', () => {
     });
   });
 
+  describe('copyToContainer / copyFromContainer', () => {
+    it('passes paths and a progress channel to copy_to_container', async () => {
+      mockInvoke.mockResolvedValue(undefined);
+      const onProgress = vi.fn();
+      await dockerApi.copyToContainer('abc123', '/home/me/site', '/usr/share/nginx/html', onProgress);
+      expect(mockInvoke).toHaveBeenCalledWith('copy_to_container', {
+        id: 'abc123',
+        hostPath: '/home/me/site',
+        containerPath: '/usr/share/nginx/html',
+        onProgress: expect.objectContaining({ onmessage: onProgress }),
+      });
+    });
+
+    it('passes paths and a progress channel to copy_from_container', async () => {
+      mockInvoke.mockResolvedValue(undefined);
+      const onProgress = vi.fn();
+      await dockerApi.copyFromContainer('abc123', '/etc/nginx/nginx.conf', '/tmp/nginx.conf', onProgress);
+      expect(mockInvoke).toHaveBeenCalledWith('copy_from_container', {
+        id: 'abc123',
+        containerPath: '/etc/nginx/nginx.conf',
+        hostPath: '/tmp/nginx.conf',
+        onProgress: expect.objectContaining({ onmessage: onProgress }),
+      });
+    });
+
+    it('rejects with the backend error', async () => {
+      mockInvoke.mockRejectedValue('Failed to copy to container: no such container');
+      await expect(dockerApi.copyToContainer('missing', '/tmp/a', '/a', vi.fn())).rejects.toBe('Failed to copy to container: no such container');
+    });
+  });
+
+  describe('listContainerDir / readContainerFile', () => {
+    it('lists a directory', async () => {
+      const entries = [
+        { name: 'ssl', kind: 'directory', size: 4096, mode: 0o755, mtime: 1714564800, linkTarget: null },
+        { name: 'localtime', kind: 'symlink', size: 0, mode: 0o777, mtime: 1714564800, linkTarget: '/usr/share/zoneinfo/UTC' },
+      ];
+      mockInvoke.mockResolvedValue(entries);
+      const result = await dockerApi.listContainerDir('abc123', '/etc');
+      expect(mockInvoke).toHaveBeenCalledWith('list_container_dir', { id: 'abc123', path: '/etc' });
+      expect(result).toEqual(entries);
+    });
+
+    it('reads a file preview with a byte limit', async () => {
+      const file = { path: '/etc/hostname', size: 13, content: 'web-1\n', truncated: false, binary: false };
+      mockInvoke.mockResolvedValue(file);
+      const result = await dockerApi.readContainerFile('abc123', '/etc/hostname', 4096);
+      expect(mockInvoke).toHaveBeenCalledWith('read_container_file', { id: 'abc123', path: '/etc/hostname', maxBytes: 4096 });
+      expect(result).toEqual(file);
+    });
+
+    it('rejects when the path is a directory', async () => {
+      mockInvoke.mockRejectedValue("'/etc' is a directory");
+      await expect(dockerApi.readContainerFile('abc123', '/etc')).rejects.toBe("'/etc' is a directory");
+    });
+  });
+
+  describe('containerChanges', () => {
+    it('returns the change tree', async () => {
+      const changes = {
+        added: 1,
+        modified: 1,
+        deleted: 0,
+        sizeRw: 4096,
+        sizeRootFs: 80000000,
+        tree: [{
+          name: 'var',
+          path: '/var',
+          kind: 'modified',
+          size: 4096,
+          children: [{ name: 'data.db', path: '/var/data.db', kind: 'added', size: 4096, children: [] }],
+        }],
+      };
+      mockInvoke.mockResolvedValue(changes);
+      const result = await dockerApi.containerChanges('abc123');
+      expect(mockInvoke).toHaveBeenCalledWith('container_changes', { id: 'abc123' });
+      expect(result).toEqual(changes);
+    });
+  });
+
+  describe('container processes', () => {
+    it('containerTop passes ps arguments', async () => {
+      const list = {
+        titles: ['USER', 'PID', '%CPU', 'COMMAND'],
+        processes: [{
+          pid: 4188, ppid: null, user: 'www', cpuPercent: 97.3, memPercent: null, vsz: null, rss: null,
+          tty: null, stat: null, started: null, time: null, command: 'nginx: worker process',
+          columns: { USER: 'www', PID: '4188', '%CPU': '97.3', COMMAND: 'nginx: worker process' },
+        }],
+      };
+      mockInvoke.mockResolvedValue(list);
+      const result = await dockerApi.containerTop('abc123', 'aux');
+      expect(mockInvoke).toHaveBeenCalledWith('container_top', { id: 'abc123', psArgs: 'aux' });
+      expect(result).toEqual(list);
+    });
+
+    it('streamContainerTop passes options and a channel', async () => {
+      mockInvoke.mockResolvedValue('top-1');
+      const onSnapshot = vi.fn();
+      const streamId = await dockerApi.streamContainerTop('abc123', onSnapshot, { intervalMs: 1000 });
+      expect(mockInvoke).toHaveBeenCalledWith('stream_container_top', {
+        id: 'abc123',
+        intervalMs: 1000,
+        onSnapshot: expect.objectContaining({ onmessage: onSnapshot }),
+      });
+      expect(streamId).toBe('top-1');
+    });
+
+    it('stopTopStream passes the stream id', async () => {
+      mockInvoke.mockResolvedValue(undefined);
+      await dockerApi.stopTopStream('top-1');
+      expect(mockInvoke).toHaveBeenCalledWith('stop_top_stream', { streamId: 'top-1' });
+    });
+
+    it('killContainerProcess passes the pid and signal', async () => {
+      mockInvoke.mockResolvedValue(undefined);
+      await dockerApi.killContainerProcess('abc123', 4188, 'KILL');
+      expect(mockInvoke).toHaveBeenCalledWith('kill_container_process', { id: 'abc123', pid: 4188, signal: 'KILL' });
+      await dockerApi.killContainerProcess('abc123', 4188, 9);
+      expect(mockInvoke).toHaveBeenCalledWith('kill_container_process', { id: 'abc123', pid: 4188, signal: '9' });
+    });
+  });
+
   describe('getContainerStats', () => {
     it('returns container stats', async () => {
       const stats = {
@@ -253,14 +508,84 @@ describe('dockerApi', () => {
   describe('compose deployment', () => {
     it('deployCompose passes yaml content', async () => {
       const results = [
-        { service_name: 'web', container_id: 'abc', success: true },
-        { service_name: 'db', container_id: 'def', success: true },
+        { service_name: 'web', container_id: 'abc', success: true, unset_variables: [], warnings: [] },
+        { service_name: 'db', container_id: 'def', success: true, unset_variables: [], warnings: [] },
       ];
       mockInvoke.mockResolvedValue(results);
       const result = await dockerApi.deployCompose('version: "3"\nservices:\n  web:\n    image: nginx');
-      expect(mockInvoke).toHaveBeenCalledWith('deploy_compose', { yamlContent: 'version: "3"\nservices:\n  web:\n    image: nginx' });
+      expect(mockInvoke).toHaveBeenCalledWith('deploy_compose', {
+        yamlContent: 'version: "3"\nservices:\n  web:\n    image: nginx',
+        onBuildProgress: expect.any(Object),
+      });
       expect(result).toEqual(results);
     });
+
+    it('deployCompose passes deploy options', async () => {
+      mockInvoke.mockResolvedValue([]);
+      await dockerApi.deployCompose('services: {}', { projectName: 'shop', envContent: 'TAG=1', workingDir: '/srv/shop' });
+      expect(mockInvoke).toHaveBeenCalledWith('deploy_compose', {
+        yamlContent: 'services: {}',
+        projectName: 'shop',
+        envContent: 'TAG=1',
+        workingDir: '/srv/shop',
+        onBuildProgress: expect.any(Object),
+      });
+    });
+
+    it('planCompose passes yaml content and options', async () => {
+      const plan = { project: 'shop', services: [], networks_to_create: ['shop_default'], volumes_to_create: [] };
+      mockInvoke.mockResolvedValue(plan);
+      const result = await dockerApi.planCompose('services: {}', { projectName: 'shop' });
+      expect(mockInvoke).toHaveBeenCalledWith('plan_compose', { yamlContent: 'services: {}', projectName: 'shop' });
+      expect(result).toEqual(plan);
+    });
+
+    it('generateCompose passes the selected container ids', async () => {
+      mockInvoke.mockResolvedValue('services:\n  web:\n    image: nginx\n');
+      const result = await dockerApi.generateCompose(['abc', 'def']);
+      expect(mockInvoke).toHaveBeenCalledWith('generate_compose', { containerIds: ['abc', 'def'] });
+      expect(result).toContain('image: nginx');
+    });
+
+    it('renderComposeConfig passes override files and profiles', async () => {
+      mockInvoke.mockResolvedValue('services: {}\n');
+      const result = await dockerApi.renderComposeConfig('services: {}', {
+        overrideContents: ['services: {}'],
+        profiles: ['debug'],
+      });
+      expect(mockInvoke).toHaveBeenCalledWith('render_compose_config', {
+        yamlContent: 'services: {}',
+        overrideContents: ['services: {}'],
+        profiles: ['debug'],
+      });
+      expect(result).toBe('services: {}\n');
+    });
+
+    it('project lifecycle commands pass project name', async () => {
+      mockInvoke.mockResolvedValue(undefined);
+      await dockerApi.composeStart('shop');
+      expect(mockInvoke).toHaveBeenCalledWith('compose_start', { project: 'shop' });
+      await dockerApi.composeStop('shop');
+      expect(mockInvoke).toHaveBeenCalledWith('compose_stop', { project: 'shop' });
+      await dockerApi.composeRestart('shop');
+      expect(mockInvoke).toHaveBeenCalledWith('compose_restart', { project: 'shop' });
+    });
+
+    it('composeScale passes project, service and count', async () => {
+      const summary = { containers_created: ['shop-web-2', 'shop-web-3'], containers_removed: [] };
+      mockInvoke.mockResolvedValue(summary);
+      const result = await dockerApi.composeScale('shop', 'web', 3);
+      expect(mockInvoke).toHaveBeenCalledWith('compose_scale', { project: 'shop', service: 'web', count: 3 });
+      expect(result).toEqual(summary);
+    });
+
+    it('composeDown keeps volumes by default', async () => {
+      const summary = { containers_removed: ['shop-web-1'], networks_removed: ['shop_default'], volumes_removed: [] };
+      mockInvoke.mockResolvedValue(summary);
+      const result = await dockerApi.composeDown('shop');
+      expect(mockInvoke).toHaveBeenCalledWith('compose_down', { project: 'shop', removeVolumes: false });
+      expect(result).toEqual(summary);
+    });
   });
 
   describe('registry search', () => {
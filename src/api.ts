@@ -1,5 +1,6 @@
 import { invoke, Channel } from '@tauri-apps/api/core';
-import type { ContainerInfo, ContainerDetails, ImageInfo, ContainerStats, ContainerStatsEntry, VolumeInfo, NetworkDetails, CreateContainerRequest, ComposeDeployResult, RegistrySearchResult, TerminalOutputEvent, PullProgressEvent, PruneResult, ImageUpdateInfo } from './types';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { ContainerInfo, ContainerDetails, ImageInfo, ContainerStats, ContainerStatsEntry, VolumeInfo, NetworkDetails, CreateContainerRequest, ComposeDeployResult, ComposeDeployOptions, ComposePlan, ComposeProjectInfo, ComposeScaleResult, ComposeDownResult, BuildProgressEvent, RegistrySearchResult, TerminalOutputEvent, LogEntry, LogQuery, LogBatchEvent, AggregatedLogBatchEvent, AggregatedLogStreamOptions, LogStreamOptions, LogExportOptions, LogExportProgress, LogExportResult, CopyProgress, ContainerDirEntry, ContainerFile, ContainerChanges, ProcessList, ProcessSnapshotEvent, ProcessStreamOptions, WaitCondition, PullProgressEvent, PruneResult, DockerEvent, DockerEventsStatus, EventFilter, NotificationSettings, ImageUpdateInfo } from './types';
 
 export const dockerApi = {
   async checkConnection(): Promise<boolean> {
@@ -18,12 +19,23 @@ export const dockerApi = {
     return invoke('start_container', { id });
   },
 
-  async stopContainer(id: string): Promise<void> {
-    return invoke('stop_container', { id });
+  // timeout: seconds to wait before the container is killed, the daemon default when omitted
+  async stopContainer(id: string, timeout?: number): Promise<void> {
+    return invoke('stop_container', { id, timeout });
   },
 
-  async restartContainer(id: string): Promise<void> {
-    return invoke('restart_container', { id });
+  async restartContainer(id: string, timeout?: number): Promise<void> {
+    return invoke('restart_container', { id, timeout });
+  },
+
+  // signal: a name such as 'KILL' or 'SIGTERM', or a number; SIGKILL when omitted
+  async killContainer(id: string, signal?: string | number): Promise<void> {
+    return invoke('kill_container', { id, signal: signal === undefined ? undefined : String(signal) });
+  },
+
+  // Resolves to the exit code once the container reaches the condition ('not-running' by default)
+  async waitContainer(id: string, condition?: WaitCondition): Promise<number> {
+    return invoke<number>('wait_container', { id, condition });
   },
 
   async pauseContainer(id: string): Promise<void> {
@@ -38,14 +50,135 @@ export const dockerApi = {
     return invoke('remove_container', { id, force });
   },
 
-  async getContainerLogs(id: string, tail?: string): Promise<string> {
-    return invoke<string>('get_container_logs', { id, tail });
+  async getContainerLogs(id: string, tail?: string, query?: LogQuery): Promise<LogEntry[]> {
+    return invoke<LogEntry[]>('get_container_logs', { id, tail, query });
+  },
+
+  async exportContainerLogs(
+    id: string,
+    path: string,
+    onProgress: (progress: LogExportProgress) => void,
+    options?: LogExportOptions,
+  ): Promise<LogExportResult> {
+    const channel = new Channel<LogExportProgress>();
+    channel.onmessage = onProgress;
+    return invoke<LogExportResult>('export_container_logs', { id, path, options, onProgress: channel });
+  },
+
+  // Follows a container's logs; resolves to the stream id used to stop it
+  async streamContainerLogs(
+    id: string,
+    onLogs: (event: LogBatchEvent) => void,
+    options: LogStreamOptions = {},
+  ): Promise<string> {
+    const channel = new Channel<LogBatchEvent>();
+    channel.onmessage = onLogs;
+    return invoke<string>('stream_container_logs', { id, ...options, onLogs: channel });
+  },
+
+  async streamAggregatedLogs(
+    containerIds: string[],
+    onLogs: (event: AggregatedLogBatchEvent) => void,
+    options: AggregatedLogStreamOptions = {},
+  ): Promise<string> {
+    const channel = new Channel<AggregatedLogBatchEvent>();
+    channel.onmessage = onLogs;
+    return invoke<string>('stream_aggregated_logs', { containerIds, ...options, onLogs: channel });
+  },
+
+  async stopLogStream(streamId: string): Promise<void> {
+    return invoke('stop_log_stream', { streamId });
+  },
+
+  // Events come from one subscription the backend keeps for the lifetime of the app
+  async onDockerEvent(handler: (event: DockerEvent) => void): Promise<UnlistenFn> {
+    return listen<DockerEvent>('docker-event', (event) => handler(event.payload));
+  },
+
+  async onDockerEventsStatus(handler: (status: DockerEventsStatus) => void): Promise<UnlistenFn> {
+    return listen<DockerEventsStatus>('docker-events-status', (event) => handler(event.payload));
+  },
+
+  // Recorded events, oldest first
+  async queryEvents(filter?: EventFilter): Promise<DockerEvent[]> {
+    return invoke<DockerEvent[]>('query_events', { filter });
+  },
+
+  async getNotificationSettings(): Promise<NotificationSettings> {
+    return invoke<NotificationSettings>('get_notification_settings');
+  },
+
+  async setNotificationSettings(settings: NotificationSettings): Promise<void> {
+    return invoke('set_notification_settings', { settings });
   },
 
   async getContainerDetails(id: string): Promise<ContainerDetails> {
     return invoke<ContainerDetails>('get_container_details', { id });
   },
 
+  // Copies a host file or directory like `docker cp`, into containerPath when it is an existing directory
+  async copyToContainer(
+    id: string,
+    hostPath: string,
+    containerPath: string,
+    onProgress: (progress: CopyProgress) => void,
+  ): Promise<void> {
+    const channel = new Channel<CopyProgress>();
+    channel.onmessage = onProgress;
+    return invoke<void>('copy_to_container', { id, hostPath, containerPath, onProgress: channel });
+  },
+
+  async copyFromContainer(
+    id: string,
+    containerPath: string,
+    hostPath: string,
+    onProgress: (progress: CopyProgress) => void,
+  ): Promise<void> {
+    const channel = new Channel<CopyProgress>();
+    channel.onmessage = onProgress;
+    return invoke<void>('copy_from_container', { id, containerPath, hostPath, onProgress: channel });
+  },
+
+  // Works on stopped containers and images without a shell too
+  async listContainerDir(id: string, path: string): Promise<ContainerDirEntry[]> {
+    return invoke<ContainerDirEntry[]>('list_container_dir', { id, path });
+  },
+
+  // Reads the start of a file for previews, 1 MiB unless maxBytes says otherwise
+  async readContainerFile(id: string, path: string, maxBytes?: number): Promise<ContainerFile> {
+    return invoke<ContainerFile>('read_container_file', { id, path, maxBytes });
+  },
+
+  // Paths changed in the writable layer since the image; file sizes only resolve while running
+  async containerChanges(id: string): Promise<ContainerChanges> {
+    return invoke<ContainerChanges>('container_changes', { id });
+  },
+
+  async containerTop(id: string, psArgs?: string): Promise<ProcessList> {
+    return invoke<ProcessList>('container_top', { id, psArgs });
+  },
+
+  // Sends a fresh process list every interval; resolves to the stream id used to stop it
+  async streamContainerTop(
+    id: string,
+    onSnapshot: (event: ProcessSnapshotEvent) => void,
+    options: ProcessStreamOptions = {},
+  ): Promise<string> {
+    const channel = new Channel<ProcessSnapshotEvent>();
+    channel.onmessage = onSnapshot;
+    return invoke<string>('stream_container_top', { id, ...options, onSnapshot: channel });
+  },
+
+  async stopTopStream(streamId: string): Promise<void> {
+    return invoke('stop_top_stream', { streamId });
+  },
+
+  // pid is the host PID containerTop reports; the container needs sh.
+  // signal takes the same forms as killContainer; SIGTERM when omitted
+  async killContainerProcess(id: string, pid: number, signal?: string | number): Promise<void> {
+    return invoke('kill_container_process', { id, pid, signal: signal === undefined ? undefined : String(signal) });
+  },
+
   async getContainerStats(id: string): Promise<ContainerStats> {
     return invoke<ContainerStats>('get_container_stats', { id });
   },
@@ -107,8 +240,52 @@ export const dockerApi = {
   },
 
   // Docker Compose
-  async deployCompose(yamlContent: string): Promise<ComposeDeployResult[]> {
-    return invoke<ComposeDeployResult[]>('deploy_compose', { yamlContent });
+  async deployCompose(
+    yamlContent: string,
+    options: ComposeDeployOptions = {},
+    onBuildProgress?: (event: BuildProgressEvent) => void,
+  ): Promise<ComposeDeployResult[]> {
+    const channel = new Channel<BuildProgressEvent>();
+    if (onBuildProgress) {
+      channel.onmessage = onBuildProgress;
+    }
+    return invoke<ComposeDeployResult[]>('deploy_compose', { yamlContent, ...options, onBuildProgress: channel });
+  },
+
+  async planCompose(yamlContent: string, options: ComposeDeployOptions = {}): Promise<ComposePlan> {
+    return invoke<ComposePlan>('plan_compose', { yamlContent, ...options });
+  },
+
+  async renderComposeConfig(yamlContent: string, options: ComposeDeployOptions = {}): Promise<string> {
+    return invoke<string>('render_compose_config', { yamlContent, ...options });
+  },
+
+  async generateCompose(containerIds: string[]): Promise<string> {
+    return invoke<string>('generate_compose', { containerIds });
+  },
+
+  async listComposeProjects(): Promise<ComposeProjectInfo[]> {
+    return invoke<ComposeProjectInfo[]>('list_compose_projects');
+  },
+
+  async composeStart(project: string): Promise<void> {
+    return invoke('compose_start', { project });
+  },
+
+  async composeStop(project: string): Promise<void> {
+    return invoke('compose_stop', { project });
+  },
+
+  async composeRestart(project: string): Promise<void> {
+    return invoke('compose_restart', { project });
+  },
+
+  async composeScale(project: string, service: string, count: number): Promise<ComposeScaleResult> {
+    return invoke<ComposeScaleResult>('compose_scale', { project, service, count });
+  },
+
+  async composeDown(project: string, removeVolumes: boolean = false): Promise<ComposeDownResult> {
+    return invoke<ComposeDownResult>('compose_down', { project, removeVolumes });
   },
 
   // Image existence check
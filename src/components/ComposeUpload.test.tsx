@@ -91,6 +91,23 @@ describe('ComposeUpload', () => {
     });
   });
 
+  it('passes the project name to the deploy', async () => {
+    mockInvoke.mockResolvedValue([{ service_name: 'web', container_id: 'web123', success: true }]);
+
+    render(<ComposeUpload onClose={onClose} onSuccess={onSuccess} />);
+
+    const file = new File([sampleCompose], 'docker-compose.yml', { type: 'text/yaml' });
+    fireEvent.change(document.querySelector('input[type="file"]')!, { target: { files: [file] } });
+    await vi.waitFor(() => screen.getByText('Preview:'));
+
+    await userEvent.type(screen.getByLabelText('Project name'), ' shop ');
+    await userEvent.click(screen.getByText('Deploy'));
+
+    await vi.waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith('deploy_compose', expect.objectContaining({ yamlContent: sampleCompose, projectName: 'shop' }));
+    });
+  });
+
   it('shows success and failure counts', async () => {
     const deployResults = [
       { service_name: 'web', container_id: 'web123', success: true },
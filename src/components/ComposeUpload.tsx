@@ -10,6 +10,7 @@ interface ComposeUploadProps {
 
 const ComposeUpload: React.FC<ComposeUploadProps> = ({ onClose, onSuccess }) => {
   const [yamlContent, setYamlContent] = useState('');
+  const [projectName, setProjectName] = useState('');
   const [loading, setLoading] = useState(false);
   const [deployResults, setDeployResults] = useState<ComposeDeployResult[]>([]);
   const [error, setError] = useState<string | null>(null);
@@ -40,7 +41,9 @@ const ComposeUpload: React.FC<ComposeUploadProps> = ({ onClose, onSuccess }) =>
     setError(null);
     
     try {
-      const results = await dockerApi.deployCompose(yamlContent);
+      // Uploaded files have no directory to name the project after, so unnamed files need one here
+      const name = projectName.trim();
+      const results = await dockerApi.deployCompose(yamlContent, name ? { projectName: name } : {});
       setDeployResults(results);
       setDeployed(true);
       
@@ -105,6 +108,20 @@ const ComposeUpload: React.FC<ComposeUploadProps> = ({ onClose, onSuccess }) =>
                 />
               </div>
 
+              <div className="file-upload-section">
+                <label htmlFor="compose-project" className="file-upload-label">
+                  Project name
+                </label>
+                <input
+                  type="text"
+                  id="compose-project"
+                  value={projectName}
+                  onChange={(e) => setProjectName(e.target.value)}
+                  placeholder="Required when the file has no name:"
+                  className="file-input"
+                />
+              </div>
+
               {yamlContent && (
                 <div className="yaml-preview">
                   <h3>Preview:</h3>
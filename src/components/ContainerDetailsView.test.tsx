@@ -26,7 +26,26 @@ const runningDetails: ContainerDetails = {
   env: ['NGINX_HOST=localhost', 'NGINX_PORT=80'],
   network_settings: {
     networks: { bridge: '172.17.0.2' },
+    endpoints: {
+      bridge: { ip_address: '172.17.0.2', ip_prefix_len: 16, gateway: '172.17.0.1', mac_address: '02:42:ac:11:00:02', aliases: [] },
+    },
   },
+  health: null,
+  exit_code: 0,
+  oom_killed: false,
+  error: null,
+  started_at: '2024-01-15T10:30:05Z',
+  finished_at: null,
+  restart_count: 0,
+  labels: {},
+  entrypoint: ['/docker-entrypoint.sh'],
+  cmd: ['nginx', '-g', 'daemon off;'],
+  working_dir: null,
+  user: null,
+  restart_policy: { name: 'no', maximum_retry_count: null },
+  resources: { memory: null, memory_reservation: null, memory_swap: null, cpus: null, cpu_shares: null, pids_limit: null },
+  image_id: 'sha256:abc123',
+  image_digests: [],
 };
 
 const stoppedDetails: ContainerDetails = {
@@ -37,16 +56,22 @@ const stoppedDetails: ContainerDetails = {
 
 const onAction = vi.fn();
 
+const stats = {
+  cpu_usage: 12.5,
+  memory_usage: 52428800,
+  memory_limit: 1073741824,
+  memory_percent: 4.9,
+  network_rx: 10240,
+  network_tx: 5120,
+};
+
+const mockCommands = (containerStats: unknown, events: unknown[] = []) => {
+  mockInvoke.mockImplementation((cmd) => Promise.resolve(cmd === 'query_events' ? events : containerStats));
+};
+
 beforeEach(() => {
   vi.clearAllMocks();
-  mockInvoke.mockResolvedValue({
-    cpu_usage: 12.5,
-    memory_usage: 52428800,
-    memory_limit: 1073741824,
-    memory_percent: 4.9,
-    network_rx: 10240,
-    network_tx: 5120,
-  });
+  mockCommands(stats);
 });
 
 describe('ContainerDetailsView', () => {
@@ -109,7 +134,7 @@ describe('ContainerDetailsView', () => {
     });
 
     it('does not show resource usage for stopped containers', async () => {
-      mockInvoke.mockResolvedValue(null);
+      mockCommands(null);
       render(<ContainerDetailsView details={stoppedDetails} onAction={onAction} />);
 
       await waitFor(() => {
@@ -171,9 +196,70 @@ describe('ContainerDetailsView', () => {
     });
 
     it('shows "No networks" when none exist', () => {
-      const noNetworks = { ...runningDetails, network_settings: { networks: {} } };
+      const noNetworks = { ...runningDetails, network_settings: { networks: {}, endpoints: {} } };
       render(<ContainerDetailsView details={noNetworks} onAction={onAction} />);
       expect(screen.getByText('No networks')).toBeInTheDocument();
     });
   });
+
+  describe('lifecycle', () => {
+    it('shows recorded events newest first', async () => {
+      mockCommands(stats, [
+        { kind: 'container', action: 'start', detail: null, id: runningDetails.id, name: 'nginx-web', attributes: {}, timeNano: 1_700_000_000_000_000_000 },
+        { kind: 'container', action: 'die', detail: null, id: runningDetails.id, name: 'nginx-web', attributes: { exitCode: '137' }, timeNano: 1_700_000_100_000_000_000 },
+      ]);
+      render(<ContainerDetailsView details={runningDetails} onAction={onAction} />);
+
+      await waitFor(() => {
+        expect(screen.getByText('exit code 137')).toBeInTheDocument();
+      });
+      expect(mockInvoke).toHaveBeenCalledWith('query_events', {
+        filter: { container: runningDetails.id, kind: 'container', limit: 20 },
+      });
+      const rows = screen.getAllByRole('row').map(row => row.textContent ?? '');
+      const dieRow = rows.findIndex(text => text.includes('die'));
+      const startRow = rows.findIndex(text => text.includes('start'));
+      expect(dieRow).toBeLessThan(startRow);
+    });
+
+    it('shows "No recorded events" without history', async () => {
+      render(<ContainerDetailsView details={runningDetails} onAction={onAction} />);
+      await waitFor(() => {
+        expect(screen.getByText('No recorded events')).toBeInTheDocument();
+      });
+    });
+  });
+
+  describe('inspect details', () => {
+    it('shows command, restart policy and limits', () => {
+      const limited = {
+        ...runningDetails,
+        restart_policy: { name: 'on-failure', maximum_retry_count: 3 },
+        resources: { ...runningDetails.resources, memory: 536870912, cpus: 1.5 },
+        labels: { 'com.example.tier': 'web' },
+      };
+      render(<ContainerDetailsView details={limited} onAction={onAction} />);
+      expect(screen.getByText("/docker-entrypoint.sh nginx -g daemon off;")).toBeInTheDocument();
+      expect(screen.getByText('on-failure (max 3)')).toBeInTheDocument();
+      expect(screen.getByText('512.0 MB')).toBeInTheDocument();
+      expect(screen.getByText('1.5 CPUs')).toBeInTheDocument();
+      expect(screen.getByText('com.example.tier')).toBeInTheDocument();
+      expect(screen.getByText('172.17.0.1')).toBeInTheDocument();
+    });
+
+    it('shows health status and probe output', () => {
+      const unhealthy = {
+        ...runningDetails,
+        health: {
+          status: 'unhealthy',
+          failing_streak: 3,
+          log: [{ start: '2024-01-15T10:31:00Z', end: '2024-01-15T10:31:01Z', exit_code: 1, output: 'connection refused\n' }],
+        },
+      };
+      render(<ContainerDetailsView details={unhealthy} onAction={onAction} />);
+      expect(screen.getByText('Health')).toBeInTheDocument();
+      expect(screen.getByText('unhealthy')).toBeInTheDocument();
+      expect(screen.getByText('connection refused')).toBeInTheDocument();
+    });
+  });
 });
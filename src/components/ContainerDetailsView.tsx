@@ -1,7 +1,7 @@
 import { useState, useEffect } from 'react';
 import { openInBrowser } from '../utils';
 import { useRefreshInterval } from '../hooks/useRefreshInterval';
-import type { ContainerDetails, ContainerStats } from '../types';
+import type { ContainerDetails, ContainerStats, DockerEvent } from '../types';
 import { dockerApi } from '../api';
 import './ContainerDetails.css';
 
@@ -16,6 +16,7 @@ interface ContainerDetailsViewProps {
 const ContainerDetailsView = ({ details, onAction, actionLoading = {}, updateAvailable, onUpdate }: ContainerDetailsViewProps) => {
   const [stats, setStats] = useState<ContainerStats | null>(null);
   const [statsError, setStatsError] = useState<string | null>(null);
+  const [events, setEvents] = useState<DockerEvent[]>([]);
   const refreshMs = useRefreshInterval();
 
   useEffect(() => {
@@ -39,6 +40,19 @@ const ContainerDetailsView = ({ details, onAction, actionLoading = {}, updateAva
     return () => clearInterval(interval);
   }, [details.id, details.state, refreshMs]);
 
+  // Reloaded on state changes so a restart shows up in the timeline
+  useEffect(() => {
+    dockerApi.queryEvents({ container: details.id, kind: 'container', limit: 20 })
+      .then(history => setEvents(history.reverse()))
+      .catch(() => setEvents([]));
+  }, [details.id, details.state]);
+
+  const describeEvent = (event: DockerEvent): string => {
+    if (event.detail) return event.detail;
+    if (event.attributes.exitCode !== undefined) return `exit code ${event.attributes.exitCode}`;
+    return '-';
+  };
+
   const formatBytes = (bytes: number): string => {
     const mb = bytes / (1024 * 1024);
     if (mb < 1024) {
@@ -166,13 +180,114 @@ const ContainerDetailsView = ({ details, onAction, actionLoading = {}, updateAva
                 {details.state}
               </span>
             </div>
+            <div className="info-item">
+              <label>Status:</label>
+              <span>{details.status}</span>
+            </div>
             <div className="info-item">
               <label>Created:</label>
               <span>{new Date(details.created).toLocaleString()}</span>
             </div>
+            {details.started_at && (
+              <div className="info-item">
+                <label>Started:</label>
+                <span>{new Date(details.started_at).toLocaleString()}</span>
+              </div>
+            )}
+            {details.finished_at && details.state.toLowerCase() !== 'running' && (
+              <div className="info-item">
+                <label>Finished:</label>
+                <span>{new Date(details.finished_at).toLocaleString()}</span>
+              </div>
+            )}
+            {details.error && (
+              <div className="info-item">
+                <label>Error:</label>
+                <span>{details.error}</span>
+              </div>
+            )}
+            <div className="info-item">
+              <label>Restarts:</label>
+              <span>{details.restart_count}</span>
+            </div>
+            <div className="info-item">
+              <label>Restart Policy:</label>
+              <span>
+                {details.restart_policy.name}
+                {details.restart_policy.maximum_retry_count !== null && ` (max ${details.restart_policy.maximum_retry_count})`}
+              </span>
+            </div>
+            <div className="info-item">
+              <label>Command:</label>
+              <code>{[...details.entrypoint, ...details.cmd].join(' ') || '-'}</code>
+            </div>
+            {details.working_dir && (
+              <div className="info-item">
+                <label>Working Dir:</label>
+                <span>{details.working_dir}</span>
+              </div>
+            )}
+            {details.user && (
+              <div className="info-item">
+                <label>User:</label>
+                <span>{details.user}</span>
+              </div>
+            )}
+            <div className="info-item">
+              <label>Memory Limit:</label>
+              <span>{details.resources.memory !== null ? formatBytes(details.resources.memory) : 'Unlimited'}</span>
+            </div>
+            <div className="info-item">
+              <label>CPU Limit:</label>
+              <span>{details.resources.cpus !== null ? `${details.resources.cpus} CPUs` : 'Unlimited'}</span>
+            </div>
+            <div className="info-item">
+              <label>Image Digest:</label>
+              <code title={details.image_digests.join('\n') || details.image_id}>
+                {(details.image_digests[0]?.split('@')[1] ?? details.image_id).substring(0, 19)}
+              </code>
+            </div>
           </div>
         </section>
 
+        {details.health && (
+          <section className="details-section">
+            <h3>Health</h3>
+            <div className="info-grid">
+              <div className="info-item">
+                <label>Status:</label>
+                <span className={`status-badge status-${details.health.status}`}>{details.health.status}</span>
+              </div>
+              <div className="info-item">
+                <label>Failing Streak:</label>
+                <span>{details.health.failing_streak}</span>
+              </div>
+            </div>
+            {details.health.log.length > 0 ? (
+              <table className="info-table">
+                <thead>
+                  <tr>
+                    <th>Time</th>
+                    <th>Exit Code</th>
+                    <th>Output</th>
+                  </tr>
+                </thead>
+                <tbody>
+                  {[...details.health.log].reverse().map((probe, idx) => (
+                    <tr key={idx}>
+                      <td>{probe.start ? new Date(probe.start).toLocaleString() : '-'}</td>
+                      <td>{probe.exit_code ?? '-'}</td>
+                      <td><code>{probe.output.trim() || '-'}</code></td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            ) : (
+              <p className="text-muted">No probes yet</p>
+            )}
+          </section>
+        )}
+
         <section className="details-section">
           <h3>Port Mappings</h3>
           {details.ports.length > 0 ? (
@@ -249,19 +364,72 @@ const ContainerDetailsView = ({ details, onAction, actionLoading = {}, updateAva
                 <tr>
                   <th>Network</th>
                   <th>IP Address</th>
+                  <th>Gateway</th>
+                  <th>MAC Address</th>
+                  <th>Aliases</th>
+                </tr>
+              </thead>
+              <tbody>
+                {Object.entries(details.network_settings.networks).map(([name, ip]) => {
+                  const endpoint = details.network_settings.endpoints[name];
+                  return (
+                    <tr key={name}>
+                      <td>{name}</td>
+                      <td>{ip || '-'}</td>
+                      <td>{endpoint?.gateway || '-'}</td>
+                      <td>{endpoint?.mac_address || '-'}</td>
+                      <td>{endpoint?.aliases.join(', ') || '-'}</td>
+                    </tr>
+                  );
+                })}
+              </tbody>
+            </table>
+          ) : (
+            <p className="text-muted">No networks</p>
+          )}
+        </section>
+
+        <section className="details-section full-width">
+          <h3>Lifecycle</h3>
+          {events.length > 0 ? (
+            <table className="info-table">
+              <thead>
+                <tr>
+                  <th>Time</th>
+                  <th>Event</th>
+                  <th>Details</th>
                 </tr>
               </thead>
               <tbody>
-                {Object.entries(details.network_settings.networks).map(([name, ip]) => (
-                  <tr key={name}>
-                    <td>{name}</td>
-                    <td>{ip || '-'}</td>
+                {events.map((event) => (
+                  <tr key={`${event.timeNano}-${event.action}`}>
+                    <td>{new Date(event.timeNano / 1e6).toLocaleString()}</td>
+                    <td>{event.action}</td>
+                    <td>{describeEvent(event)}</td>
                   </tr>
                 ))}
               </tbody>
             </table>
           ) : (
-            <p className="text-muted">No networks</p>
+            <p className="text-muted">No recorded events</p>
+          )}
+        </section>
+
+        <section className="details-section full-width">
+          <h3>Labels</h3>
+          {Object.keys(details.labels).length > 0 ? (
+            <div className="env-list">
+              {Object.entries(details.labels).sort(([a], [b]) => a.localeCompare(b)).map(([key, value]) => (
+                <div key={key} className="env-item">
+                  <code>
+                    <span className="env-key">{key}</span>=
+                    <span className="env-value">{value}</span>
+                  </code>
+                </div>
+              ))}
+            </div>
+          ) : (
+            <p className="text-muted">No labels</p>
           )}
         </section>
 
@@ -24,8 +24,8 @@ const LogsView = ({ containerId, onToast }: LogsViewProps) => {
     setLoading(true);
     setError(null);
     try {
-      const containerLogs = await dockerApi.getContainerLogs(containerId, tailLines);
-      setLogs(containerLogs);
+      const entries = await dockerApi.getContainerLogs(containerId, tailLines);
+      setLogs(entries.map(entry => entry.line).join('\n'));
     } catch (err) {
       setError(err instanceof Error ? err.message : 'Failed to load logs');
     } finally {
@@ -23,8 +23,45 @@ export interface MountInfo {
   rw: boolean;
 }
 
+export interface NetworkEndpoint {
+  ip_address: string;
+  ip_prefix_len: number | null;
+  gateway: string;
+  mac_address: string;
+  aliases: string[];
+}
+
 export interface NetworkInfo {
-  networks: Record<string, string>;
+  networks: Record<string, string>; // Network name to IP address
+  endpoints: Record<string, NetworkEndpoint>;
+}
+
+export interface HealthProbe {
+  start: string | null;
+  end: string | null;
+  exit_code: number | null;
+  output: string;
+}
+
+export interface HealthInfo {
+  status: string; // 'starting' | 'healthy' | 'unhealthy'
+  failing_streak: number;
+  log: HealthProbe[]; // Most recent probes, oldest first
+}
+
+export interface RestartPolicyInfo {
+  name: string; // 'no' | 'always' | 'unless-stopped' | 'on-failure'
+  maximum_retry_count: number | null;
+}
+
+// null means no limit
+export interface ResourceLimits {
+  memory: number | null;
+  memory_reservation: number | null;
+  memory_swap: number | null;
+  cpus: number | null;
+  cpu_shares: number | null;
+  pids_limit: number | null;
 }
 
 export interface ContainerDetails {
@@ -38,6 +75,22 @@ export interface ContainerDetails {
   mounts: MountInfo[];
   env: string[];
   network_settings: NetworkInfo;
+  health: HealthInfo | null; // null without a healthcheck
+  exit_code: number | null;
+  oom_killed: boolean;
+  error: string | null;
+  started_at: string | null;
+  finished_at: string | null;
+  restart_count: number;
+  labels: Record<string, string>;
+  entrypoint: string[];
+  cmd: string[];
+  working_dir: string | null;
+  user: string | null;
+  restart_policy: RestartPolicyInfo;
+  resources: ResourceLimits;
+  image_id: string;
+  image_digests: string[]; // Empty for locally built images
 }
 
 export interface ImageInfo {
@@ -81,6 +134,74 @@ export interface CreateContainerRequest {
   command?: string[];
   memory_limit?: number; // Memory limit in bytes
   cpu_quota?: number;    // CPU quota (100000 = 1 CPU core)
+  labels?: Record<string, string>;
+  network_aliases?: string[];
+  ipv4_address?: string; // Static addresses on `network`
+  ipv6_address?: string;
+  extra_networks?: NetworkEndpointRequest[]; // Connected after creation
+  port_bindings?: PortBindingRequest[];
+  mounts?: MountRequest[];
+  healthcheck?: HealthcheckRequest;
+  user?: string;
+  working_dir?: string;
+  entrypoint?: string[];
+  hostname?: string;
+  extra_hosts?: string[]; // ["host:ip"]
+  dns?: string[];
+  cap_add?: string[];
+  cap_drop?: string[];
+  privileged?: boolean;
+  devices?: string[]; // ["/dev/host[:/dev/container[:rwm]]"]
+  tmpfs?: Record<string, string>; // container path -> mount options
+  ulimits?: UlimitRequest[];
+  shm_size?: number; // Bytes
+  stop_signal?: string;
+  stop_timeout?: number; // Seconds
+  init?: boolean;
+  read_only?: boolean;
+  log_driver?: string;
+  log_options?: Record<string, string>;
+}
+
+export interface NetworkEndpointRequest {
+  name: string;
+  aliases?: string[];
+  ipv4_address?: string;
+  ipv6_address?: string;
+}
+
+export interface HealthcheckRequest {
+  test: string[]; // ["CMD", ...], ["CMD-SHELL", "..."] or ["NONE"]
+  interval?: number; // Durations in nanoseconds
+  timeout?: number;
+  retries?: number;
+  start_period?: number;
+  start_interval?: number;
+}
+
+export interface UlimitRequest {
+  name: string;
+  soft: number;
+  hard: number;
+}
+
+export interface PortBindingRequest {
+  container_port: string; // "80/tcp"
+  host_ip?: string;
+  host_port?: string; // "8080", "9000-9010", or omitted to let Docker pick
+}
+
+export interface MountRequest {
+  mount_type: 'bind' | 'volume' | 'tmpfs';
+  source?: string;
+  target: string;
+  read_only?: boolean;
+  bind_propagation?: string;
+  selinux_label?: 'z' | 'Z';
+  volume_nocopy?: boolean;
+  volume_subpath?: string;
+  tmpfs_size?: number; // bytes
+  tmpfs_mode?: number;
 }
 
 export interface ComposeDeployResult {
@@ -88,6 +209,54 @@ export interface ComposeDeployResult {
   container_id?: string;
   success: boolean;
   error?: string;
+  unset_variables: string[]; // Variables that were not set and resolved to an empty string
+  action?: PlanAction | null; // What happened to the service's container
+  warnings: string[]; // Unsupported keys, and secret/config uid, gid or mode that were ignored
+}
+
+export type PlanAction = 'create' | 'recreate' | 'unchanged' | 'orphaned' | 'remove';
+
+export interface ServicePlan {
+  service_name: string;
+  action: PlanAction;
+  container_id: string | null;
+  container_name: string | null;
+  changes: string[]; // Settings that differ from the existing container, e.g. "image"
+  error: string | null;
+}
+
+export interface ComposePlan {
+  project: string;
+  services: ServicePlan[];
+  networks_to_create: string[];
+  volumes_to_create: string[];
+}
+
+export interface ComposeDeployOptions {
+  overrideContents?: string[]; // Override files merged over the main file in order, e.g. docker-compose.override.yml
+  profiles?: string[]; // Active profiles; defaults to COMPOSE_PROFILES
+  projectName?: string; // Overrides name:; without either, the name comes from workingDir
+  envContent?: string; // Contents of a .env file used for ${VAR} interpolation
+  workingDir?: string; // Project directory, used to resolve relative paths such as env_file
+}
+
+export interface ComposeProjectInfo {
+  name: string;
+  status: string; // e.g. "running(2), exited(1)"
+  services: string[];
+  containers: ContainerInfo[];
+  config_files?: string;
+}
+
+export interface ComposeScaleResult {
+  containers_created: string[];
+  containers_removed: string[];
+}
+
+export interface ComposeDownResult {
+  containers_removed: string[];
+  networks_removed: string[];
+  volumes_removed: string[];
 }
 
 export interface RegistrySearchResult {
@@ -98,6 +267,15 @@ export interface RegistrySearchResult {
   is_automated: boolean;
 }
 
+export interface BuildProgressEvent {
+  service: string;
+  image: string;
+  stream: string | null; // Build output line
+  status: string | null;
+  complete: boolean;
+  error: string | null;
+}
+
 export interface PullProgressEvent {
   image: string;
   id: string | null;
@@ -113,6 +291,152 @@ export interface TerminalOutputEvent {
   data: string;
 }
 
+export type LogStream = 'stdout' | 'stderr';
+
+export interface LogEntry {
+  stream: LogStream;
+  timestamp: string | null; // RFC 3339, only when requested
+  line: string;
+}
+
+export interface LogQuery {
+  since?: string; // RFC 3339, Unix timestamp, or a duration back from now like "15m"
+  until?: string;
+  timestamps?: boolean;
+  stream?: LogStream; // Both streams when omitted
+  filter?: string; // Substring, or regular expression with `regex`
+  regex?: boolean;
+  case_sensitive?: boolean;
+}
+
+export interface LogBatchEvent {
+  streamId: string;
+  lines: LogEntry[];
+  ended: boolean; // The container stopped or the stream failed
+  error: string | null;
+}
+
+export interface ContainerLogEntry extends LogEntry {
+  containerId: string;
+  containerName: string;
+  colorIndex: number; // Stable per container; pick a colour with colorIndex % palette.length
+}
+
+export interface AggregatedLogBatchEvent {
+  streamId: string;
+  lines: ContainerLogEntry[]; // Ordered by timestamp across containers
+  ended: boolean; // All containers were removed or the stream failed
+  error: string | null;
+}
+
+export interface AggregatedLogStreamOptions {
+  project?: string; // Also follow the project's containers, including ones created later
+  tail?: string; // Lines of history per container, default "100"
+}
+
+export type LogExportFormat = 'text' | 'jsonl';
+
+export interface LogExportOptions extends LogQuery {
+  format?: LogExportFormat; // Default 'text'; 'jsonl' always includes stream and timestamp
+  gzip?: boolean;
+  tail?: string; // Default: all lines
+}
+
+export interface LogExportProgress {
+  linesWritten: number;
+  bytesWritten: number; // Before compression
+}
+
+export interface LogExportResult {
+  path: string;
+  linesWritten: number;
+  fileSize: number;
+}
+
+export interface CopyProgress {
+  bytesCopied: number; // Archive bytes, tar headers and padding included
+  totalBytes: number | null; // Only known when copying into a container
+  done: boolean;
+}
+
+export type ContainerFileKind = 'file' | 'directory' | 'symlink' | 'other';
+
+export interface ContainerDirEntry {
+  name: string;
+  kind: ContainerFileKind;
+  size: number;
+  mode: number; // Permission bits, e.g. 0o755
+  mtime: number; // Unix timestamp
+  linkTarget: string | null;
+}
+
+export interface ContainerFile {
+  path: string;
+  size: number; // Whole file, even when truncated
+  content: string; // Empty for binary files
+  truncated: boolean;
+  binary: boolean;
+}
+
+export type ContainerChangeKind = 'added' | 'modified' | 'deleted';
+
+export interface ContainerChangeNode {
+  name: string;
+  path: string;
+  kind: ContainerChangeKind;
+  size: number | null; // File size, or total of the resolved sizes below a directory
+  children: ContainerChangeNode[];
+}
+
+export interface ContainerChanges {
+  added: number;
+  modified: number;
+  deleted: number;
+  sizeRw: number | null; // Writable layer
+  sizeRootFs: number | null;
+  tree: ContainerChangeNode[];
+}
+
+export interface ProcessRow {
+  pid: number; // As the host sees it; pass it to killContainerProcess
+  ppid: number | null;
+  user: string | null;
+  cpuPercent: number | null;
+  memPercent: number | null;
+  vsz: number | null; // KiB
+  rss: number | null; // KiB
+  tty: string | null;
+  stat: string | null;
+  started: string | null;
+  time: string | null; // Cumulative CPU time
+  command: string;
+  columns: Record<string, string>; // Every ps column by title
+}
+
+export interface ProcessList {
+  titles: string[];
+  processes: ProcessRow[];
+}
+
+export interface ProcessSnapshotEvent {
+  streamId: string;
+  snapshot: ProcessList | null;
+  ended: boolean; // Last event; the container stopped or listing failed
+  error: string | null;
+}
+
+export interface ProcessStreamOptions {
+  psArgs?: string; // Default "aux"
+  intervalMs?: number; // Default 2000, at least 500
+}
+
+export type WaitCondition = 'not-running' | 'next-exit' | 'removed';
+
+export interface LogStreamOptions {
+  tail?: string; // Lines of history to send first ("all" for everything), default "100"
+  since?: number; // Unix timestamp
+}
+
 export interface PruneResult {
   containers_removed: number;
   images_removed: number;
@@ -140,3 +464,48 @@ export interface ImageUpdateInfo {
   hasUpdate: boolean;
   error: string | null;
 }
+
+export type DockerEventKind = 'container' | 'image' | 'volume' | 'network';
+
+export interface DockerEvent {
+  kind: DockerEventKind;
+  action: string; // 'create', 'start', 'die', 'oom', 'health_status', 'destroy', 'pull', ...
+  detail: string | null; // e.g. 'healthy' for 'health_status: healthy'
+  id: string;
+  name: string | null;
+  attributes: Record<string, string>;
+  timeNano: number;
+}
+
+export interface DockerEventsStatus {
+  connected: boolean;
+  error: string | null;
+}
+
+export interface EventFilter {
+  container?: string; // Container id, id prefix or name
+  kind?: DockerEventKind;
+  action?: string;
+  since?: string; // RFC 3339, Unix timestamp, or relative like '24h'
+  until?: string;
+  limit?: number; // Most recent events to return, default 1000
+}
+
+export interface NotificationTriggers {
+  crash: boolean; // Exited with a non-zero code
+  oom: boolean;
+  unhealthy: boolean;
+}
+
+export interface NotificationRule {
+  container?: string; // Container name
+  label?: string; // 'key' or 'key=value'
+  triggers: NotificationTriggers;
+}
+
+export interface NotificationSettings {
+  enabled: boolean;
+  rateLimitSecs: number; // Least time between notifications about the same container
+  defaults: NotificationTriggers; // For containers no rule matches
+  rules: NotificationRule[]; // The first matching rule decides
+}